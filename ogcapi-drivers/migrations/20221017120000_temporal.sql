@@ -0,0 +1,8 @@
+-- Functions
+
+-- Timestamp of a RFC 3339 string. Immutable, unlike the cast it wraps, as the
+-- string carries its offset and hence does not depend on the session time
+-- zone. This allows indexes on the temporal properties of features.
+CREATE FUNCTION meta.to_timestamptz(text) RETURNS timestamptz
+    LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE
+    AS $$ SELECT $1::timestamptz $$;
@@ -0,0 +1,31 @@
+-- Functions
+
+-- Notify the instances caching collections about changes, with the id of the
+-- collection or an empty payload for all of them.
+CREATE FUNCTION meta.notify_collections() RETURNS trigger
+    LANGUAGE plpgsql
+    AS $$
+BEGIN
+    IF TG_LEVEL = 'STATEMENT' THEN
+        PERFORM pg_notify('collections', '');
+    ELSIF TG_OP = 'INSERT' THEN
+        PERFORM pg_notify('collections', NEW.id);
+    ELSE
+        PERFORM pg_notify('collections', OLD.id);
+        IF TG_OP = 'UPDATE' AND NEW.id <> OLD.id THEN
+            PERFORM pg_notify('collections', NEW.id);
+        END IF;
+    END IF;
+    RETURN NULL;
+END
+$$;
+
+-- Triggers
+
+CREATE TRIGGER notify_collections
+    AFTER INSERT OR UPDATE OR DELETE ON meta.collections
+    FOR EACH ROW EXECUTE FUNCTION meta.notify_collections();
+
+CREATE TRIGGER notify_collections_truncate
+    AFTER TRUNCATE ON meta.collections
+    FOR EACH STATEMENT EXECUTE FUNCTION meta.notify_collections();
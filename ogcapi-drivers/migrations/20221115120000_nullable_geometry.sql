@@ -0,0 +1,14 @@
+-- Tables
+
+-- Features may have a null geometry, as in GeoJSON.
+DO $$
+DECLARE
+    id text;
+BEGIN
+    FOR id IN SELECT c.id FROM meta.collections c LOOP
+        IF to_regclass(format('items.%I', id)) IS NOT NULL THEN
+            EXECUTE format('ALTER TABLE items.%I ALTER COLUMN geom DROP NOT NULL', id);
+        END IF;
+    END LOOP;
+END
+$$;
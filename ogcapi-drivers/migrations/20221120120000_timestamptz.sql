@@ -0,0 +1,52 @@
+-- Functions
+
+-- Timestamp of a RFC 3339 string, or NULL for anything else. Only strings
+-- with an explicit offset are converted, as those without, like plain dates,
+-- depend on the session time zone. This keeps the function immutable for the
+-- indexes and generated columns on the temporal properties of features, and
+-- features with other values in them can still be stored.
+CREATE OR REPLACE FUNCTION meta.to_timestamptz(text) RETURNS timestamptz
+    LANGUAGE plpgsql IMMUTABLE STRICT PARALLEL SAFE
+    AS $$
+BEGIN
+    IF $1 !~ '^\d{4}-\d{2}-\d{2}[Tt ]\d{2}:\d{2}:\d{2}(\.\d+)?([Zz]|[+-]\d{2}:\d{2})$' THEN
+        RETURN NULL;
+    END IF;
+    RETURN $1::timestamptz;
+EXCEPTION
+    WHEN data_exception THEN
+        RETURN NULL;
+END
+$$;
+
+-- Tables
+
+-- Recompute the generated columns and the indexes of the temporal properties
+-- which hold values of the previous function.
+DO $$
+DECLARE
+    r record;
+BEGIN
+    FOR r IN
+        SELECT c.relname, a.attname
+        FROM pg_attribute a
+        JOIN pg_class c ON c.oid = a.attrelid
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = 'items' AND a.attgenerated = 's' AND NOT a.attisdropped
+    LOOP
+        EXECUTE format(
+            'UPDATE items.%I SET properties = properties WHERE %I IS DISTINCT FROM meta.to_timestamptz(properties ->> %L)',
+            r.relname, r.attname, r.attname
+        );
+    END LOOP;
+
+    FOR r IN
+        SELECT c.relname
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = 'items' AND c.relkind = 'r'
+    LOOP
+        EXECUTE format('REINDEX TABLE items.%I', r.relname);
+    END LOOP;
+END
+$$;
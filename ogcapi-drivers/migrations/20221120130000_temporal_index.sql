@@ -0,0 +1,40 @@
+-- Functions
+
+-- Name of the index of a temporal property of a collection. Hashed, as the
+-- collection id and the property name together may exceed the length limit
+-- of identifiers, and truncated names could collide.
+CREATE FUNCTION meta.temporal_index(collection text, property text) RETURNS text
+    LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE
+    AS $$ SELECT 'temporal_' || md5(collection || chr(31) || property) $$;
+
+-- Indexes
+
+-- Rename the indexes named `{collection}_{property}_idx` so far.
+DO $$
+DECLARE
+    r record;
+BEGIN
+    FOR r IN
+        SELECT c.id, COALESCE(p.value ->> 'property', p.value ->> 'column') AS property
+        FROM meta.collections c,
+        jsonb_each(
+            CASE WHEN jsonb_typeof(c.collection -> 'temporalProperties') = 'object'
+            THEN c.collection -> 'temporalProperties'
+            ELSE '{
+                "instant": {"property": "datetime"},
+                "start": {"property": "start_datetime"},
+                "end": {"property": "end_datetime"}
+            }'::jsonb
+            END
+        ) AS p
+    LOOP
+        IF r.property IS NOT NULL THEN
+            EXECUTE format(
+                'ALTER INDEX IF EXISTS items.%I RENAME TO %I',
+                r.id || '_' || r.property || '_idx',
+                meta.temporal_index(r.id, r.property)
+            );
+        END IF;
+    END LOOP;
+END
+$$;
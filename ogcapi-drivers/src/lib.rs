@@ -3,12 +3,18 @@ pub mod postgres;
 #[cfg(feature = "s3")]
 pub mod s3;
 
+use std::fmt;
+
+use futures_util::stream::{self, BoxStream, StreamExt};
+
 #[cfg(feature = "stac")]
 use ogcapi_types::stac::SearchParams;
 use ogcapi_types::{
-    common::{Collection, Collections, Crs, Query as CollectionQuery},
+    common::{Collection, Collections, Crs, CrsDefinition, Query as CollectionQuery},
     edr::{Query as EdrQuery, QueryType},
-    features::{Feature, FeatureCollection, Query as FeatureQuery},
+    features::{
+        BulkMode, BulkReport, Feature, FeatureCollection, Query as FeatureQuery, Queryables,
+    },
     processes::{Results, StatusInfo},
     styles::Styles,
     tiles::TileMatrixSet,
@@ -21,11 +27,92 @@ pub trait CollectionTransactions: Send + Sync {
 
     async fn read_collection(&self, id: &str) -> anyhow::Result<Option<Collection>>;
 
+    /// Replace the collection metadata. A changed storage crs reprojects the
+    /// stored geometries.
     async fn update_collection(&self, collection: &Collection) -> anyhow::Result<()>;
 
+    /// Move the collection `id` and its items to the id of `collection`, and
+    /// replace its metadata like [`CollectionTransactions::update_collection`].
+    /// Either both apply or neither does.
+    async fn rename_collection(&self, id: &str, collection: &Collection) -> anyhow::Result<()>;
+
     async fn delete_collection(&self, id: &str) -> anyhow::Result<()>;
 
     async fn list_collections(&self, query: &CollectionQuery) -> anyhow::Result<Collections>;
+
+    /// Recompute the extent of a collection from its items, and with
+    /// `summaries` also the STAC summaries of the item properties. Returns the
+    /// updated collection if it exists.
+    async fn update_extent(&self, id: &str, summaries: bool) -> anyhow::Result<Option<Collection>>;
+}
+
+/// Outcome of a modification that may be conditional on an entity tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The modification was applied
+    Applied,
+    /// The target does not exist
+    NotFound,
+    /// The entity tag of the target does not match
+    PreconditionFailed,
+}
+
+/// Error of a coordinate reference system the driver does not know
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownCrs(pub Crs);
+
+impl fmt::Display for UnknownCrs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown CRS `{}`", self.0)
+    }
+}
+
+impl std::error::Error for UnknownCrs {}
+
+/// Error of a filter expression which parses but cannot be evaluated, e.g.
+/// a spatial operator on a property that is not a geometry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidFilter(pub String);
+
+impl fmt::Display for InvalidFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid filter: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidFilter {}
+
+/// Error of a continuation token which does not fit the sort keys of the
+/// query, e.g. because it was tampered with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidToken(pub String);
+
+impl fmt::Display for InvalidToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid token: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidToken {}
+
+/// Error of collection metadata which the driver cannot store, e.g. a
+/// temporal property in a column clashing with one of the driver
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidCollection(pub String);
+
+impl fmt::Display for InvalidCollection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid collection: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidCollection {}
+
+/// Features of a page as they are read, along with the number of features
+/// matching the query
+pub struct FeatureStream {
+    pub number_matched: Option<u64>,
+    pub features: BoxStream<'static, anyhow::Result<Feature>>,
 }
 
 /// Trait for `Feature` transactions
@@ -33,21 +120,75 @@ pub trait CollectionTransactions: Send + Sync {
 pub trait FeatureTransactions: Send + Sync {
     async fn create_feature(&self, feature: &Feature) -> anyhow::Result<String>;
 
+    /// Write many features at once, either all or none of them.
+    async fn create_features(
+        &self,
+        collection: &str,
+        features: &[Feature],
+        mode: BulkMode,
+    ) -> anyhow::Result<BulkReport>;
+
+    /// Read a single feature, with the geometry in the `crs` of the query and
+    /// optionally simplified.
     async fn read_feature(
         &self,
         collection: &str,
         id: &str,
-        crs: &Crs,
+        query: &FeatureQuery,
     ) -> anyhow::Result<Option<Feature>>;
-    async fn update_feature(&self, feature: &Feature) -> anyhow::Result<()>;
 
-    async fn delete_feature(&self, collection: &str, id: &str) -> anyhow::Result<()>;
+    /// Like `read_feature`, along with the entity tag of the same state of the
+    /// stored feature
+    async fn read_feature_with_etag(
+        &self,
+        collection: &str,
+        id: &str,
+        query: &FeatureQuery,
+    ) -> anyhow::Result<Option<(Feature, String)>>;
+
+    /// Entity tag of the stored feature, changes with every modification
+    async fn feature_etag(&self, collection: &str, id: &str) -> anyhow::Result<Option<String>>;
+
+    /// Replace a feature. With `keep_geometry` the stored geometry is left
+    /// unchanged instead, for patches which do not touch it.
+    async fn update_feature(
+        &self,
+        feature: &Feature,
+        if_match: Option<&str>,
+        keep_geometry: bool,
+    ) -> anyhow::Result<Outcome>;
+
+    async fn delete_feature(
+        &self,
+        collection: &str,
+        id: &str,
+        if_match: Option<&str>,
+    ) -> anyhow::Result<Outcome>;
 
     async fn list_items(
         &self,
         collection: &str,
         query: &FeatureQuery,
     ) -> anyhow::Result<FeatureCollection>;
+
+    /// Like `list_items`, but without holding all features of the page in
+    /// memory if the driver supports it.
+    async fn stream_items(
+        &self,
+        collection: &str,
+        query: &FeatureQuery,
+    ) -> anyhow::Result<FeatureStream> {
+        let fc = self.list_items(collection, query).await?;
+
+        Ok(FeatureStream {
+            number_matched: fc.number_matched,
+            features: stream::iter(fc.features.into_iter().map(Ok)).boxed(),
+        })
+    }
+
+    /// Queryable properties of a collection, inferred from the features and
+    /// refined by the `queryables` member of the collection metadata.
+    async fn queryables(&self, collection: &str) -> anyhow::Result<Option<Queryables>>;
 }
 
 /// Trait for `STAC` search
@@ -68,6 +209,16 @@ pub trait EdrQuerier: Send + Sync {
     ) -> anyhow::Result<FeatureCollection>;
 }
 
+/// Trait for the catalogue of coordinate reference systems
+#[async_trait::async_trait]
+pub trait CrsCatalogue: Send + Sync {
+    /// Union of the crs of all collections
+    async fn list_crs(&self) -> anyhow::Result<Vec<Crs>>;
+
+    /// Definition of a crs, `None` if the backend does not know it
+    async fn crs_definition(&self, crs: &Crs) -> anyhow::Result<Option<CrsDefinition>>;
+}
+
 /// Trait for `Processes` jobs
 #[async_trait::async_trait]
 pub trait JobHandler: Send + Sync {
@@ -0,0 +1,120 @@
+use ogcapi_types::common::{AxisOrder, Bbox, Crs};
+
+use super::{sql::Params, Db};
+
+impl Db {
+    /// Predicate selecting the items of an `items` table intersecting the bbox.
+    ///
+    /// Coordinates are swapped from the axis order of the crs, e.g. latitude
+    /// first for geographic EPSG crs.
+    ///
+    /// A geographic bbox with its west edge east of its east edge crosses the
+    /// antimeridian and is split in two there.
+    ///
+    /// The vertical extent of a 3D bbox only applies to geometries with z
+    /// coordinates.
+    pub(crate) async fn bbox_predicate(
+        &self,
+        bbox: &Bbox,
+        crs: &Crs,
+        storage_srid: i32,
+        params: &mut Params,
+    ) -> anyhow::Result<String> {
+        let parts = self.bbox_parts(bbox, crs).await?;
+
+        let srid = params.push(self.srid(crs).await?);
+        let storage_srid = params.push(storage_srid);
+
+        let predicates = parts
+            .iter()
+            .map(|bbox| {
+                let (lower, upper) = (bbox.lower(), bbox.upper());
+                let mut predicate = format!(
+                    "items.geom && ST_Transform(ST_MakeEnvelope({}, {}, {}, {}, {srid}), {storage_srid})",
+                    params.push(lower[0]),
+                    params.push(lower[1]),
+                    params.push(upper[0]),
+                    params.push(upper[1]),
+                );
+                if let (Some(min), Some(max)) = (lower.get(2), upper.get(2)) {
+                    predicate.push_str(&format!(
+                        " AND (NOT ST_HasZ(items.geom) OR (ST_ZMax(items.geom) >= {} AND ST_ZMin(items.geom) <= {}))",
+                        params.push(*min),
+                        params.push(*max)
+                    ));
+                }
+                format!("({predicate})")
+            })
+            .collect::<Vec<String>>();
+
+        Ok(format!("({})", predicates.join(" OR ")))
+    }
+
+    /// Predicate selecting the collections with a spatial extent intersecting
+    /// the bbox, with the same axis order and antimeridian handling as
+    /// [`Db::bbox_predicate`]. Collections without a spatial extent do not match.
+    pub(crate) async fn extent_predicate(
+        &self,
+        bbox: &Bbox,
+        crs: &Crs,
+        params: &mut Params,
+    ) -> anyhow::Result<String> {
+        let parts = self.bbox_parts(bbox, crs).await?;
+
+        let srid = params.push(self.srid(crs).await?);
+
+        let envelopes = parts
+            .iter()
+            .map(|bbox| {
+                let (lower, upper) = (bbox.lower(), bbox.upper());
+                format!(
+                    "ST_Transform(ST_MakeEnvelope({}, {}, {}, {}, {srid}), 4326)",
+                    params.push(lower[0]),
+                    params.push(lower[1]),
+                    params.push(upper[0]),
+                    params.push(upper[1]),
+                )
+            })
+            .collect::<Vec<String>>();
+
+        // the extents are longitude/latitude bboxes with 4 or 6 coordinates
+        Ok(format!(
+            r#"EXISTS (
+                SELECT
+                FROM jsonb_array_elements(collection #> '{{extent,spatial,bbox}}') AS extent(bbox),
+                    LATERAL (
+                        SELECT
+                            (bbox ->> 0)::float8 AS xmin,
+                            (bbox ->> 1)::float8 AS ymin,
+                            (bbox ->> (jsonb_array_length(bbox) / 2))::float8 AS xmax,
+                            (bbox ->> (jsonb_array_length(bbox) / 2 + 1))::float8 AS ymax
+                    ) AS corners,
+                    unnest(ARRAY[{}]) AS query(envelope)
+                WHERE CASE
+                    WHEN xmin > xmax
+                    THEN ST_MakeEnvelope(xmin, ymin, 180, ymax, 4326) && envelope
+                        OR ST_MakeEnvelope(-180, ymin, xmax, ymax, 4326) && envelope
+                    ELSE ST_MakeEnvelope(xmin, ymin, xmax, ymax, 4326) && envelope
+                END
+            )"#,
+            envelopes.join(", ")
+        ))
+    }
+
+    /// Parts of a bbox in x/y order, split at the antimeridian if the crs is
+    /// geographic
+    async fn bbox_parts(&self, bbox: &Bbox, crs: &Crs) -> anyhow::Result<Vec<Bbox>> {
+        let spatial_ref = self.spatial_ref(crs).await?;
+
+        let bbox = match spatial_ref.axis_order {
+            AxisOrder::XY => bbox.to_owned(),
+            AxisOrder::YX => bbox.swap_axes(),
+        };
+
+        Ok(if spatial_ref.geographic {
+            bbox.split_antimeridian()
+        } else {
+            vec![bbox]
+        })
+    }
+}
@@ -0,0 +1,112 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use sqlx::postgres::{PgListener, PgPool};
+
+use ogcapi_types::common::Collection;
+
+/// Channel of the notifications about changed collections, see the trigger
+/// on `meta.collections`
+const CHANNEL: &str = "collections";
+
+/// Delay before subscribing again after the connection was lost
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// In-process cache of the collection documents, invalidated by the
+/// notifications of the database so that all instances sharing it stay
+/// consistent. Collections are only cached while subscribed to them.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Cache {
+    inner: Arc<RwLock<Inner>>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    subscribed: bool,
+    /// Incremented with every invalidation, to detect reads racing with it
+    generation: u64,
+    collections: HashMap<String, Collection>,
+}
+
+impl Cache {
+    /// Cached collection along with the generation to pass to
+    /// [`Cache::insert`] after reading it from the database instead
+    pub(crate) fn get(&self, id: &str) -> (Option<Collection>, u64) {
+        let inner = self.inner.read().unwrap();
+        (inner.collections.get(id).cloned(), inner.generation)
+    }
+
+    /// Cache a collection read at `generation`, unless it has been
+    /// invalidated meanwhile
+    pub(crate) fn insert(&self, collection: &Collection, generation: u64) {
+        let mut inner = self.inner.write().unwrap();
+        if inner.subscribed && inner.generation == generation {
+            inner
+                .collections
+                .insert(collection.id.to_owned(), collection.to_owned());
+        }
+    }
+
+    /// Drop a collection, or all of them for an empty `id`
+    pub(crate) fn invalidate(&self, id: &str) {
+        let mut inner = self.inner.write().unwrap();
+        inner.generation += 1;
+        if id.is_empty() {
+            inner.collections.clear();
+        } else {
+            inner.collections.remove(id);
+        }
+    }
+
+    fn set_subscribed(&self, subscribed: bool) {
+        let mut inner = self.inner.write().unwrap();
+        inner.subscribed = subscribed;
+        inner.generation += 1;
+        inner.collections.clear();
+    }
+
+    /// Subscribe to the notifications about changed collections, and keep
+    /// listening in the background until the pool is closed. The
+    /// subscription holds one connection of the pool.
+    pub(crate) async fn subscribe(&self, pool: &PgPool) -> Result<(), sqlx::Error> {
+        let mut listener = listen(pool).await?;
+        self.set_subscribed(true);
+
+        let cache = self.to_owned();
+        let pool = pool.to_owned();
+
+        tokio::spawn(async move {
+            loop {
+                // notifications are missed while the connection is lost
+                while let Ok(Some(notification)) = listener.try_recv().await {
+                    cache.invalidate(notification.payload());
+                }
+
+                cache.set_subscribed(false);
+
+                listener = loop {
+                    if pool.is_closed() {
+                        return;
+                    }
+                    match listen(&pool).await {
+                        Ok(listener) => break listener,
+                        Err(_) => tokio::time::sleep(RETRY_DELAY).await,
+                    }
+                };
+
+                cache.set_subscribed(true);
+            }
+        });
+
+        Ok(())
+    }
+}
+
+async fn listen(pool: &PgPool) -> Result<PgListener, sqlx::Error> {
+    let mut listener = PgListener::connect_with(pool).await?;
+    listener.listen(CHANNEL).await?;
+    Ok(listener)
+}
@@ -1,55 +1,62 @@
-use ogcapi_types::common::{Collection, Collections, Query};
+use anyhow::ensure;
+use serde_json::{json, Value};
+use sqlx::{types::Json, PgConnection};
+
+use ogcapi_types::common::{
+    AxisOrder, Bbox, Collection, Collections, Crs, Extent, Query, SpatialExtent, TemporalExtent,
+};
 
 use crate::CollectionTransactions;
 
-use super::Db;
+use super::{
+    sql::{self, Params},
+    temporal, Db,
+};
+
+/// Maximum number of distinct values of a property to list in the summaries
+#[cfg(feature = "stac")]
+const MAX_SUMMARY_VALUES: i64 = 25;
 
 #[async_trait::async_trait]
 impl CollectionTransactions for Db {
     async fn create_collection(&self, collection: &Collection) -> anyhow::Result<String> {
+        sql::validate_collection_id(&collection.id)?;
+        temporal::validate(collection)?;
+        let table = sql::items_table(&collection.id);
+
         let mut tx = self.pool.begin().await?;
 
+        let default = sql::literal(&collection.id);
         sqlx::query(&format!(
             r#"
-            CREATE TABLE items."{0}" (
+            CREATE TABLE {table} (
                 id text PRIMARY KEY DEFAULT gen_random_uuid()::text,
-                collection text REFERENCES meta.collections(id) DEFAULT '{0}',
+                collection text REFERENCES meta.collections(id) DEFAULT {default},
                 properties jsonb,
-                geom geometry NOT NULL,
+                geom geometry,
                 links jsonb NOT NULL DEFAULT '[]'::jsonb,
                 assets jsonb NOT NULL DEFAULT '{{}}'::jsonb,
                 bbox jsonb
             )
-            "#,
-            collection.id
+            "#
         ))
         .execute(&mut tx)
         .await?;
 
-        sqlx::query(&format!(
-            r#"CREATE INDEX ON items."{}" USING btree (collection)"#,
-            collection.id
-        ))
-        .execute(&mut tx)
-        .await?;
+        for index in ["btree (collection)", "gin (properties)", "gist (geom)"] {
+            sqlx::query(&format!("CREATE INDEX ON {table} USING {index}"))
+                .execute(&mut tx)
+                .await?;
+        }
 
-        sqlx::query(&format!(
-            r#"CREATE INDEX ON items."{}" USING gin (properties)"#,
-            collection.id
-        ))
-        .execute(&mut tx)
-        .await?;
-
-        sqlx::query(&format!(
-            r#"CREATE INDEX ON items."{}" USING gist (geom)"#,
-            collection.id
-        ))
-        .execute(&mut tx)
-        .await?;
+        temporal::index(&mut tx, collection).await?;
 
         sqlx::query("SELECT UpdateGeometrySRID('items', $1, 'geom', $2)")
             .bind(&collection.id)
-            .bind(collection.storage_crs.clone().unwrap_or_default().as_srid())
+            .bind(
+                self.srid(&collection.storage_crs.clone().unwrap_or_default())
+                    .await?,
+            )
             .execute(&mut tx)
             .await?;
 
@@ -60,12 +67,17 @@ impl CollectionTransactions for Db {
             .await?;
 
         tx.commit().await?;
+        self.cache.invalidate(&collection.id);
 
         Ok(collection.id.to_owned())
     }
 
     async fn read_collection(&self, id: &str) -> anyhow::Result<Option<Collection>> {
-        // TODO: cache
+        let (cached, generation) = self.cache.get(id);
+        if cached.is_some() {
+            return Ok(cached);
+        }
+
         let collection: Option<sqlx::types::Json<Collection>> = sqlx::query_scalar(
             r#"
             SELECT collection as "collection!" 
@@ -76,15 +88,31 @@ impl CollectionTransactions for Db {
         .fetch_optional(&self.pool)
         .await?;
 
+        if let Some(collection) = &collection {
+            self.cache.insert(collection, generation);
+        }
+
         Ok(collection.map(|c| c.0))
     }
 
     async fn update_collection(&self, collection: &Collection) -> anyhow::Result<()> {
-        sqlx::query("UPDATE meta.collections SET collection = $2 WHERE id = $1")
-            .bind(&collection.id)
-            .bind(sqlx::types::Json(collection))
-            .execute(&self.pool)
-            .await?;
+        let mut tx = self.pool.begin().await?;
+        self.update(&mut tx, collection).await?;
+        tx.commit().await?;
+        self.cache.invalidate(&collection.id);
+
+        Ok(())
+    }
+
+    async fn rename_collection(&self, id: &str, collection: &Collection) -> anyhow::Result<()> {
+        sql::validate_collection_id(&collection.id)?;
+
+        let mut tx = self.pool.begin().await?;
+        self.rename(&mut tx, id, &collection.id).await?;
+        self.update(&mut tx, collection).await?;
+        tx.commit().await?;
+        self.cache.invalidate(id);
+        self.cache.invalidate(&collection.id);
 
         Ok(())
     }
@@ -92,7 +120,7 @@ impl CollectionTransactions for Db {
     async fn delete_collection(&self, id: &str) -> anyhow::Result<()> {
         let mut tx = self.pool.begin().await?;
 
-        sqlx::query(&format!(r#"DROP TABLE IF EXISTS items."{}""#, id))
+        sqlx::query(&format!("DROP TABLE IF EXISTS {}", sql::items_table(id)))
             .execute(&mut tx)
             .await?;
 
@@ -102,25 +130,349 @@ impl CollectionTransactions for Db {
             .await?;
 
         tx.commit().await?;
+        self.cache.invalidate(id);
 
         Ok(())
     }
 
-    async fn list_collections(&self, _query: &Query) -> anyhow::Result<Collections> {
-        let collections: Option<sqlx::types::Json<Vec<Collection>>> = sqlx::query_scalar(
-            r#"
-            SELECT array_to_json(array_agg(collection))
-            FROM meta.collections
-            WHERE collection ->> 'type' = 'Collection'
-            "#,
+    async fn list_collections(&self, query: &Query) -> anyhow::Result<Collections> {
+        let mut params = Params::default();
+
+        // WHERE
+        let mut where_conditions = vec!["collection ->> 'type' = 'Collection'".to_string()];
+
+        // bbox
+        if let Some(bbox) = query.bbox.as_ref() {
+            let crs = query.bbox_crs.to_owned().unwrap_or_default();
+            where_conditions.push(self.extent_predicate(bbox, &crs, &mut params).await?);
+        }
+
+        // datetime
+        if let Some(datetime) = query.datetime.as_ref() {
+            where_conditions.push(temporal::extent_predicate(datetime, &mut params));
+        }
+
+        // q, any of the terms
+        if let Some(terms) = query.q.as_ref() {
+            let patterns: Vec<String> = terms
+                .iter()
+                .map(|term| format!("%{}%", sql::escape_like(term)))
+                .collect();
+            where_conditions.push(format!(
+                r#"
+                concat_ws(
+                    ' ',
+                    collection ->> 'title',
+                    collection ->> 'description',
+                    collection ->> 'keywords'
+                ) ILIKE ANY({})
+                "#,
+                params.push(patterns)
+            ));
+        }
+
+        let conditions = where_conditions.join(" AND ");
+
+        // COUNT
+        let number_matched: i64 = sqlx::query_scalar_with(
+            &format!("SELECT count(*) FROM meta.collections WHERE {conditions}"),
+            params.arguments(),
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        // FETCH
+        let collections: Option<sqlx::types::Json<Vec<Collection>>> = sqlx::query_scalar_with(
+            &format!(
+                r#"
+                SELECT array_to_json(array_agg(collection ORDER BY id))
+                FROM (
+                    SELECT id, collection
+                    FROM meta.collections
+                    WHERE {conditions}
+                    ORDER BY id
+                    LIMIT {}
+                    OFFSET {}
+                ) t
+                "#,
+                query
+                    .limit
+                    .map_or_else(|| String::from("NULL"), |l| l.to_string()),
+                query.offset.unwrap_or(0)
+            ),
+            params.arguments(),
         )
         .fetch_one(&self.pool)
         .await?;
 
         let collections = collections.map(|c| c.0).unwrap_or_default();
         let mut collections = Collections::new(collections);
-        collections.number_matched = collections.number_returned;
+        collections.number_matched = Some(number_matched as u64);
 
         Ok(collections)
     }
+
+    async fn update_extent(&self, id: &str, summaries: bool) -> anyhow::Result<Option<Collection>> {
+        ensure!(
+            !summaries || cfg!(feature = "stac"),
+            "Summaries require the `stac` feature"
+        );
+
+        let mut tx = self.pool.begin().await?;
+
+        let collection: Option<Json<Collection>> =
+            sqlx::query_scalar("SELECT collection FROM meta.collections WHERE id = $1 FOR UPDATE")
+                .bind(id)
+                .fetch_optional(&mut tx)
+                .await?;
+        let mut collection = match collection {
+            Some(collection) => collection.0,
+            None => return Ok(None),
+        };
+        let table = sql::items_table(id);
+
+        // spatial extent in CRS84 and the storage crs
+        let (bbox, storage_bbox): (Option<Vec<f64>>, Option<Vec<f64>>) = sqlx::query_as(&format!(
+            r#"
+            SELECT
+                CASE WHEN crs84 IS NOT NULL
+                THEN ARRAY[ST_XMin(crs84), ST_YMin(crs84), ST_XMax(crs84), ST_YMax(crs84)]
+                END,
+                CASE WHEN storage IS NOT NULL
+                THEN ARRAY[ST_XMin(storage), ST_YMin(storage), ST_XMax(storage), ST_YMax(storage)]
+                END
+            FROM (
+                SELECT ST_Extent(ST_Transform(geom, 4326)) AS crs84, ST_Extent(geom) AS storage
+                FROM {table}
+            ) extent
+            "#
+        ))
+        .fetch_one(&mut tx)
+        .await?;
+
+        let storage_crs = collection.storage_crs.to_owned().unwrap_or_default();
+        let storage_bbox = match storage_bbox {
+            Some(bbox) => {
+                let bbox = Bbox::try_from(bbox.as_slice()).map_err(anyhow::Error::msg)?;
+                match self.spatial_ref(&storage_crs).await?.axis_order {
+                    AxisOrder::XY => Some(vec![bbox]),
+                    AxisOrder::YX => Some(vec![bbox.swap_axes()]),
+                }
+            }
+            None => None,
+        };
+        let spatial = match bbox {
+            Some(bbox) => Some(SpatialExtent {
+                bbox: vec![Bbox::try_from(bbox.as_slice()).map_err(anyhow::Error::msg)?],
+                crs: Crs::default(),
+                storage_crs_bbox: storage_bbox,
+            }),
+            None => None,
+        };
+
+        // temporal extent over the temporal properties
+        let temporal = collection
+            .temporal_properties
+            .to_owned()
+            .unwrap_or_default();
+        let (first, last) = temporal::bounds(&temporal);
+        let interval: Json<Vec<Value>> = sqlx::query_scalar(&format!(
+            "SELECT jsonb_build_array(to_jsonb(min({first})), to_jsonb(max({last}))) FROM {table}"
+        ))
+        .fetch_one(&mut tx)
+        .await?;
+        let temporal: Option<TemporalExtent> = if interval.0.iter().all(Value::is_null) {
+            None
+        } else {
+            Some(serde_json::from_value(json!({ "interval": [interval.0] }))?)
+        };
+
+        collection.extent = if spatial.is_some() || temporal.is_some() {
+            Some(Extent { spatial, temporal })
+        } else {
+            None
+        };
+
+        #[cfg(feature = "stac")]
+        if summaries {
+            collection.summaries = summarize(&mut tx, &table).await?;
+        }
+
+        sqlx::query("UPDATE meta.collections SET collection = $2 WHERE id = $1")
+            .bind(id)
+            .bind(Json(&collection))
+            .execute(&mut tx)
+            .await?;
+
+        tx.commit().await?;
+        self.cache.invalidate(id);
+
+        Ok(Some(collection))
+    }
+}
+
+impl Db {
+    /// Replace the metadata of a collection, reprojecting the geometries into
+    /// a changed storage crs. Missing collections are left alone.
+    async fn update(&self, conn: &mut PgConnection, collection: &Collection) -> anyhow::Result<()> {
+        temporal::validate(collection)?;
+
+        let current: Option<Json<Collection>> =
+            sqlx::query_scalar("SELECT collection FROM meta.collections WHERE id = $1 FOR UPDATE")
+                .bind(&collection.id)
+                .fetch_optional(&mut *conn)
+                .await?;
+        let current = match current {
+            Some(current) => current.0,
+            None => return Ok(()),
+        };
+
+        let mut collection = collection.to_owned();
+
+        let srid = self.srid(&current.storage_crs.unwrap_or_default()).await?;
+        let new_srid = self
+            .srid(&collection.storage_crs.clone().unwrap_or_default())
+            .await?;
+        if srid != new_srid {
+            let table = sql::items_table(&collection.id);
+
+            // keep the geometry type and dimensions of the column
+            let r#type: Option<String> = sqlx::query_scalar(
+                r#"
+                    SELECT CASE WHEN atttypmod >= 0 THEN postgis_typmod_type(atttypmod) END
+                    FROM pg_attribute
+                    WHERE attrelid = $1::regclass AND attname = 'geom'
+                    "#,
+            )
+            .bind(&table)
+            .fetch_one(&mut *conn)
+            .await?;
+            let r#type = match r#type {
+                Some(r#type) => format!("geometry({type}, {new_srid})"),
+                None => "geometry".to_string(),
+            };
+
+            sqlx::query(&format!(
+                r#"
+                    ALTER TABLE {table} ALTER COLUMN geom TYPE {type}
+                    USING ST_Transform(geom, {new_srid})
+                    "#
+            ))
+            .execute(&mut *conn)
+            .await?;
+
+            // the bounding boxes in the previous storage crs are outdated
+            if let Some(spatial) = collection
+                .extent
+                .as_mut()
+                .and_then(|extent| extent.spatial.as_mut())
+            {
+                spatial.storage_crs_bbox = None;
+            }
+        }
+
+        sqlx::query("UPDATE meta.collections SET collection = $2 WHERE id = $1")
+            .bind(&collection.id)
+            .bind(Json(&collection))
+            .execute(&mut *conn)
+            .await?;
+
+        // the temporal properties may have changed
+        temporal::index(conn, &collection).await?;
+
+        Ok(())
+    }
+
+    /// Move a collection and its items to a new id
+    async fn rename(&self, conn: &mut PgConnection, id: &str, new_id: &str) -> anyhow::Result<()> {
+        let collection: Option<Json<Collection>> =
+            sqlx::query_scalar("SELECT collection FROM meta.collections WHERE id = $1 FOR UPDATE")
+                .bind(id)
+                .fetch_optional(&mut *conn)
+                .await?;
+        let mut collection = collection
+            .ok_or_else(|| anyhow::anyhow!("Collection `{id}` not found"))?
+            .0;
+        collection.id = new_id.to_owned();
+
+        // the items reference the collection, so it is inserted under the new
+        // id before the old one is removed
+        sqlx::query("INSERT INTO meta.collections ( id, collection ) VALUES ( $1, $2 )")
+            .bind(new_id)
+            .bind(Json(&collection))
+            .execute(&mut *conn)
+            .await?;
+
+        let table = sql::items_table(id);
+        sqlx::query(&format!(
+            "ALTER TABLE {table} ALTER COLUMN collection SET DEFAULT {}",
+            sql::literal(new_id)
+        ))
+        .execute(&mut *conn)
+        .await?;
+
+        sqlx::query(&format!("UPDATE {table} SET collection = $1"))
+            .bind(new_id)
+            .execute(&mut *conn)
+            .await?;
+
+        sqlx::query(&format!(
+            "ALTER TABLE {table} RENAME TO {}",
+            sql::identifier(new_id)
+        ))
+        .execute(&mut *conn)
+        .await?;
+
+        temporal::rename(conn, &collection, id).await?;
+
+        sqlx::query("DELETE FROM meta.collections WHERE id = $1")
+            .bind(id)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Property name, numeric range and distinct values of a property
+#[cfg(feature = "stac")]
+type SummaryRow = (String, Option<f64>, Option<f64>, Option<Json<Vec<Value>>>);
+
+/// Summaries of the item properties, either the range of the numbers or the
+/// distinct strings and booleans if there are only a few
+#[cfg(feature = "stac")]
+async fn summarize(
+    conn: &mut sqlx::PgConnection,
+    table: &str,
+) -> anyhow::Result<serde_json::Map<String, Value>> {
+    let rows: Vec<SummaryRow> = sqlx::query_as(&format!(
+        r#"
+            SELECT
+                key,
+                min((value #>> '{{}}')::float8) FILTER (WHERE jsonb_typeof(value) = 'number'),
+                max((value #>> '{{}}')::float8) FILTER (WHERE jsonb_typeof(value) = 'number'),
+                CASE WHEN count(DISTINCT value) <= {MAX_SUMMARY_VALUES}
+                THEN jsonb_agg(DISTINCT value)
+                    FILTER (WHERE jsonb_typeof(value) IN ('string', 'boolean'))
+                END
+            FROM {table}, jsonb_each(properties)
+            GROUP BY key
+            "#
+    ))
+    .fetch_all(conn)
+    .await?;
+
+    let mut summaries = serde_json::Map::new();
+    for (key, minimum, maximum, values) in rows {
+        let summary = match (minimum, maximum, values) {
+            (_, _, Some(values)) => Value::from(values.0),
+            (Some(minimum), Some(maximum), None) => {
+                json!({ "minimum": minimum, "maximum": maximum })
+            }
+            _ => continue,
+        };
+        summaries.insert(key, summary);
+    }
+
+    Ok(summaries)
 }
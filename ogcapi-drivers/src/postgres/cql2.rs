@@ -0,0 +1,259 @@
+use anyhow::bail;
+use chrono::{DateTime, NaiveDate};
+use serde_json::json;
+
+use ogcapi_types::{
+    common::AxisOrder,
+    features::cql2::{Expr, SpatialOp, TemporalOp},
+};
+
+use crate::InvalidFilter;
+
+use super::{crs::SpatialRef, sql::Params};
+
+/// Translates CQL2 expressions into parameterized PostGIS predicates
+/// on an `items` table.
+pub(crate) struct Filter<'a> {
+    params: &'a mut Params,
+    storage_srid: i32,
+    filter_srid: i32,
+    filter_axis_order: AxisOrder,
+}
+
+impl<'a> Filter<'a> {
+    pub(crate) fn new(params: &'a mut Params, storage_srid: i32, filter: &SpatialRef) -> Self {
+        Filter {
+            params,
+            storage_srid,
+            filter_srid: filter.srid,
+            filter_axis_order: filter.axis_order,
+        }
+    }
+
+    /// Boolean SQL expression
+    pub(crate) fn predicate(&mut self, expr: &Expr) -> anyhow::Result<String> {
+        Ok(match expr {
+            Expr::And(exprs) => self.join(exprs, " AND ")?,
+            Expr::Or(exprs) => self.join(exprs, " OR ")?,
+            Expr::Not(expr) => format!("NOT ({})", self.predicate(expr)?),
+            Expr::Comparison(op, a, b) => {
+                if is_instant(a) || is_instant(b) {
+                    let (a, _) = self.temporal(a)?;
+                    let (b, _) = self.temporal(b)?;
+                    format!("{a} {op} {b}")
+                } else {
+                    format!("{} {op} {}", self.scalar(a)?, self.scalar(b)?)
+                }
+            }
+            Expr::Like(a, b) => format!("{} LIKE {}", self.text(a)?, self.text(b)?),
+            Expr::Between(a, low, high) => format!(
+                "{} BETWEEN {} AND {}",
+                self.scalar(a)?,
+                self.scalar(low)?,
+                self.scalar(high)?
+            ),
+            Expr::In(a, list) => {
+                let a = self.scalar(a)?;
+                let list = list
+                    .iter()
+                    .map(|e| self.scalar(e))
+                    .collect::<anyhow::Result<Vec<String>>>()?;
+                format!("{a} IN ({})", list.join(", "))
+            }
+            Expr::IsNull(a) => format!(
+                "COALESCE(jsonb_typeof({}), 'null') = 'null'",
+                self.scalar(a)?
+            ),
+            Expr::Spatial(op, a, b) => {
+                let function = match op {
+                    SpatialOp::Intersects => "ST_Intersects",
+                    SpatialOp::Equals => "ST_Equals",
+                    SpatialOp::Disjoint => "ST_Disjoint",
+                    SpatialOp::Touches => "ST_Touches",
+                    SpatialOp::Within => "ST_Within",
+                    SpatialOp::Overlaps => "ST_Overlaps",
+                    SpatialOp::Crosses => "ST_Crosses",
+                    SpatialOp::Contains => "ST_Contains",
+                };
+                format!("{function}({}, {})", self.geometry(a)?, self.geometry(b)?)
+            }
+            Expr::Temporal(op, a, b) => {
+                let (a_start, a_end) = self.temporal(a)?;
+                let (b_start, b_end) = self.temporal(b)?;
+                match op {
+                    TemporalOp::After => format!("{a_start} > {b_end}"),
+                    TemporalOp::Before => format!("{a_end} < {b_start}"),
+                    TemporalOp::Contains => {
+                        format!("({a_start} < {b_start} AND {a_end} > {b_end})")
+                    }
+                    TemporalOp::Disjoint => format!("({a_end} < {b_start} OR {a_start} > {b_end})"),
+                    TemporalOp::During => format!("({a_start} > {b_start} AND {a_end} < {b_end})"),
+                    TemporalOp::Equals => format!("({a_start} = {b_start} AND {a_end} = {b_end})"),
+                    TemporalOp::FinishedBy => {
+                        format!("({a_start} < {b_start} AND {a_end} = {b_end})")
+                    }
+                    TemporalOp::Finishes => {
+                        format!("({a_start} > {b_start} AND {a_end} = {b_end})")
+                    }
+                    TemporalOp::Intersects => {
+                        format!("({a_start} <= {b_end} AND {a_end} >= {b_start})")
+                    }
+                    TemporalOp::Meets => format!("{a_end} = {b_start}"),
+                    TemporalOp::MetBy => format!("{a_start} = {b_end}"),
+                    TemporalOp::OverlappedBy => format!(
+                        "({a_start} > {b_start} AND {a_start} < {b_end} AND {a_end} > {b_end})"
+                    ),
+                    TemporalOp::Overlaps => format!(
+                        "({a_start} < {b_start} AND {a_end} > {b_start} AND {a_end} < {b_end})"
+                    ),
+                    TemporalOp::StartedBy => {
+                        format!("({a_start} = {b_start} AND {a_end} > {b_end})")
+                    }
+                    TemporalOp::Starts => format!("({a_start} = {b_start} AND {a_end} < {b_end})"),
+                }
+            }
+            Expr::Bool(true) => "TRUE".to_string(),
+            Expr::Bool(false) => "FALSE".to_string(),
+            Expr::Property(_) => format!("{} = 'true'::jsonb", self.scalar(expr)?),
+            _ => bail!(InvalidFilter(format!(
+                "Expected a predicate but found `{expr:?}`"
+            ))),
+        })
+    }
+
+    fn join(&mut self, exprs: &[Expr], separator: &str) -> anyhow::Result<String> {
+        let exprs = exprs
+            .iter()
+            .map(|e| self.predicate(e))
+            .collect::<anyhow::Result<Vec<String>>>()?;
+        Ok(format!("({})", exprs.join(separator)))
+    }
+
+    /// Scalar as `jsonb`
+    fn scalar(&mut self, expr: &Expr) -> anyhow::Result<String> {
+        Ok(match expr {
+            Expr::Property(p) if p == "id" => "to_jsonb(items.id)".to_string(),
+            Expr::Property(p) => format!("items.properties -> {}", self.params.push(p.as_str())),
+            Expr::Bool(b) => self.params.push(json!(b)),
+            Expr::Number(n) => self.params.push(json!(n)),
+            Expr::String(s) => self.params.push(json!(s)),
+            _ => bail!(InvalidFilter(format!(
+                "Expected a scalar but found `{expr:?}`"
+            ))),
+        })
+    }
+
+    /// Character expression as `text`
+    fn text(&mut self, expr: &Expr) -> anyhow::Result<String> {
+        Ok(match expr {
+            Expr::Property(p) if p == "id" => "items.id".to_string(),
+            Expr::Property(p) => format!("items.properties ->> {}", self.params.push(p.as_str())),
+            Expr::String(s) => self.params.push(s.as_str()),
+            _ => bail!(InvalidFilter(format!(
+                "Expected a character expression but found `{expr:?}`"
+            ))),
+        })
+    }
+
+    /// Geometry in the storage crs, from coordinates in the axis order of the
+    /// filter crs
+    fn geometry(&mut self, expr: &Expr) -> anyhow::Result<String> {
+        Ok(match expr {
+            Expr::Property(p) if p == "geometry" => "items.geom".to_string(),
+            Expr::Geometry(geometry) => {
+                let mut geometry = format!(
+                    "ST_SetSRID(ST_GeomFromGeoJSON({}), {})",
+                    self.params.push(geometry.to_string()),
+                    self.params.push(self.filter_srid)
+                );
+                if self.filter_axis_order == AxisOrder::YX {
+                    geometry = format!("ST_FlipCoordinates({geometry})");
+                }
+                format!(
+                    "ST_Transform({geometry}, {})",
+                    self.params.push(self.storage_srid)
+                )
+            }
+            Expr::Bbox(bbox) => {
+                // only the horizontal footprint is relevant for the spatial operators
+                let (mut x1, mut y1, mut x2, mut y2) = match bbox.len() {
+                    4 => (bbox[0], bbox[1], bbox[2], bbox[3]),
+                    6 => (bbox[0], bbox[1], bbox[3], bbox[4]),
+                    _ => bail!(InvalidFilter("Expected 4 or 6 numbers in bbox".to_string())),
+                };
+                if self.filter_axis_order == AxisOrder::YX {
+                    (x1, y1, x2, y2) = (y1, x1, y2, x2);
+                }
+                format!(
+                    "ST_Transform(ST_MakeEnvelope({}, {}, {}, {}, {}), {})",
+                    self.params.push(x1),
+                    self.params.push(y1),
+                    self.params.push(x2),
+                    self.params.push(y2),
+                    self.params.push(self.filter_srid),
+                    self.params.push(self.storage_srid)
+                )
+            }
+            _ => bail!(InvalidFilter(format!(
+                "Expected a geometry but found `{expr:?}`"
+            ))),
+        })
+    }
+
+    /// Start and end of an instant or interval as `timestamptz`
+    fn temporal(&mut self, expr: &Expr) -> anyhow::Result<(String, String)> {
+        Ok(match expr {
+            Expr::Interval(start, end) => {
+                let start = if start.is_open() {
+                    "'-infinity'::timestamptz".to_string()
+                } else {
+                    self.temporal(start)?.0
+                };
+                let end = if end.is_open() {
+                    "'infinity'::timestamptz".to_string()
+                } else {
+                    self.temporal(end)?.1
+                };
+                (start, end)
+            }
+            _ => {
+                let instant = self.instant(expr)?;
+                (instant.to_owned(), instant)
+            }
+        })
+    }
+
+    fn instant(&mut self, expr: &Expr) -> anyhow::Result<String> {
+        Ok(match expr {
+            // values other than timestamps are null rather than failing the query
+            Expr::Property(p) => format!(
+                "meta.to_timestamptz(items.properties ->> {})",
+                self.params.push(p.as_str())
+            ),
+            Expr::Timestamp(t) => {
+                format!("CAST({} AS timestamptz)", self.params.push(t.to_rfc3339()))
+            }
+            Expr::Date(d) => format!("CAST({} AS timestamptz)", self.params.push(d.to_string())),
+            // validated here, as Postgres would fail on it with a server error
+            Expr::String(s) => {
+                let instant = if let Ok(t) = DateTime::parse_from_rfc3339(s) {
+                    t.to_rfc3339()
+                } else if let Ok(d) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+                    d.to_string()
+                } else {
+                    bail!(InvalidFilter(format!(
+                        "Expected a timestamp or date but found `{s}`"
+                    )))
+                };
+                format!("CAST({} AS timestamptz)", self.params.push(instant))
+            }
+            _ => bail!(InvalidFilter(format!(
+                "Expected a temporal expression but found `{expr:?}`"
+            ))),
+        })
+    }
+}
+
+fn is_instant(expr: &Expr) -> bool {
+    matches!(expr, Expr::Timestamp(_) | Expr::Date(_))
+}
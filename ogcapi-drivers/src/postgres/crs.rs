@@ -0,0 +1,183 @@
+use std::str::FromStr;
+
+use ogcapi_types::common::{AreaOfUse, Authority, AxisOrder, Bbox, Crs, CrsDefinition};
+
+use crate::{CrsCatalogue, UnknownCrs};
+
+use super::Db;
+
+/// Definition of CRS84, whose entry in `spatial_ref_sys` is the one of
+/// EPSG:4326 with the latitude first
+const CRS84: &str = r#"GEOGCRS["WGS 84 (CRS84)",DATUM["World Geodetic System 1984",ELLIPSOID["WGS 84",6378137,298.257223563,LENGTHUNIT["metre",1]]],PRIMEM["Greenwich",0,ANGLEUNIT["degree",0.0174532925199433]],CS[ellipsoidal,2],AXIS["geodetic longitude (Lon)",east,ORDER[1],ANGLEUNIT["degree",0.0174532925199433]],AXIS["geodetic latitude (Lat)",north,ORDER[2],ANGLEUNIT["degree",0.0174532925199433]],USAGE[SCOPE["Not known."],AREA["World."],BBOX[-90,-180,90,180]],ID["OGC","CRS84"]]"#;
+
+/// Definition of CRS84h, see [`CRS84`]
+const CRS84H: &str = r#"GEOGCRS["WGS 84 (CRS84h)",DATUM["World Geodetic System 1984",ELLIPSOID["WGS 84",6378137,298.257223563,LENGTHUNIT["metre",1]]],PRIMEM["Greenwich",0,ANGLEUNIT["degree",0.0174532925199433]],CS[ellipsoidal,3],AXIS["geodetic longitude (Lon)",east,ORDER[1],ANGLEUNIT["degree",0.0174532925199433]],AXIS["geodetic latitude (Lat)",north,ORDER[2],ANGLEUNIT["degree",0.0174532925199433]],AXIS["ellipsoidal height (h)",up,ORDER[3],LENGTHUNIT["metre",1]],USAGE[SCOPE["Not known."],AREA["World."],BBOX[-90,-180,90,180]],ID["OGC","CRS84h"]]"#;
+
+/// Spatial reference system of a crs in the `spatial_ref_sys` catalogue
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SpatialRef {
+    pub(crate) srid: i32,
+    pub(crate) geographic: bool,
+    /// Order of the horizontal axes of the coordinates in requests and
+    /// responses, PostGIS always has them in x/y order
+    pub(crate) axis_order: AxisOrder,
+}
+
+impl Db {
+    /// SRID of a crs, see [`Db::spatial_ref`]
+    pub(crate) async fn srid(&self, crs: &Crs) -> anyhow::Result<i32> {
+        Ok(self.spatial_ref(crs).await?.srid)
+    }
+
+    /// Spatial reference system of a crs, looked up by its authority and code
+    /// in the `spatial_ref_sys` catalogue. Fails with [`UnknownCrs`] if it is
+    /// not listed there.
+    pub(crate) async fn spatial_ref(&self, crs: &Crs) -> anyhow::Result<SpatialRef> {
+        if let Some(spatial_ref) = self.spatial_refs.read().unwrap().get(crs) {
+            return Ok(*spatial_ref);
+        }
+
+        let unknown = || anyhow::Error::new(UnknownCrs(crs.to_owned()));
+
+        let entry: Option<(i32, Option<String>)> = match crs.authority {
+            // only the CRS84 ones have an entry, as the EPSG one of the datum
+            Authority::OGC => {
+                sqlx::query_as("SELECT srid, srtext FROM spatial_ref_sys WHERE srid = $1")
+                    .bind(crs.as_srid().ok_or_else(unknown)?)
+                    .fetch_optional(&self.pool)
+                    .await?
+            }
+            _ => {
+                let code: i32 = crs.code.parse().map_err(|_| unknown())?;
+                // entries of the IAU are versioned, e.g. `IAU_2015`
+                sqlx::query_as(
+                    r#"
+                    SELECT srid, srtext FROM spatial_ref_sys
+                    WHERE (upper(auth_name) = $1 OR upper(auth_name) LIKE $1 || '\_%')
+                    AND auth_srid = $2
+                    ORDER BY auth_name DESC, srid
+                    LIMIT 1
+                    "#,
+                )
+                .bind(crs.authority.to_string().to_uppercase())
+                .bind(code)
+                .fetch_optional(&self.pool)
+                .await?
+            }
+        };
+        let (srid, srtext) = entry.ok_or_else(unknown)?;
+        let srtext = srtext.unwrap_or_default();
+
+        let geographic = ["GEOGCS", "GEOGCRS", "GEOGRAPHICCRS"]
+            .iter()
+            .any(|keyword| srtext.starts_with(keyword));
+
+        // the EPSG has the latitude first, if the definition does not tell
+        let axis_order = match crs.authority {
+            Authority::OGC => AxisOrder::XY,
+            _ => AxisOrder::from_wkt(&srtext).unwrap_or(
+                if geographic && crs.authority == Authority::EPSG {
+                    AxisOrder::YX
+                } else {
+                    AxisOrder::XY
+                },
+            ),
+        };
+
+        let spatial_ref = SpatialRef {
+            srid,
+            geographic,
+            axis_order,
+        };
+        self.spatial_refs
+            .write()
+            .unwrap()
+            .insert(crs.to_owned(), spatial_ref);
+
+        Ok(spatial_ref)
+    }
+
+    /// Area of use of a spatial reference system according to the PROJ
+    /// database, with `postgis_srs` of PostGIS 3.4 or later
+    async fn area_of_use(&self, srid: i32) -> anyhow::Result<Option<AreaOfUse>> {
+        let available: bool =
+            sqlx::query_scalar("SELECT to_regprocedure('postgis_srs(text, text)') IS NOT NULL")
+                .fetch_one(&self.pool)
+                .await?;
+        if !available {
+            return Ok(None);
+        }
+
+        let corners: Option<(f64, f64, f64, f64)> = sqlx::query_as(
+            r#"
+            SELECT ST_X(point_sw), ST_Y(point_sw), ST_X(point_ne), ST_Y(point_ne)
+            FROM spatial_ref_sys, postgis_srs(auth_name, auth_srid::text)
+            WHERE srid = $1 AND point_sw IS NOT NULL AND point_ne IS NOT NULL
+            "#,
+        )
+        .bind(srid)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(corners.map(|(west, south, east, north)| AreaOfUse {
+            description: None,
+            bbox: Some(Bbox::from([west, south, east, north])),
+        }))
+    }
+}
+
+#[async_trait::async_trait]
+impl CrsCatalogue for Db {
+    async fn list_crs(&self) -> anyhow::Result<Vec<Crs>> {
+        let uris: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT jsonb_array_elements_text(collection -> 'crs')
+            FROM meta.collections
+            WHERE jsonb_typeof(collection -> 'crs') = 'array'
+            ORDER BY 1
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        // the same crs may be written differently, e.g. as URN
+        let mut crs = vec![Crs::default()];
+        for uri in uris {
+            if let Ok(c) = Crs::from_str(&uri) {
+                if !crs.contains(&c) {
+                    crs.push(c);
+                }
+            }
+        }
+
+        Ok(crs)
+    }
+
+    async fn crs_definition(&self, crs: &Crs) -> anyhow::Result<Option<CrsDefinition>> {
+        let srid = match self.srid(crs).await {
+            Ok(srid) => srid,
+            Err(e) if e.is::<UnknownCrs>() => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let wkt = match (&crs.authority, crs.code.as_str()) {
+            (Authority::OGC, "CRS84") => Some(CRS84.to_owned()),
+            (Authority::OGC, "CRS84h") => Some(CRS84H.to_owned()),
+            _ => {
+                sqlx::query_scalar("SELECT srtext FROM spatial_ref_sys WHERE srid = $1")
+                    .bind(srid)
+                    .fetch_one(&self.pool)
+                    .await?
+            }
+        };
+
+        let mut definition = CrsDefinition::from_wkt(crs.to_owned(), &wkt.unwrap_or_default());
+
+        // the WKT 1 of `spatial_ref_sys` has no area of use, but PROJ does
+        if definition.area_of_use.is_none() {
+            definition.area_of_use = self.area_of_use(srid).await?;
+        }
+
+        Ok(Some(definition))
+    }
+}
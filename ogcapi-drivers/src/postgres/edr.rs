@@ -1,3 +1,4 @@
+use anyhow::{anyhow, bail, Context};
 use ogcapi_types::{
     edr::{Query, QueryType},
     features::{Feature, FeatureCollection},
@@ -6,7 +7,10 @@ use sqlx::types::Json;
 
 use crate::{CollectionTransactions, EdrQuerier};
 
-use super::Db;
+use super::{
+    sql::{self, Params},
+    Db,
+};
 
 #[async_trait::async_trait]
 impl EdrQuerier for Db {
@@ -16,25 +20,32 @@ impl EdrQuerier for Db {
         query_type: &QueryType,
         query: &Query,
     ) -> anyhow::Result<FeatureCollection> {
-        let srid: i32 = query.crs.as_srid();
+        let srid = self.srid(&query.crs).await?;
+
+        let c = self
+            .read_collection(collection_id)
+            .await?
+            .ok_or_else(|| anyhow!("Collection `{collection_id}` not found"))?;
+        let storage_srid = self.srid(&c.storage_crs.unwrap_or_default()).await?;
 
-        let c = self.read_collection(collection_id).await?;
-        let storage_srid = c.unwrap().storage_crs.unwrap_or_default().as_srid();
+        let mut params = Params::default();
+        let output_srid = params.push(srid);
 
         let mut geometry_type = query.coords.split('(').next().unwrap().to_uppercase();
         geometry_type.retain(|c| !c.is_whitespace());
 
         let spatial_predicate = match &query_type {
             QueryType::Position | QueryType::Area | QueryType::Trajectory => {
+                let coords = params.push(query.coords.as_str());
                 if geometry_type.ends_with('Z') || geometry_type.ends_with('M') {
                     format!(
-                        "ST_3DIntersects(geom, ST_Transform(ST_GeomFromEWKT('SRID={};{}'), {}))",
-                        srid, query.coords, storage_srid
+                        "ST_3DIntersects(geom, ST_Transform(ST_GeomFromText({}, {}), {}))",
+                        coords, output_srid, storage_srid
                     )
                 } else {
                     format!(
-                        "ST_Intersects(geom, ST_Transform(ST_GeomFromEWKT('SRID={};{}'), {}))",
-                        srid, query.coords, storage_srid
+                        "ST_Intersects(geom, ST_Transform(ST_GeomFromText({}, {}), {}))",
+                        coords, output_srid, storage_srid
                     )
                 }
             }
@@ -52,29 +63,38 @@ impl EdrQuerier for Db {
                 let distance = rink_core::one_line(&mut ctx, &line)
                     .ok()
                     .and_then(|s| s.split(' ').next().and_then(|s| s.parse::<f64>().ok()))
-                    .expect("Failed to parse & convert distance");
+                    .ok_or_else(|| anyhow!("Failed to parse & convert distance"))?;
 
+                let coords = params.push(query.coords.as_str());
+                let distance = params.push(distance);
                 if geometry_type.ends_with('Z') || geometry_type.ends_with('M') {
                     format!(
-                        "ST_3DDWithin(geom, ST_Transform(ST_GeomFromEWKT('SRID={};{}'), {}))",
-                        srid, query.coords, storage_srid
+                        "ST_3DDWithin(geom, ST_Transform(ST_GeomFromText({}, {}), {}), {})",
+                        coords, output_srid, storage_srid, distance
                     )
                 } else {
                     format!(
-                    "ST_DWithin(ST_Transform(geom, 4326)::geography, ST_Transform(ST_GeomFromEWKT('SRID={};{}'), 4326)::geography, {}, false)",
-                    srid, query.coords, distance
+                    "ST_DWithin(ST_Transform(geom, 4326)::geography, ST_Transform(ST_GeomFromText({}, {}), 4326)::geography, {}, false)",
+                    coords, output_srid, distance
                 )
                 }
             }
             QueryType::Cube => {
-                let bbox: Vec<&str> = query.coords.split(',').collect();
-                if bbox.len() == 4 {
-                    format!(
-                        "ST_Intersects(geom, ST_Transform(ST_MakeEnvelope({}, {}), {})",
-                        query.coords, srid, storage_srid
-                    )
-                } else {
-                    format!(
+                let bbox = query
+                    .coords
+                    .split(',')
+                    .map(|c| c.trim().parse::<f64>())
+                    .collect::<Result<Vec<f64>, _>>()
+                    .context("Failed to parse cube coordinates")?;
+                let bbox: Vec<String> = bbox.into_iter().map(|c| params.push(c)).collect();
+                match bbox.len() {
+                    4 => format!(
+                        "ST_Intersects(geom, ST_Transform(ST_MakeEnvelope({}, {}), {}))",
+                        bbox.join(", "),
+                        output_srid,
+                        storage_srid
+                    ),
+                    6 => format!(
                         "ST_3DIntersects(
                             geom,
                             ST_Transform(
@@ -85,8 +105,16 @@ impl EdrQuerier for Db {
                                 {}
                             )
                         )",
-                        bbox[0], bbox[1], bbox[2], bbox[3], bbox[4], bbox[5], srid, storage_srid
-                    )
+                        bbox[0],
+                        bbox[1],
+                        bbox[2],
+                        bbox[3],
+                        bbox[4],
+                        bbox[5],
+                        output_srid,
+                        storage_srid
+                    ),
+                    n => bail!("Cube requires 4 or 6 coordinates, got {n}"),
                 }
             }
             QueryType::Corridor | QueryType::Locations => unimplemented!(),
@@ -94,15 +122,15 @@ impl EdrQuerier for Db {
 
         let properties = if let Some(parameters) = &query.parameter_name {
             format!(
-                "{0} as properties",
+                "jsonb_build_object({}) as properties",
                 parameters
                     .split(',')
-                    .map(|s| format!(
-                        "('{{\"{0}\":' || (properties -> '{0}')::text || '}}')::jsonb",
-                        s
-                    ))
+                    .map(|s| {
+                        let name = params.push(s);
+                        format!("{name}, properties -> {name}")
+                    })
                     .collect::<Vec<String>>()
-                    .join("||")
+                    .join(", ")
             )
         } else {
             "properties".to_string()
@@ -112,31 +140,36 @@ impl EdrQuerier for Db {
             r#"
             SELECT
                 id,
-                {1},
-                ST_AsGeoJSON(ST_Transform(geom, $1))::jsonb as geometry,
+                {},
+                ST_AsGeoJSON(ST_Transform(geom, {}))::jsonb as geometry,
                 links,
-                '{0}' as collection,
+                {} as collection,
                 assets
-            FROM items."{0}"
-            WHERE {2}
+            FROM {}
+            WHERE {}
             "#,
-            collection_id, properties, spatial_predicate
+            properties,
+            output_srid,
+            params.push(collection_id),
+            sql::items_table(collection_id),
+            spatial_predicate
         );
 
-        let number_matched = sqlx::query(&sql)
-            .bind(srid)
+        let number_matched = sqlx::query_with(&sql, params.arguments())
             .execute(&self.pool)
             .await?
             .rows_affected();
 
-        let features: Option<Json<Vec<Feature>>> = sqlx::query_scalar(&format!(
-            r#"
-            SELECT array_to_json(array_agg(row_to_json(t)))
-            FROM ( {} ) t
-            "#,
-            sql
-        ))
-        .bind(srid)
+        let features: Option<Json<Vec<Feature>>> = sqlx::query_scalar_with(
+            &format!(
+                r#"
+                SELECT array_to_json(array_agg(row_to_json(t)))
+                FROM ( {} ) t
+                "#,
+                sql
+            ),
+            params.arguments(),
+        )
         .fetch_one(&self.pool)
         .await?;
 
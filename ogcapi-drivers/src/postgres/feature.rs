@@ -1,27 +1,51 @@
+use std::collections::{BTreeMap, HashSet, VecDeque};
+
+use anyhow::anyhow;
+use futures_util::stream::{self, StreamExt};
+
 use ogcapi_types::{
-    common::{Bbox, Crs, Datetime, IntervalDatetime},
-    features::{Feature, FeatureCollection, Query},
+    common::AxisOrder,
+    features::{
+        BulkError, BulkMode, BulkReport, Feature, FeatureCollection, Query, Queryable, Queryables,
+    },
+};
+
+use crate::{CollectionTransactions, FeatureStream, FeatureTransactions, InvalidFilter, Outcome};
+
+use super::{
+    cql2::Filter,
+    crs::SpatialRef,
+    sort::Order,
+    sql::{self, Params},
+    temporal, Db,
 };
 
-use crate::{CollectionTransactions, FeatureTransactions};
+/// Number of features written per statement of a bulk upload
+const BATCH_SIZE: usize = 10000;
+
+/// Number of features fetched from the cursor at once when streaming
+const FETCH_SIZE: usize = 1000;
 
-use super::Db;
+/// Equatorial circumference of the WGS 84 ellipsoid, in meters
+const CIRCUMFERENCE: f64 = 2.0 * std::f64::consts::PI * 6378137.0;
+
+/// Pixels per side of a web map tile
+const TILE_SIZE: f64 = 256.0;
+
+/// Decimal places of the coordinates unless requested otherwise
+const PRECISION: i32 = 9;
 
 #[cfg(not(feature = "stac"))]
 static ROWS: &str = "
 items.id,
 items.collection,
-properties,
-ST_AsGeoJSON(ST_Transform(geom, $1))::jsonb AS geometry,
-links,
+links
 ";
 
 #[cfg(feature = "stac")]
 static ROWS: &str = "
 items.id,
 items.collection,
-properties,
-ST_AsGeoJSON(ST_Transform(geom, $1))::jsonb AS geometry,
 links,
 meta.collection ->> 'stac_version' AS stac_version,
 COALESCE(
@@ -45,11 +69,11 @@ COALESCE(
 #[async_trait::async_trait]
 impl FeatureTransactions for Db {
     async fn create_feature(&self, feature: &Feature) -> anyhow::Result<String> {
-        let collection = feature.collection.as_ref().unwrap();
+        let table = sql::items_table(feature.collection.as_ref().unwrap());
 
         let id: (String,) = sqlx::query_as(&format!(
             r#"
-            INSERT INTO items."{0}" (
+            INSERT INTO {table} (
                 id,
                 properties,
                 geom,
@@ -59,14 +83,13 @@ impl FeatureTransactions for Db {
             ) VALUES (
                 COALESCE($1 ->> 'id', gen_random_uuid()::text),
                 $1 -> 'properties',
-                ST_GeomFromGeoJSON($1 -> 'geometry'),
+                ST_GeomFromGeoJSON(NULLIF($1 -> 'geometry', 'null'::jsonb)),
                 $1 -> 'links',
                 COALESCE($1 -> 'assets', '{{}}'::jsonb),
                 $1 -> 'bbox'
             )
             RETURNING id
-            "#,
-            &collection
+            "#
         ))
         .bind(serde_json::to_value(feature)?)
         .fetch_one(&self.pool)
@@ -75,61 +98,226 @@ impl FeatureTransactions for Db {
         Ok(id.0)
     }
 
+    async fn create_features(
+        &self,
+        collection: &str,
+        features: &[Feature],
+        mode: BulkMode,
+    ) -> anyhow::Result<BulkReport> {
+        let mut report = BulkReport::default();
+
+        let mut ids = HashSet::new();
+        for (index, feature) in features.iter().enumerate() {
+            if let Some(id) = feature.id.as_deref() {
+                if !ids.insert(id) {
+                    report
+                        .errors
+                        .push(BulkError::new(index, Some(id), "Duplicate id"));
+                }
+            }
+        }
+
+        let table = sql::items_table(collection);
+
+        let mut tx = self.pool.begin().await?;
+
+        if mode == BulkMode::Insert && !ids.is_empty() {
+            let existing: HashSet<String> =
+                sqlx::query_scalar(&format!("SELECT id FROM {table} WHERE id = ANY($1)"))
+                    .bind(ids.iter().copied().collect::<Vec<&str>>())
+                    .fetch_all(&mut tx)
+                    .await?
+                    .into_iter()
+                    .collect();
+
+            for (index, feature) in features.iter().enumerate() {
+                if let Some(id) = feature.id.as_ref().filter(|id| existing.contains(*id)) {
+                    report
+                        .errors
+                        .push(BulkError::new(index, Some(id), "Id already exists"));
+                }
+            }
+        }
+
+        if !report.is_ok() {
+            report.errors.sort_by_key(|e| e.index);
+            return Ok(report);
+        }
+
+        let conflict = match mode {
+            BulkMode::Insert => "",
+            BulkMode::Upsert => {
+                r#"
+                ON CONFLICT (id) DO UPDATE SET
+                    properties = EXCLUDED.properties,
+                    geom = EXCLUDED.geom,
+                    links = EXCLUDED.links,
+                    assets = EXCLUDED.assets,
+                    bbox = EXCLUDED.bbox
+                "#
+            }
+        };
+
+        for batch in features.chunks(BATCH_SIZE) {
+            let values = batch
+                .iter()
+                .map(|f| serde_json::to_value(f).map(sqlx::types::Json))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            // `RETURNING` does not keep the order of the input rows, so the
+            // ids are paired with their position in the batch
+            let batch_ids: Vec<(i64, String)> = sqlx::query_as(&format!(
+                r#"
+                WITH input AS (
+                    SELECT i, COALESCE(f ->> 'id', gen_random_uuid()::text) AS id, f
+                    FROM UNNEST($1::jsonb[]) WITH ORDINALITY AS t(f, i)
+                ), inserted AS (
+                    INSERT INTO {table} (
+                        id,
+                        properties,
+                        geom,
+                        links,
+                        assets,
+                        bbox
+                    )
+                    SELECT
+                        id,
+                        f -> 'properties',
+                        ST_GeomFromGeoJSON(NULLIF(f -> 'geometry', 'null'::jsonb)),
+                        COALESCE(f -> 'links', '[]'::jsonb),
+                        COALESCE(f -> 'assets', '{{}}'::jsonb),
+                        f -> 'bbox'
+                    FROM input
+                    ORDER BY i
+                    {conflict}
+                    RETURNING id
+                )
+                SELECT input.i, input.id
+                FROM input JOIN inserted USING (id)
+                ORDER BY input.i
+                "#
+            ))
+            .bind(values)
+            .fetch_all(&mut tx)
+            .await?;
+
+            report.ids.extend(batch_ids.into_iter().map(|(_, id)| id));
+        }
+
+        tx.commit().await?;
+
+        Ok(report)
+    }
+
     async fn read_feature(
         &self,
         collection: &str,
         id: &str,
-        crs: &Crs,
+        query: &Query,
     ) -> anyhow::Result<Option<Feature>> {
-        let feature: Option<sqlx::types::Json<Feature>> = sqlx::query_scalar(&format!(
-            r#"
-            SELECT row_to_json(t)
-            FROM (
-                SELECT {ROWS}
-                FROM items."{collection}" items JOIN meta.collections meta
-                    ON items.collection = meta.id
-                WHERE items.id = $2
-            ) t
-            "#
+        let feature = self.read_feature_with_etag(collection, id, query).await?;
+        Ok(feature.map(|(feature, _)| feature))
+    }
+
+    async fn read_feature_with_etag(
+        &self,
+        collection: &str,
+        id: &str,
+        query: &Query,
+    ) -> anyhow::Result<Option<(Feature, String)>> {
+        let table = sql::items_table(collection);
+
+        let mut params = Params::default();
+        let spatial_ref = self.spatial_ref(&query.crs).await?;
+        let properties = properties(query, &mut params);
+        let geometry = geometry(query, &spatial_ref, &mut params);
+        let id = params.push(id);
+
+        let feature: Option<(sqlx::types::Json<Feature>, String)> = sqlx::query_as_with(
+            &format!(
+                r#"
+                SELECT row_to_json(t), md5(items::text)
+                FROM {table} items JOIN meta.collections meta
+                    ON items.collection = meta.id,
+                    LATERAL (SELECT {ROWS}, {properties}, {geometry}) t
+                WHERE items.id = {id}
+                "#
+            ),
+            params.arguments(),
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(feature.map(|(feature, etag)| (feature.0, etag)))
+    }
+
+    async fn feature_etag(&self, collection: &str, id: &str) -> anyhow::Result<Option<String>> {
+        let etag: Option<String> = sqlx::query_scalar(&format!(
+            "SELECT md5(items::text) FROM {} items WHERE id = $1",
+            sql::items_table(collection)
         ))
-        .bind(crs.as_srid())
         .bind(id)
         .fetch_optional(&self.pool)
         .await?;
 
-        Ok(feature.map(|f| f.0))
+        Ok(etag)
     }
 
-    async fn update_feature(&self, feature: &Feature) -> anyhow::Result<()> {
-        sqlx::query(&format!(
+    async fn update_feature(
+        &self,
+        feature: &Feature,
+        if_match: Option<&str>,
+        keep_geometry: bool,
+    ) -> anyhow::Result<Outcome> {
+        let collection = feature.collection.as_ref().unwrap();
+        let table = sql::items_table(collection);
+
+        let id: Option<String> = sqlx::query_scalar(&format!(
             r#"
-            UPDATE items."{0}"
+            UPDATE {table} items
             SET
                 properties = $1 -> 'properties',
-                geom = ST_GeomFromGeoJSON($1 -> 'geometry'),
+                geom = CASE WHEN $3 THEN geom
+                    ELSE ST_GeomFromGeoJSON(NULLIF($1 -> 'geometry', 'null'::jsonb))
+                END,
                 links = $1 -> 'links',
                 assets = COALESCE($1 -> 'assets', '{{}}'::jsonb)
             WHERE id = $1 ->> 'id'
-            "#,
-            &feature.collection.as_ref().unwrap()
+                AND ($2::text IS NULL OR md5(items::text) = $2)
+            RETURNING id
+            "#
         ))
         .bind(serde_json::to_value(feature)?)
-        .execute(&self.pool)
+        .bind(if_match)
+        .bind(keep_geometry)
+        .fetch_optional(&self.pool)
         .await?;
 
-        Ok(())
+        self.outcome(collection, feature.id.as_ref().unwrap(), id)
+            .await
     }
 
-    async fn delete_feature(&self, collection: &str, id: &str) -> anyhow::Result<()> {
-        sqlx::query(&format!(
-            r#"DELETE FROM items."{}" WHERE id = $1"#,
-            collection
+    async fn delete_feature(
+        &self,
+        collection: &str,
+        id: &str,
+        if_match: Option<&str>,
+    ) -> anyhow::Result<Outcome> {
+        let table = sql::items_table(collection);
+
+        let deleted: Option<String> = sqlx::query_scalar(&format!(
+            r#"
+            DELETE FROM {table} items
+            WHERE id = $1 AND ($2::text IS NULL OR md5(items::text) = $2)
+            RETURNING id
+            "#
         ))
         .bind(id)
-        .execute(&self.pool)
+        .bind(if_match)
+        .fetch_optional(&self.pool)
         .await?;
 
-        Ok(())
+        self.outcome(collection, id, deleted).await
     }
 
     async fn list_items(
@@ -137,141 +325,381 @@ impl FeatureTransactions for Db {
         collection: &str,
         query: &Query,
     ) -> anyhow::Result<FeatureCollection> {
+        let (select, params, number_matched) = self.select_items(collection, query).await?;
+
+        let features: Option<sqlx::types::Json<Vec<Feature>>> = sqlx::query_scalar_with(
+            &format!("SELECT array_to_json(array_agg(row_to_json(t))) FROM ({select}) t"),
+            params.arguments(),
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let features = features.map(|f| f.0).unwrap_or_default();
+        let mut fc = FeatureCollection::new(features);
+        fc.number_matched = number_matched;
+
+        Ok(fc)
+    }
+
+    async fn stream_items(&self, collection: &str, query: &Query) -> anyhow::Result<FeatureStream> {
+        let (select, params, number_matched) = self.select_items(collection, query).await?;
+
+        // the cursor is closed with the transaction, which is rolled back once
+        // the stream is dropped
+        let mut tx = self.pool.begin().await?;
+        sqlx::query_with(
+            &format!(
+                "DECLARE features NO SCROLL CURSOR FOR SELECT row_to_json(t) FROM ({select}) t"
+            ),
+            params.arguments(),
+        )
+        .execute(&mut tx)
+        .await?;
+
+        let features = stream::unfold(
+            (tx, VecDeque::new(), false),
+            |(mut tx, mut buffer, mut exhausted)| async move {
+                if buffer.is_empty() && !exhausted {
+                    let rows: Vec<sqlx::types::Json<Feature>> =
+                        match sqlx::query_scalar(&format!("FETCH {FETCH_SIZE} FROM features"))
+                            .fetch_all(&mut tx)
+                            .await
+                        {
+                            Ok(rows) => rows,
+                            Err(e) => return Some((Err(e.into()), (tx, buffer, true))),
+                        };
+                    exhausted = rows.len() < FETCH_SIZE;
+                    buffer.extend(rows.into_iter().map(|row| row.0));
+                }
+                buffer
+                    .pop_front()
+                    .map(|feature| (Ok(feature), (tx, buffer, exhausted)))
+            },
+        )
+        .boxed();
+
+        Ok(FeatureStream {
+            number_matched,
+            features,
+        })
+    }
+
+    async fn queryables(&self, collection: &str) -> anyhow::Result<Option<Queryables>> {
+        let collection = match self.read_collection(collection).await? {
+            Some(collection) => collection,
+            None => return Ok(None),
+        };
+
+        // infer the schema from the values of all features
+        let rows: Vec<(String, Option<Vec<String>>, Option<bool>, Option<bool>)> =
+            sqlx::query_as(&format!(
+                r#"
+                SELECT
+                    key,
+                    array_agg(DISTINCT jsonb_typeof(value))
+                        FILTER (WHERE jsonb_typeof(value) <> 'null'),
+                    bool_and(
+                        CASE WHEN jsonb_typeof(value) = 'number'
+                        THEN (value #>> '{{}}')::numeric % 1 = 0 END
+                    ),
+                    bool_and(
+                        CASE WHEN jsonb_typeof(value) = 'string'
+                        THEN value #>> '{{}}' ~ '^\d{{4}}-\d{{2}}-\d{{2}}T\d{{2}}:\d{{2}}' END
+                    )
+                FROM {} items, jsonb_each(items.properties)
+                GROUP BY key
+                "#,
+                sql::items_table(&collection.id)
+            ))
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut queryables = Queryables {
+            title: collection.title.to_owned(),
+            ..Default::default()
+        };
+
+        queryables.properties.insert(
+            "id".to_string(),
+            Queryable {
+                title: Some("Feature identifier".to_string()),
+                r#type: Some("string".to_string()),
+                ..Default::default()
+            },
+        );
+        queryables.properties.insert(
+            "geometry".to_string(),
+            Queryable {
+                r#ref: Some("https://geojson.org/schema/Geometry.json".to_string()),
+                ..Default::default()
+            },
+        );
+
+        for (key, types, integer, datetime) in rows {
+            let mut queryable = Queryable::default();
+            if let Some([r#type]) = types.as_deref() {
+                queryable.r#type = match r#type.as_str() {
+                    "number" if integer == Some(true) => Some("integer".to_string()),
+                    _ => Some(r#type.to_owned()),
+                };
+                if datetime == Some(true) {
+                    queryable.format = Some("date-time".to_string());
+                }
+            }
+            queryables.properties.insert(key, queryable);
+        }
+
+        // overrides from the collection metadata
+        if let Some(overrides) = collection.additional_properties.get("queryables") {
+            let overrides: BTreeMap<String, Queryable> =
+                serde_json::from_value(overrides.to_owned())?;
+            queryables.merge(overrides);
+        }
+
+        Ok(Some(queryables))
+    }
+}
+
+impl Db {
+    /// Statement selecting a page of features matching the query, along with its
+    /// parameters and the number of matching features
+    async fn select_items(
+        &self,
+        collection: &str,
+        query: &Query,
+    ) -> anyhow::Result<(String, Params, Option<u64>)> {
+        let table = sql::items_table(collection);
+        let mut params = Params::default();
+
+        // properties and geometry
+        let properties = properties(query, &mut params);
+        let spatial_ref = self.spatial_ref(&query.crs).await?;
+        let geometry = geometry(query, &spatial_ref, &mut params);
+
         let mut where_conditions = vec!["TRUE".to_owned()];
 
+        let definition = self
+            .read_collection(collection)
+            .await?
+            .ok_or_else(|| anyhow!("Collection `{collection}` not found"))?;
+        let storage_srid = self
+            .srid(&definition.storage_crs.unwrap_or_default())
+            .await?;
+
         // bbox
         if let Some(bbox) = query.bbox.as_ref() {
-            // TODO: Properly handle crs and bbox transformation
-            let bbox_srid: i32 = query.bbox_crs.as_srid();
-
-            let c = self.read_collection(collection).await?;
-            let storage_srid = c
-                .expect("collection exists")
-                .storage_crs
-                .unwrap_or_default()
-                .as_srid();
-
-            let envelope = match bbox {
-                Bbox::Bbox2D(bbox) => format!(
-                    "ST_MakeEnvelope({}, {}, {}, {}, {})",
-                    bbox[0], bbox[1], bbox[2], bbox[3], bbox_srid
-                ),
-                Bbox::Bbox3D(bbox) => format!(
-                    "ST_MakeEnvelope({}, {}, {}, {}, {})",
-                    bbox[0], bbox[1], bbox[3], bbox[4], bbox_srid
-                ),
-            };
-            where_conditions.push(format!(
-                "geom && ST_Transform({}, {})",
-                envelope, storage_srid
-            ));
+            let predicate = self
+                .bbox_predicate(bbox, &query.bbox_crs, storage_srid, &mut params)
+                .await?;
+            where_conditions.push(predicate);
         }
 
         // datetime
         if let Some(datetime) = query.datetime.as_ref() {
-            let (from, to) = match datetime {
-                Datetime::Datetime(_) => (
-                    format!("CAST('{datetime}' AS timestamptz)"),
-                    format!("CAST('{datetime}' AS timestamptz)"),
-                ),
-                Datetime::Interval { from, to } => {
-                    let from = match from {
-                        IntervalDatetime::Datetime(_) => {
-                            format!("CAST('{from}' AS timestamptz)")
-                        }
-                        IntervalDatetime::Open => "to_timestamp('-infinity')".to_owned(),
-                    };
-                    let to = match to {
-                        IntervalDatetime::Datetime(_) => {
-                            format!("CAST('{to}' AS timestamptz)")
-                        }
-                        IntervalDatetime::Open => "NOW()".to_owned(),
-                    };
-                    (from, to)
-                }
-            };
-
-            where_conditions.push(format!(
-                r#"
-                (
-                    CASE
-                        WHEN (properties->'datetime') IS NOT NULL THEN (
-                            CAST(properties->>'datetime' AS timestamptz)
-                            BETWEEN {from} AND {to}
-                        )
-                        WHEN (
-                            (properties->'datetime') IS NULL
-                            AND (properties->'start_datetime') IS NOT NULL
-                            AND (properties->'end_datetime') IS NOT NULL
-                        ) THEN (
-                            ({from}, {to}) OVERLAPS (
-                                CAST(properties->>'start_datetime' AS timestamptz),
-                                CAST(properties->>'end_datetime' AS timestamptz)
-                            )
-                        )
-                        ELSE TRUE
-                    END
-                )
-                "#
-            ));
+            let temporal = definition.temporal_properties.unwrap_or_default();
+            where_conditions.push(temporal::predicate(&temporal, datetime, &mut params));
         }
 
         // kv
-        for (k, v) in query.additional_parameters.iter() {
-            where_conditions.push(format!(
-                r#"
-                CASE
-                    WHEN properties ? '{k}' THEN (
-                        CASE
-                            WHEN jsonb_typeof(properties -> '{k}') = 'number'
-                            THEN RTRIM(properties ->> '{k}', '.0') = RTRIM('{v}', '.0')
-                            ELSE properties ->> '{k}' = '{v}'
-                        END
-                    ) 
-                    ELSE TRUE
-                END
-                "#
-            ));
+        if !query.additional_parameters.is_empty() {
+            let queryables = self.queryables(collection).await?.unwrap_or_default();
+            for (key, value) in query.additional_parameters.iter() {
+                let queryable = queryables.properties.get(key).cloned().unwrap_or_default();
+                where_conditions.push(property_predicate(key, value, &queryable, &mut params)?);
+            }
+        }
+
+        // filter
+        if let Some(expr) = query.filter_expr().map_err(InvalidFilter)? {
+            let filter_ref = self
+                .spatial_ref(&query.filter_crs.clone().unwrap_or_default())
+                .await?;
+            let mut filter = Filter::new(&mut params, storage_srid, &filter_ref);
+            where_conditions.push(filter.predicate(&expr)?);
+        }
+
+        // number matched, only counted for the first page
+        let number_matched = if query.token.is_some() {
+            None
+        } else if where_conditions.len() == 1 {
+            Some(self.count_items(collection).await?)
+        } else {
+            let conditions = where_conditions.join(" AND ");
+            let count: i64 = sqlx::query_scalar_with(
+                &format!(
+                    r#"
+                    SELECT count(*) FROM {table} items
+                    WHERE {conditions}
+                    "#,
+                ),
+                params.arguments(),
+            )
+            .fetch_one(&self.pool)
+            .await?;
+            Some(count as u64)
+        };
+
+        // sort keys with the primary key as tiebreaker for a stable order
+        let order = Order::new(
+            query.sortby.as_deref().unwrap_or_default(),
+            &["id"],
+            &mut params,
+        );
+        let order_by = order.order_by();
+
+        // keyset pagination
+        if let Some(token) = query.token.as_ref() {
+            where_conditions.push(order.after(token, &mut params)?);
         }
 
         let conditions = where_conditions.join(" AND ");
 
-        // count
-        let number_matched: (i64,) = sqlx::query_as(&format!(
+        let select = format!(
             r#"
-            SELECT count(*) FROM items."{collection}"
+            SELECT {ROWS}, {properties}, {geometry}
+            FROM {table} items JOIN meta.collections meta
+                ON items.collection = meta.id
             WHERE {conditions}
-            "#,
-        ))
-        .fetch_one(&self.pool)
-        .await?;
-
-        // fetch
-        let features: Option<sqlx::types::Json<Vec<Feature>>> = sqlx::query_scalar(&format!(
-            r#"
-            SELECT array_to_json(array_agg(row_to_json(t)))
-            FROM (
-                SELECT {ROWS}
-                FROM items."{collection}" items JOIN meta.collections meta
-                    ON items.collection = meta.id
-                WHERE {conditions}
-                LIMIT {}
-                OFFSET {}
-            ) t
+            {order_by}
+            LIMIT {}
+            OFFSET {}
             "#,
             query
                 .limit
                 .map_or_else(|| String::from("NULL"), |l| l.to_string()),
-            query.offset.unwrap_or(0)
-        ))
-        .bind(query.crs.as_srid())
-        .fetch_one(&self.pool)
-        .await?;
+            // the token continues after the last feature of the previous page
+            if query.token.is_some() {
+                0
+            } else {
+                query.offset.unwrap_or(0)
+            }
+        );
 
-        let features = features.map(|f| f.0).unwrap_or_default();
-        let mut fc = FeatureCollection::new(features);
-        fc.number_matched = Some(number_matched.0 as u64);
+        Ok((select, params, number_matched))
+    }
 
-        Ok(fc)
+    /// Tell apart a missing feature from a failed precondition if nothing was modified
+    async fn outcome(
+        &self,
+        collection: &str,
+        id: &str,
+        modified: Option<String>,
+    ) -> anyhow::Result<Outcome> {
+        if modified.is_some() {
+            Ok(Outcome::Applied)
+        } else if self.feature_etag(collection, id).await?.is_some() {
+            Ok(Outcome::PreconditionFailed)
+        } else {
+            Ok(Outcome::NotFound)
+        }
     }
 }
+
+/// Typed comparison of a property with the comma separated values of a query
+/// parameter. Features without the property do not match.
+fn property_predicate(
+    key: &str,
+    value: &str,
+    queryable: &Queryable,
+    params: &mut Params,
+) -> anyhow::Result<String> {
+    let values = value
+        .split(',')
+        .map(|v| {
+            queryable
+                .value(v)
+                .ok_or_else(|| anyhow!("Value `{v}` of property `{key}` not valid"))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let key = params.push(key);
+    let values = params.push(serde_json::Value::from(values));
+
+    Ok(
+        match (queryable.r#type.as_deref(), queryable.format.as_deref()) {
+            (Some("string"), Some("date-time")) => format!(
+                r#"
+                CASE WHEN jsonb_typeof(properties -> {key}) = 'string'
+                THEN meta.to_timestamptz(properties ->> {key}) IN (
+                    SELECT meta.to_timestamptz(jsonb_array_elements_text({values}))
+                )
+                END
+                "#
+            ),
+            // jsonb compares numbers by value, e.g. `1.0 = 1`
+            (Some(_), _) => {
+                format!("properties -> {key} IN (SELECT jsonb_array_elements({values}))")
+            }
+            // properties of mixed types compare as text
+            (None, _) => {
+                format!("properties ->> {key} IN (SELECT jsonb_array_elements_text({values}))")
+            }
+        },
+    )
+}
+
+/// Properties of a feature, only the selected ones if requested
+fn properties(query: &Query, params: &mut Params) -> String {
+    match query.properties.as_deref() {
+        Some([]) => "'{}'::jsonb AS properties".to_string(),
+        Some(keys) => {
+            let keys = keys
+                .iter()
+                .map(|key| params.push(key.as_str()))
+                .collect::<Vec<String>>();
+            format!(
+                r#"(
+                    SELECT COALESCE(jsonb_object_agg(key, value), '{{}}'::jsonb)
+                    FROM jsonb_each(items.properties)
+                    WHERE key IN ({})
+                ) AS properties"#,
+                keys.join(", ")
+            )
+        }
+        None => "items.properties".to_string(),
+    }
+}
+
+/// Geometry in the output crs, optionally simplified and with fewer decimal
+/// places
+fn geometry(query: &Query, spatial_ref: &SpatialRef, params: &mut Params) -> String {
+    if query.skip_geometry.unwrap_or_default() {
+        return "NULL::jsonb AS geometry".to_string();
+    }
+
+    let mut geometry = format!(
+        "ST_Transform(items.geom, {})",
+        params.push(spatial_ref.srid)
+    );
+
+    let tolerance = match (query.max_allowable_offset, query.zoom_level) {
+        (Some(offset), _) => Some(offset),
+        (None, Some(zoom)) => {
+            // pixel size at the equator, in degrees for geographic crs
+            let meters = CIRCUMFERENCE / (TILE_SIZE * 2_f64.powi(zoom.into()));
+            if spatial_ref.geographic {
+                Some(meters * 360.0 / CIRCUMFERENCE)
+            } else {
+                Some(meters)
+            }
+        }
+        (None, None) => None,
+    };
+    if let Some(tolerance) = tolerance {
+        geometry = format!(
+            "ST_SimplifyPreserveTopology({geometry}, {})",
+            params.push(tolerance)
+        );
+    }
+
+    // the coordinates in the axis order of the crs
+    if spatial_ref.axis_order == AxisOrder::YX {
+        geometry = format!("ST_FlipCoordinates({geometry})");
+    }
+
+    let precision = query.coordinate_precision.map_or(PRECISION, i32::from);
+    format!(
+        "ST_AsGeoJSON({geometry}, {})::jsonb AS geometry",
+        params.push(precision)
+    )
+}
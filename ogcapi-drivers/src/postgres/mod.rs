@@ -1,12 +1,24 @@
+mod bbox;
+mod cache;
 mod collection;
+mod cql2;
+mod crs;
 mod edr;
 mod feature;
 mod job;
+mod sort;
+mod sql;
 #[cfg(feature = "stac")]
 mod stac;
 mod style;
+mod temporal;
 mod tile;
 
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
 use sqlx::{
     migrate::MigrateDatabase,
     postgres::{PgConnectOptions, PgPool, PgPoolOptions},
@@ -14,12 +26,36 @@ use sqlx::{
 };
 use url::Url;
 
+use ogcapi_types::common::Crs;
+
+/// Tables with more rows than this are not counted exactly
+const EXACT_COUNT_LIMIT: f32 = 100_000.0;
+
 #[derive(Debug, Clone)]
 pub struct Db {
     pub pool: PgPool,
+    cache: cache::Cache,
+    /// Spatial reference systems of the crs looked up so far
+    spatial_refs: Arc<RwLock<HashMap<Crs, crs::SpatialRef>>>,
 }
 
 impl Db {
+    /// Create driver from a pool. The collections are not cached until
+    /// [`Db::cache_collections`] is called.
+    pub fn with_pool(pool: PgPool) -> Self {
+        Db {
+            pool,
+            cache: Default::default(),
+            spatial_refs: Default::default(),
+        }
+    }
+
+    /// Cache the collections, invalidated by the notifications of the
+    /// database in a background task. Done by [`Db::new`] and [`Db::setup`].
+    pub async fn cache_collections(&self) -> Result<(), sqlx::Error> {
+        self.cache.subscribe(&self.pool).await
+    }
+
     /// Create driver from env `DATABASE_URL` or else `PGUSER` and friends
     pub async fn new() -> Result<Self, sqlx::Error> {
         let pool = if let Ok(url) = std::env::var("DATABASE_URL") {
@@ -34,7 +70,10 @@ impl Db {
                 .await?
         };
 
-        Ok(Db { pool })
+        let db = Db::with_pool(pool);
+        db.cache_collections().await?;
+
+        Ok(db)
     }
 
     /// Setup database driver from url
@@ -53,6 +92,30 @@ impl Db {
         // Run embedded migrations
         sqlx::migrate!().run(&pool).await?;
 
-        Ok(Db { pool })
+        let db = Db::with_pool(pool);
+        db.cache_collections().await?;
+
+        Ok(db)
+    }
+
+    /// Number of features in a collection, estimated from the planner
+    /// statistics for large tables as an exact `count(*)` is too expensive.
+    pub(crate) async fn count_items(&self, collection: &str) -> anyhow::Result<u64> {
+        let table = sql::items_table(collection);
+
+        let estimate: f32 =
+            sqlx::query_scalar("SELECT reltuples FROM pg_class WHERE oid = $1::regclass")
+                .bind(&table)
+                .fetch_one(&self.pool)
+                .await?;
+
+        if estimate > EXACT_COUNT_LIMIT {
+            Ok(estimate as u64)
+        } else {
+            let count: i64 = sqlx::query_scalar(&format!("SELECT count(*) FROM {table}"))
+                .fetch_one(&self.pool)
+                .await?;
+            Ok(count as u64)
+        }
     }
 }
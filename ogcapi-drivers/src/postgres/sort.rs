@@ -0,0 +1,113 @@
+use anyhow::bail;
+use serde_json::Value;
+
+use ogcapi_types::{
+    common::Cursor,
+    features::{Direction, SortBy},
+};
+
+use crate::InvalidToken;
+
+use super::sql::Params;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Text,
+    Json,
+    Number,
+}
+
+/// Stable ordering of an `items` table from sort keys and unique tiebreaker columns.
+pub(crate) struct Order {
+    keys: Vec<(String, Kind, Direction)>,
+}
+
+impl Order {
+    pub(crate) fn new(sortby: &[SortBy], tiebreakers: &[&str], params: &mut Params) -> Self {
+        let mut keys = Vec::new();
+
+        for key in sortby {
+            let (expression, kind) = match key.field.as_str() {
+                "id" => ("items.id".to_string(), Kind::Text),
+                "collection" => ("items.collection".to_string(), Kind::Text),
+                "geometry.area" => (
+                    "ST_Area(ST_Transform(items.geom, 4326)::geography)".to_string(),
+                    Kind::Number,
+                ),
+                "geometry.length" => (
+                    "ST_Length(ST_Transform(items.geom, 4326)::geography)".to_string(),
+                    Kind::Number,
+                ),
+                field => (
+                    // missing properties sort like `null` instead of being excluded from
+                    // keyset comparisons
+                    format!(
+                        "COALESCE(items.properties -> {}, 'null'::jsonb)",
+                        params.push(field)
+                    ),
+                    Kind::Json,
+                ),
+            };
+            keys.push((expression, kind, key.direction));
+        }
+
+        for column in tiebreakers {
+            keys.push((format!("items.{column}"), Kind::Text, Direction::Asc));
+        }
+
+        Order { keys }
+    }
+
+    /// `ORDER BY` clause
+    pub(crate) fn order_by(&self) -> String {
+        let keys = self
+            .keys
+            .iter()
+            .map(|(expression, _, direction)| match direction {
+                Direction::Asc => format!("{expression} ASC"),
+                Direction::Desc => format!("{expression} DESC"),
+            })
+            .collect::<Vec<String>>();
+        format!("ORDER BY {}", keys.join(", "))
+    }
+
+    /// Predicate selecting the rows after the cursor
+    pub(crate) fn after(&self, cursor: &Cursor, params: &mut Params) -> anyhow::Result<String> {
+        if cursor.0.len() != self.keys.len() {
+            bail!(InvalidToken(format!(
+                "Expected {} values but found {}",
+                self.keys.len(),
+                cursor.0.len()
+            )));
+        }
+
+        let mut values = Vec::new();
+        for ((_, kind, _), value) in self.keys.iter().zip(cursor.0.iter()) {
+            let placeholder = match (kind, value) {
+                (Kind::Text, Value::String(s)) => params.push(s.as_str()),
+                (Kind::Json, value) => params.push(value.to_owned()),
+                (Kind::Number, Value::Number(n)) => params.push(n.as_f64().unwrap_or_default()),
+                _ => bail!(InvalidToken(format!("Unexpected value `{value}`"))),
+            };
+            values.push(placeholder);
+        }
+
+        // (a > x) OR (a = x AND b > y) OR ...
+        let mut disjunction = Vec::new();
+        for (i, (expression, _, direction)) in self.keys.iter().enumerate() {
+            let mut conjunction = self.keys[..i]
+                .iter()
+                .zip(values.iter())
+                .map(|((expression, _, _), value)| format!("{expression} = {value}"))
+                .collect::<Vec<String>>();
+            let op = match direction {
+                Direction::Asc => ">",
+                Direction::Desc => "<",
+            };
+            conjunction.push(format!("{expression} {op} {}", values[i]));
+            disjunction.push(format!("({})", conjunction.join(" AND ")));
+        }
+
+        Ok(format!("({})", disjunction.join(" OR ")))
+    }
+}
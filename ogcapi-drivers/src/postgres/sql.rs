@@ -0,0 +1,117 @@
+use anyhow::ensure;
+use sqlx::{postgres::PgArguments, types::Json, Arguments};
+
+/// Maximum length of identifiers in bytes, longer ones are truncated by Postgres
+pub(crate) const MAX_IDENTIFIER_LENGTH: usize = 63;
+
+/// Bind parameter value
+#[derive(Debug, Clone)]
+pub(crate) enum Value {
+    Int(i32),
+    Float(f64),
+    Text(String),
+    TextArray(Vec<String>),
+    Json(serde_json::Value),
+}
+
+impl From<i32> for Value {
+    fn from(v: i32) -> Self {
+        Value::Int(v)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(v: f64) -> Self {
+        Value::Float(v)
+    }
+}
+
+impl From<String> for Value {
+    fn from(v: String) -> Self {
+        Value::Text(v)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(v: &str) -> Self {
+        Value::Text(v.to_owned())
+    }
+}
+
+impl From<Vec<String>> for Value {
+    fn from(v: Vec<String>) -> Self {
+        Value::TextArray(v)
+    }
+}
+
+impl From<serde_json::Value> for Value {
+    fn from(v: serde_json::Value) -> Self {
+        Value::Json(v)
+    }
+}
+
+/// Collects the bind parameters of a dynamically built statement
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Params {
+    values: Vec<Value>,
+}
+
+impl Params {
+    /// Add a value and return its placeholder, e.g. `$1`
+    pub(crate) fn push(&mut self, value: impl Into<Value>) -> String {
+        self.values.push(value.into());
+        format!("${}", self.values.len())
+    }
+
+    /// Build the arguments to execute a statement with
+    pub(crate) fn arguments(&self) -> PgArguments {
+        let mut arguments = PgArguments::default();
+        for value in &self.values {
+            match value {
+                Value::Int(v) => arguments.add(*v),
+                Value::Float(v) => arguments.add(*v),
+                Value::Text(v) => arguments.add(v.to_owned()),
+                Value::TextArray(v) => arguments.add(v.to_owned()),
+                Value::Json(v) => arguments.add(Json(v.to_owned())),
+            }
+        }
+        arguments
+    }
+}
+
+/// Quoted identifier, e.g. of a table or column
+pub(crate) fn identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Quoted string literal, for statements that cannot have bind parameters
+/// like `CREATE INDEX`
+pub(crate) fn literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Text matching itself in a `LIKE` pattern
+pub(crate) fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Table with the features of a collection
+pub(crate) fn items_table(collection: &str) -> String {
+    format!("items.{}", identifier(collection))
+}
+
+/// Check that a collection id can name a table of its own
+pub(crate) fn validate_collection_id(id: &str) -> anyhow::Result<()> {
+    ensure!(!id.is_empty(), "Collection id must not be empty");
+    ensure!(
+        id.len() <= MAX_IDENTIFIER_LENGTH,
+        "Collection id `{id}` exceeds {MAX_IDENTIFIER_LENGTH} bytes"
+    );
+    ensure!(
+        !id.chars().any(char::is_control),
+        "Collection id must not contain control characters"
+    );
+    Ok(())
+}
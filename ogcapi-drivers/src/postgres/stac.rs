@@ -1,12 +1,17 @@
 use ogcapi_types::{
-    common::{Bbox, Datetime, IntervalDatetime},
+    common::{Crs, TemporalProperties},
     features::{Feature, FeatureCollection},
     stac::SearchParams,
 };
 
-use crate::StacSeach;
+use crate::{InvalidFilter, StacSeach};
 
-use super::Db;
+use super::{
+    cql2::Filter,
+    sort::Order,
+    sql::{self, Params},
+    temporal, Db,
+};
 
 #[async_trait::async_trait]
 impl StacSeach for Db {
@@ -14,30 +19,42 @@ impl StacSeach for Db {
         let mut tx = self.pool.begin().await?;
 
         // WITH
-        let mut collection_ids: Vec<String> = sqlx::query_scalar(
-            r#"
-            SELECT id FROM meta.collections 
-            WHERE collection ->> 'type' = 'Collection'
-            "#,
-        )
-        .fetch_all(&mut tx)
-        .await?;
-
-        if let Some(collections) = &query.collections {
-            collection_ids = collections
-                .iter()
-                .filter(|c| collection_ids.contains(c))
-                .map(|c| c.to_owned())
-                .collect();
+        let mut collections: Vec<(String, Option<sqlx::types::Json<TemporalProperties>>)> =
+            sqlx::query_as(
+                r#"
+                SELECT id, collection -> 'temporalProperties'
+                FROM meta.collections
+                WHERE collection ->> 'type' = 'Collection'
+                "#,
+            )
+            .fetch_all(&mut tx)
+            .await?;
+
+        if let Some(ids) = &query.collections {
+            collections.retain(|(id, _)| ids.contains(id));
         }
-
-        let union_all_items = collection_ids
-            .iter()
-            .map(|collection_id| {
+        let collection_ids: Vec<String> = collections.iter().map(|(id, _)| id.to_owned()).collect();
+
+        let mut params = Params::default();
+
+        // the temporal properties differ between collections
+        let union_all_items = collections
+            .into_iter()
+            .map(|(collection_id, temporal)| {
+                let datetime = match query.datetime.as_ref() {
+                    Some(datetime) => {
+                        let temporal = temporal.map(|t| t.0).unwrap_or_default();
+                        temporal::predicate(&temporal, datetime, &mut params)
+                    }
+                    None => "TRUE".to_string(),
+                };
                 format!(
                     r#"
-                    SELECT * FROM items."{collection_id}"
-                    "#
+                    SELECT id, collection, properties, geom, links, assets, bbox
+                    FROM {}
+                    WHERE {datetime}
+                    "#,
+                    sql::items_table(&collection_id)
                 )
             })
             .collect::<Vec<String>>()
@@ -48,116 +65,109 @@ impl StacSeach for Db {
 
         // bbox
         if let Some(bbox) = query.bbox.as_ref() {
-            let envelope = match bbox {
-                Bbox::Bbox2D(bbox) => format!(
-                    "ST_MakeEnvelope({}, {}, {}, {}, 4326)",
-                    bbox[0], bbox[1], bbox[2], bbox[3]
-                ),
-                Bbox::Bbox3D(bbox) => format!(
-                    "ST_MakeEnvelope({}, {}, {}, {}, 4326)",
-                    bbox[0], bbox[1], bbox[3], bbox[4]
-                ),
-            };
-            where_conditions.push(format!("geom && {}", envelope));
+            let predicate = self
+                .bbox_predicate(bbox, &Crs::default(), 4326, &mut params)
+                .await?;
+            where_conditions.push(predicate);
         }
 
-        // datetime
-        if let Some(datetime) = query.datetime.as_ref() {
-            let (from, to) = match datetime {
-                Datetime::Datetime(_) => (
-                    format!("CAST('{datetime}' AS timestamptz)"),
-                    format!("CAST('{datetime}' AS timestamptz)"),
-                ),
-                Datetime::Interval { from, to } => {
-                    let from = match from {
-                        IntervalDatetime::Datetime(_) => {
-                            format!("CAST('{from}' AS timestamptz)")
-                        }
-                        IntervalDatetime::Open => "to_timestamp('-infinity')".to_owned(),
-                    };
-                    let to = match to {
-                        IntervalDatetime::Datetime(_) => {
-                            format!("CAST('{to}' AS timestamptz)")
-                        }
-                        IntervalDatetime::Open => "NOW()".to_owned(),
-                    };
-                    (from, to)
-                }
-            };
+        // ids
+        if let Some(ids) = query.ids.as_ref() {
+            where_conditions.push(format!("id = ANY({})", params.push(ids.to_owned())))
+        }
 
+        // intersects
+        if let Some(intersects) = query.intersects.as_ref() {
             where_conditions.push(format!(
-                r#"
-                (
-                    CASE
-                        WHEN (properties->'datetime') IS NOT NULL THEN (
-                            CAST(properties->>'datetime' AS timestamptz)
-                            BETWEEN {from} AND {to}
-                        )
-                        WHEN (
-                            (properties->'datetime') IS NULL
-                            AND (properties->'start_datetime') IS NOT NULL
-                            AND (properties->'end_datetime') IS NOT NULL
-                        ) THEN (
-                            ({from}, {to}) OVERLAPS (
-                                CAST(properties->>'start_datetime' AS timestamptz),
-                                CAST(properties->>'end_datetime' AS timestamptz)
-                            )
-                        )
-                        ELSE TRUE
-                    END
-                )
-                "#
+                "geom && ST_GeomFromGeoJSON({})",
+                params.push(intersects.to_string())
             ));
         }
 
-        // ids
-        if let Some(ids) = query.ids.as_ref() {
-            where_conditions.push(format!("id IN ('{}')", ids.join("','")))
+        // filter
+        if let Some(expr) = query.filter_expr().map_err(InvalidFilter)? {
+            let filter_ref = self
+                .spatial_ref(&query.filter_crs.clone().unwrap_or_default())
+                .await?;
+            let mut filter = Filter::new(&mut params, 4326, &filter_ref);
+            where_conditions.push(filter.predicate(&expr)?);
         }
 
-        // intersects
-        if let Some(intersects) = query.intersects.as_ref() {
-            where_conditions.push(format!("geom && ST_GeomFromGeoJSON('{}')", intersects));
+        // COUNT, only for the first page
+        let number_matched = if query.token.is_some() {
+            None
+        } else if where_conditions.len() == 1 && query.datetime.is_none() {
+            let mut count = 0;
+            for collection_id in &collection_ids {
+                count += self.count_items(collection_id).await?;
+            }
+            Some(count)
+        } else {
+            let conditions = where_conditions.join(" AND ");
+            let count: i64 = sqlx::query_scalar_with(
+                &format!(
+                    r#"
+                    WITH items AS ({union_all_items})
+                    SELECT count(*) FROM items
+                    WHERE {conditions}
+                    "#,
+                ),
+                params.arguments(),
+            )
+            .fetch_one(&mut tx)
+            .await?;
+            Some(count as u64)
+        };
+
+        // sort keys with collection and id as tiebreaker for a stable order
+        let order = Order::new(
+            query.sortby.as_deref().unwrap_or_default(),
+            &["collection", "id"],
+            &mut params,
+        );
+        let order_by = order.order_by();
+
+        // keyset pagination
+        if let Some(token) = query.token.as_ref() {
+            where_conditions.push(order.after(token, &mut params)?);
         }
 
         let conditions = where_conditions.join(" AND ");
 
-        // COUNT
-        let number_matched: (i64,) = sqlx::query_as(&format!(
-            r#"
-            WITH items AS ({union_all_items})
-            SELECT count(*) FROM items
-            WHERE {conditions}
-            "#,
-        ))
-        .fetch_one(&mut tx)
-        .await?;
-
         // FETCH
-        let features: Option<sqlx::types::Json<Vec<Feature>>> = sqlx::query_scalar(&format!(
-            r#"
-            WITH items AS ({union_all_items})
-            SELECT array_to_json(array_agg(row_to_json(t)))
-            FROM (
-                SELECT
-                    id,
-                    collection,
-                    properties,
-                    ST_AsGeoJSON(ST_Transform(geom, 4326))::jsonb as geometry,
-                    links,
-                    assets,
-                    bbox
-                FROM items
-                WHERE {conditions}
-                LIMIT {}
-                OFFSET {}
-            ) t
-            "#,
-            query
-                .limit
-                .map_or_else(|| String::from("NULL"), |l| l.to_string()),
-            query.offset.unwrap_or(0)
-        ))
+        let features: Option<sqlx::types::Json<Vec<Feature>>> = sqlx::query_scalar_with(
+            &format!(
+                r#"
+                WITH items AS ({union_all_items})
+                SELECT array_to_json(array_agg(row_to_json(t)))
+                FROM (
+                    SELECT
+                        id,
+                        collection,
+                        properties,
+                        ST_AsGeoJSON(ST_Transform(geom, 4326))::jsonb as geometry,
+                        links,
+                        assets,
+                        bbox
+                    FROM items
+                    WHERE {conditions}
+                    {order_by}
+                    LIMIT {}
+                    OFFSET {}
+                ) t
+                "#,
+                query
+                    .limit
+                    .map_or_else(|| String::from("NULL"), |l| l.to_string()),
+                // the token continues after the last feature of the previous page
+                if query.token.is_some() {
+                    0
+                } else {
+                    query.offset.unwrap_or(0)
+                }
+            ),
+            params.arguments(),
+        )
         .fetch_one(&mut tx)
         .await?;
 
@@ -165,7 +175,7 @@ impl StacSeach for Db {
 
         let features = features.map(|f| f.0).unwrap_or_default();
         let mut fc = FeatureCollection::new(features);
-        fc.number_matched = Some(number_matched.0 as u64);
+        fc.number_matched = number_matched;
 
         Ok(fc)
     }
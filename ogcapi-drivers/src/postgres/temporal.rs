@@ -0,0 +1,224 @@
+use sqlx::PgConnection;
+
+use ogcapi_types::common::{
+    Collection, Datetime, IntervalDatetime, TemporalProperties, TemporalProperty,
+};
+
+use crate::InvalidCollection;
+
+use super::sql::{self, Params, MAX_IDENTIFIER_LENGTH};
+
+/// Columns of the `items` tables, which a temporal property cannot be kept in
+const RESERVED_COLUMNS: [&str; 7] = [
+    "id",
+    "collection",
+    "properties",
+    "geom",
+    "links",
+    "assets",
+    "bbox",
+];
+
+/// Predicate of the features with an instant or interval intersecting the
+/// `datetime`. Features without a temporal extent never match.
+pub(crate) fn predicate(
+    temporal: &TemporalProperties,
+    datetime: &Datetime,
+    params: &mut Params,
+) -> String {
+    let (from, to) = range(datetime, params);
+
+    let mut predicates = Vec::new();
+
+    if let Some(instant) = temporal.instant.as_ref().map(expression) {
+        predicates.push(format!("{instant} BETWEEN {from} AND {to}"));
+    }
+
+    // a missing bound of the interval is open
+    let start = temporal.start.as_ref().map(expression);
+    let end = temporal.end.as_ref().map(expression);
+    match (start, end) {
+        (Some(start), Some(end)) => predicates.push(format!(
+            r#"(
+                ({start} IS NOT NULL OR {end} IS NOT NULL)
+                AND ({start} IS NULL OR {start} <= {to})
+                AND ({end} IS NULL OR {end} >= {from})
+            )"#
+        )),
+        (Some(start), None) => predicates.push(format!("{start} <= {to}")),
+        (None, Some(end)) => predicates.push(format!("{end} >= {from}")),
+        (None, None) => {}
+    }
+
+    if predicates.is_empty() {
+        "FALSE".to_string()
+    } else {
+        format!("({})", predicates.join(" OR "))
+    }
+}
+
+/// Predicate of the collections with an interval of their temporal extent
+/// intersecting the `datetime`. Collections without a temporal extent never
+/// match.
+pub(crate) fn extent_predicate(datetime: &Datetime, params: &mut Params) -> String {
+    let (from, to) = range(datetime, params);
+
+    format!(
+        r#"EXISTS (
+            SELECT
+            FROM jsonb_array_elements(collection #> '{{extent,temporal,interval}}') AS extent(interval)
+            WHERE (interval ->> 0 IS NULL OR meta.to_timestamptz(interval ->> 0) <= {to})
+            AND (interval ->> 1 IS NULL OR meta.to_timestamptz(interval ->> 1) >= {from})
+        )"#
+    )
+}
+
+/// Placeholders of the first and last instant of the `datetime`
+fn range(datetime: &Datetime, params: &mut Params) -> (String, String) {
+    let mut timestamp = |value: String| format!("{}::timestamptz", params.push(value));
+    match datetime {
+        Datetime::Datetime(datetime) => {
+            let instant = timestamp(datetime.to_rfc3339());
+            (instant.to_owned(), instant)
+        }
+        Datetime::Interval { from, to } => (
+            timestamp(bound(from, "-infinity")),
+            timestamp(bound(to, "infinity")),
+        ),
+    }
+}
+
+fn bound(datetime: &IntervalDatetime, open: &str) -> String {
+    match datetime {
+        IntervalDatetime::Datetime(datetime) => datetime.to_rfc3339(),
+        IntervalDatetime::Open => open.to_owned(),
+    }
+}
+
+/// Expressions of the first and last instant of a feature over all its
+/// temporal properties
+pub(crate) fn bounds(temporal: &TemporalProperties) -> (String, String) {
+    let extreme = |function: &str, properties: [&Option<TemporalProperty>; 2]| {
+        let expressions: Vec<String> = properties.into_iter().flatten().map(expression).collect();
+        if expressions.is_empty() {
+            "NULL::timestamptz".to_string()
+        } else {
+            format!("{function}({})", expressions.join(", "))
+        }
+    };
+
+    (
+        extreme("LEAST", [&temporal.instant, &temporal.start]),
+        extreme("GREATEST", [&temporal.instant, &temporal.end]),
+    )
+}
+
+/// Timestamp of a feature, matching the expression of its index
+fn expression(property: &TemporalProperty) -> String {
+    match property {
+        TemporalProperty::Property(name) => {
+            format!("meta.to_timestamptz(properties ->> {})", sql::literal(name))
+        }
+        TemporalProperty::Column(name) => sql::identifier(name),
+    }
+}
+
+/// Temporal properties of a collection
+fn properties(collection: &Collection) -> Vec<TemporalProperty> {
+    let temporal = collection
+        .temporal_properties
+        .to_owned()
+        .unwrap_or_default();
+    [temporal.instant, temporal.start, temporal.end]
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// Check that the dedicated columns of the temporal properties neither clash
+/// with the columns of the `items` table nor get truncated
+pub(crate) fn validate(collection: &Collection) -> Result<(), InvalidCollection> {
+    for property in properties(collection) {
+        if let TemporalProperty::Column(name) = property {
+            if RESERVED_COLUMNS.contains(&name.as_str()) {
+                return Err(InvalidCollection(format!(
+                    "Temporal property `{name}` cannot be kept in a column, the name is reserved"
+                )));
+            }
+            if name.len() > MAX_IDENTIFIER_LENGTH {
+                return Err(InvalidCollection(format!(
+                    "Temporal property `{name}` cannot be kept in a column, the name exceeds {MAX_IDENTIFIER_LENGTH} bytes"
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Name of the index of a temporal property, hashed to stay unique within the
+/// length limit of identifiers
+async fn index_name(
+    conn: &mut PgConnection,
+    collection: &str,
+    property: &TemporalProperty,
+) -> sqlx::Result<String> {
+    sqlx::query_scalar("SELECT meta.temporal_index($1, $2)")
+        .bind(collection)
+        .bind(property.name())
+        .fetch_one(conn)
+        .await
+}
+
+/// Create the dedicated columns and the indexes of the temporal properties of
+/// a collection, unless they exist already
+pub(crate) async fn index(conn: &mut PgConnection, collection: &Collection) -> sqlx::Result<()> {
+    let table = sql::items_table(&collection.id);
+
+    for property in properties(collection) {
+        if let TemporalProperty::Column(name) = &property {
+            sqlx::query(&format!(
+                r#"
+                ALTER TABLE {table} ADD COLUMN IF NOT EXISTS {} timestamptz
+                GENERATED ALWAYS AS (meta.to_timestamptz(properties ->> {})) STORED
+                "#,
+                sql::identifier(name),
+                sql::literal(name)
+            ))
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        let index = index_name(conn, &collection.id, &property).await?;
+        sqlx::query(&format!(
+            "CREATE INDEX IF NOT EXISTS {} ON {table} (({}))",
+            sql::identifier(&index),
+            expression(&property)
+        ))
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Rename the indexes of the temporal properties of a collection moved from
+/// the id `from`
+pub(crate) async fn rename(
+    conn: &mut PgConnection,
+    collection: &Collection,
+    from: &str,
+) -> sqlx::Result<()> {
+    for property in properties(collection) {
+        let index = index_name(conn, from, &property).await?;
+        let new_index = index_name(conn, &collection.id, &property).await?;
+        sqlx::query(&format!(
+            "ALTER INDEX IF EXISTS items.{} RENAME TO {}",
+            sql::identifier(&index),
+            sql::identifier(&new_index)
+        ))
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
@@ -2,7 +2,7 @@ use ogcapi_types::tiles::TileMatrixSet;
 
 use crate::{CollectionTransactions, TileTransactions};
 
-use super::Db;
+use super::{sql, Db};
 
 #[async_trait::async_trait]
 impl TileTransactions for Db {
@@ -18,27 +18,29 @@ impl TileTransactions for Db {
 
         for collection in collections.split(',') {
             if let Some(c) = self.read_collection(collection).await? {
-                let storage_srid = c.storage_crs.unwrap_or_default().as_srid();
+                let storage_srid = self.srid(&c.storage_crs.unwrap_or_default()).await?;
 
                 sql.push(format!(
                     r#"
-                    SELECT ST_AsMVT(mvtgeom, '{0}', 4096, 'geom')
+                    SELECT ST_AsMVT(mvtgeom, {0}, 4096, 'geom')
                     FROM (
                         SELECT
                             ST_AsMVTGeom(ST_Transform(ST_Force2D(geom), 3857), ST_TileEnvelope($1, $3, $2), 4096, 64, TRUE) AS geom,
-                            '{0}' as collection,
+                            {0} as collection,
                             properties
-                        FROM items.{0}
-                        WHERE geom && ST_Transform(ST_TileEnvelope($1, $3, $2, margin => (64.0 / 4096)), {1})
+                        FROM {1}
+                        WHERE geom && ST_Transform(ST_TileEnvelope($1, $3, $2, margin => (64.0 / 4096)), {2})
                     ) AS mvtgeom
                     "#,
-                    collection,storage_srid
+                    sql::literal(collection),
+                    sql::items_table(collection),
+                    storage_srid
                 ));
             };
         }
 
         let tiles: Vec<Vec<u8>> = sqlx::query_scalar(&sql.join(" UNION ALL "))
-            .bind(matrix.parse::<i32>().unwrap())
+            .bind(matrix.parse::<i32>()?)
             .bind(row as i32)
             .bind(col as i32)
             .fetch_all(&self.pool)
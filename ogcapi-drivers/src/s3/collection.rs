@@ -1,6 +1,7 @@
+use anyhow::bail;
 use aws_sdk_s3::{error::GetObjectErrorKind, types::SdkError};
 
-use ogcapi_types::common::{media_type::JSON, Collection, Collections, Query};
+use ogcapi_types::common::{media_type::JSON, Collection, Collections, Crs, Query};
 
 use crate::CollectionTransactions;
 
@@ -59,6 +60,14 @@ impl CollectionTransactions for S3 {
         Ok(())
     }
 
+    async fn rename_collection(
+        &self,
+        _id: &str,
+        _collection: &Collection,
+    ) -> Result<(), anyhow::Error> {
+        bail!("Renaming collections is not supported by the S3 driver")
+    }
+
     async fn delete_collection(&self, id: &str) -> Result<(), anyhow::Error> {
         let key = format!("collections/{}", id);
 
@@ -68,7 +77,7 @@ impl CollectionTransactions for S3 {
         Ok(())
     }
 
-    async fn list_collections(&self, _query: &Query) -> Result<Collections, anyhow::Error> {
+    async fn list_collections(&self, query: &Query) -> Result<Collections, anyhow::Error> {
         let mut collections = Vec::new();
 
         let resp = self
@@ -92,9 +101,61 @@ impl CollectionTransactions for S3 {
             }
         }
 
+        // longitude/latitude bbox
+        let bbox = match (query.bbox.as_ref(), query.bbox_crs.as_ref()) {
+            (Some(bbox), Some(crs)) if *crs == Crs::from_epsg(4326) => Some(bbox.swap_axes()),
+            (Some(_), Some(crs)) if *crs != Crs::default() => {
+                bail!("Unsupported bbox crs `{crs}`")
+            }
+            (bbox, _) => bbox.cloned(),
+        };
+
+        let terms: Option<Vec<String>> = query
+            .q
+            .as_ref()
+            .map(|terms| terms.iter().map(|term| term.to_lowercase()).collect());
+
+        collections.retain(|collection: &Collection| {
+            let extent = collection.extent.as_ref();
+            bbox.as_ref().map_or(true, |bbox| {
+                extent
+                    .and_then(|e| e.spatial.as_ref())
+                    .map_or(false, |spatial| spatial.intersects(bbox))
+            }) && query.datetime.as_ref().map_or(true, |datetime| {
+                extent
+                    .and_then(|e| e.temporal.as_ref())
+                    .map_or(false, |temporal| temporal.intersects(datetime))
+            }) && terms.as_ref().map_or(true, |terms| {
+                let text = [
+                    collection.title.as_deref().unwrap_or_default(),
+                    collection.description.as_deref().unwrap_or_default(),
+                    &collection.keywords.join(" "),
+                ]
+                .join(" ")
+                .to_lowercase();
+                terms.iter().any(|term| text.contains(term))
+            })
+        });
+        collections.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let number_matched = collections.len();
+        let collections = collections
+            .into_iter()
+            .skip(query.offset.unwrap_or(0))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .collect();
+
         let mut collections = Collections::new(collections);
-        collections.number_matched = collections.number_matched.to_owned();
+        collections.number_matched = Some(number_matched as u64);
 
         Ok(collections)
     }
+
+    async fn update_extent(
+        &self,
+        _id: &str,
+        _summaries: bool,
+    ) -> Result<Option<Collection>, anyhow::Error> {
+        bail!("Computing the extent is not supported by the S3 driver")
+    }
 }
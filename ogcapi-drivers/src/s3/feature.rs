@@ -1,11 +1,13 @@
+use std::collections::HashSet;
+
 use aws_sdk_s3::{error::GetObjectErrorKind, types::SdkError};
 
 use ogcapi_types::{
-    common::{media_type::GEO_JSON, Crs},
-    features::{Feature, FeatureCollection, Query},
+    common::media_type::GEO_JSON,
+    features::{BulkError, BulkMode, BulkReport, Feature, FeatureCollection, Query, Queryables},
 };
 
-use crate::FeatureTransactions;
+use crate::{FeatureTransactions, Outcome};
 
 use super::S3;
 
@@ -30,21 +32,80 @@ impl FeatureTransactions for S3 {
         Ok(key)
     }
 
+    /// Features are validated up front, but objects are written one by one and
+    /// not rolled back if writing fails midway.
+    async fn create_features(
+        &self,
+        collection: &str,
+        features: &[Feature],
+        mode: BulkMode,
+    ) -> anyhow::Result<BulkReport> {
+        let mut report = BulkReport::default();
+
+        let mut ids = HashSet::new();
+        for (index, feature) in features.iter().enumerate() {
+            let id = match feature.id.as_deref() {
+                Some(id) => id,
+                None => {
+                    report
+                        .errors
+                        .push(BulkError::new(index, None, "Missing id"));
+                    continue;
+                }
+            };
+            if !ids.insert(id) {
+                report
+                    .errors
+                    .push(BulkError::new(index, Some(id), "Duplicate id"));
+            } else if mode == BulkMode::Insert && self.feature_etag(collection, id).await?.is_some()
+            {
+                report
+                    .errors
+                    .push(BulkError::new(index, Some(id), "Id already exists"));
+            }
+        }
+
+        if !report.is_ok() {
+            return Ok(report);
+        }
+
+        for feature in features {
+            let mut feature = feature.to_owned();
+            feature.collection = Some(collection.to_owned());
+            self.create_feature(&feature).await?;
+            report.ids.extend(feature.id);
+        }
+
+        Ok(report)
+    }
+
     async fn read_feature(
         &self,
         collection: &str,
         id: &str,
-        _crs: &Crs,
+        query: &Query,
     ) -> anyhow::Result<Option<Feature>> {
+        let feature = self.read_feature_with_etag(collection, id, query).await?;
+        Ok(feature.map(|(feature, _)| feature))
+    }
+
+    async fn read_feature_with_etag(
+        &self,
+        collection: &str,
+        id: &str,
+        _query: &Query,
+    ) -> anyhow::Result<Option<(Feature, String)>> {
         let key = format!("collections/{}/items/{}.json", collection, id);
 
         match self
             .get_object(self.bucket.clone().unwrap_or_default(), &key)
             .await
         {
-            Ok(r) => Ok(Some(serde_json::from_slice(
-                &r.body.collect().await?.into_bytes(),
-            )?)),
+            Ok(r) => {
+                let etag = r.e_tag().unwrap_or_default().trim_matches('"').to_string();
+                let feature = serde_json::from_slice(&r.body.collect().await?.into_bytes())?;
+                Ok(Some((feature, etag)))
+            }
             Err(e) => match e {
                 SdkError::ServiceError { err, raw: _ } => match err.kind {
                     GetObjectErrorKind::NoSuchKey(_) => Ok(None),
@@ -54,12 +115,51 @@ impl FeatureTransactions for S3 {
             },
         }
     }
-    async fn update_feature(&self, feature: &Feature) -> anyhow::Result<()> {
-        let key = format!(
-            "collections/{}/items/{}.json",
-            feature.collection.as_ref().unwrap(),
-            feature.id.as_ref().unwrap()
-        );
+
+    async fn feature_etag(&self, collection: &str, id: &str) -> anyhow::Result<Option<String>> {
+        let key = format!("collections/{}/items/{}.json", collection, id);
+
+        match self
+            .get_object(self.bucket.clone().unwrap_or_default(), &key)
+            .await
+        {
+            Ok(r) => Ok(r.e_tag().map(|e| e.trim_matches('"').to_string())),
+            Err(e) => match e {
+                SdkError::ServiceError { err, raw: _ } => match err.kind {
+                    GetObjectErrorKind::NoSuchKey(_) => Ok(None),
+                    _ => Err(anyhow::Error::new(err)),
+                },
+                _ => Err(anyhow::Error::new(e)),
+            },
+        }
+    }
+
+    async fn update_feature(
+        &self,
+        feature: &Feature,
+        if_match: Option<&str>,
+        keep_geometry: bool,
+    ) -> anyhow::Result<Outcome> {
+        let collection = feature.collection.as_ref().unwrap();
+        let id = feature.id.as_ref().unwrap();
+
+        match self.feature_etag(collection, id).await? {
+            None => return Ok(Outcome::NotFound),
+            Some(etag) if if_match.map_or(false, |m| m != etag) => {
+                return Ok(Outcome::PreconditionFailed)
+            }
+            _ => (),
+        }
+
+        let mut feature = feature.to_owned();
+        if keep_geometry {
+            feature.geometry = self
+                .read_feature(collection, id, &Query::default())
+                .await?
+                .and_then(|f| f.geometry);
+        }
+
+        let key = format!("collections/{}/items/{}.json", collection, id);
         let data = serde_json::to_vec(&feature)?;
 
         self.put_object(
@@ -70,16 +170,29 @@ impl FeatureTransactions for S3 {
         )
         .await?;
 
-        Ok(())
+        Ok(Outcome::Applied)
     }
 
-    async fn delete_feature(&self, collection: &str, id: &str) -> anyhow::Result<()> {
+    async fn delete_feature(
+        &self,
+        collection: &str,
+        id: &str,
+        if_match: Option<&str>,
+    ) -> anyhow::Result<Outcome> {
+        match self.feature_etag(collection, id).await? {
+            None => return Ok(Outcome::NotFound),
+            Some(etag) if if_match.map_or(false, |m| m != etag) => {
+                return Ok(Outcome::PreconditionFailed)
+            }
+            _ => (),
+        }
+
         let key = format!("collections/{}/items/{}.json", collection, id);
 
         self.delete_object(self.bucket.clone().unwrap_or_default(), &key)
             .await?;
 
-        Ok(())
+        Ok(Outcome::Applied)
     }
 
     async fn list_items(
@@ -89,4 +202,8 @@ impl FeatureTransactions for S3 {
     ) -> anyhow::Result<FeatureCollection> {
         unimplemented!()
     }
+
+    async fn queryables(&self, _collection: &str) -> anyhow::Result<Option<Queryables>> {
+        unimplemented!()
+    }
 }
@@ -0,0 +1,411 @@
+#[cfg(feature = "postgres")]
+mod postgres {
+    use ogcapi_drivers::{
+        postgres::Db, CollectionTransactions, FeatureTransactions, InvalidCollection,
+    };
+    use ogcapi_types::{
+        common::{
+            Bbox, Collection, Crs, Extent, Query, SpatialExtent, TemporalExtent,
+            TemporalProperties, TemporalProperty,
+        },
+        features::{self, BulkMode, Feature},
+    };
+    use serde_json::json;
+
+    #[sqlx::test]
+    async fn list_collections(pool: sqlx::PgPool) -> () {
+        let db = Db::with_pool(pool);
+
+        let collections = [
+            (
+                "alps",
+                [5.0, 43.0, 17.0, 48.0],
+                "2020-01-01T00:00:00Z",
+                "Mountains",
+            ),
+            (
+                "fiji",
+                [177.0, -19.0, -179.0, -16.0],
+                "2021-01-01T00:00:00Z",
+                "Islands",
+            ),
+            (
+                "lakes",
+                [6.0, 46.0, 10.0, 47.5],
+                "2022-01-01T00:00:00Z",
+                "Lakes and rivers",
+            ),
+        ];
+        for (id, bbox, start, title) in collections {
+            let collection = Collection {
+                id: id.to_string(),
+                title: Some(title.to_string()),
+                keywords: vec!["100%".to_string()],
+                extent: Some(Extent {
+                    spatial: Some(SpatialExtent {
+                        bbox: vec![Bbox::from(bbox)],
+                        ..Default::default()
+                    }),
+                    temporal: Some(TemporalExtent {
+                        interval: vec![vec![Some(start.parse().unwrap()), None]],
+                        ..Default::default()
+                    }),
+                }),
+                ..Default::default()
+            };
+            db.create_collection(&collection).await.unwrap();
+        }
+
+        let ids = |query: Query| {
+            let db = &db;
+            async move {
+                let collections = db.list_collections(&query).await.unwrap();
+                let ids: Vec<String> = collections.collections.into_iter().map(|c| c.id).collect();
+                (ids, collections.number_matched)
+            }
+        };
+
+        // paging ordered by id
+        let query = Query {
+            limit: Some(2),
+            offset: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(query).await,
+            (vec!["fiji".into(), "lakes".into()], Some(3))
+        );
+
+        // spatial extent, across the antimeridian
+        let query = Query {
+            bbox: Some(Bbox::from([178.0, -20.0, 180.0, -10.0])),
+            ..Default::default()
+        };
+        assert_eq!(ids(query).await.0, ["fiji"]);
+
+        let query = Query {
+            bbox: Some(Bbox::from([46.5, 7.0, 47.0, 8.0])),
+            bbox_crs: Some(Crs::from_epsg(4326)),
+            ..Default::default()
+        };
+        assert_eq!(ids(query).await.0, ["alps", "lakes"]);
+
+        // temporal extent
+        let query = Query {
+            datetime: Some("../2020-06-01T00:00:00Z".parse().unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(ids(query).await.0, ["alps"]);
+
+        // free text
+        let query = Query {
+            q: Some(vec!["RIVER".to_string(), "island".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(ids(query).await.0, ["fiji", "lakes"]);
+
+        let query = Query {
+            q: Some(vec!["0%".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(ids(query).await.1, Some(3));
+
+        let query = Query {
+            q: Some(vec!["_".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(ids(query).await.1, Some(0));
+    }
+
+    #[sqlx::test]
+    async fn update_extent(pool: sqlx::PgPool) -> () {
+        let db = Db::with_pool(pool);
+
+        let collection = Collection {
+            id: "extent".to_string(),
+            crs: vec![Crs::default(), Crs::from_epsg(4326)],
+            storage_crs: Some(Crs::from_epsg(4326)),
+            ..Default::default()
+        };
+        db.create_collection(&collection).await.unwrap();
+
+        // no items
+        let collection = db.update_extent("extent", false).await.unwrap().unwrap();
+        assert!(collection.extent.is_none());
+
+        let features: Vec<Feature> = serde_json::from_value(json!([{
+            "type": "Feature",
+            "properties": { "datetime": "2022-01-01T00:00:00Z", "kind": "a", "size": 1 },
+            "geometry": { "type": "Point", "coordinates": [7.0, 46.0] }
+        }, {
+            "type": "Feature",
+            "properties": { "start_datetime": "2021-06-01T00:00:00Z", "kind": "b", "size": 5 },
+            "geometry": { "type": "LineString", "coordinates": [[8.0, 45.0], [9.0, 47.0]] }
+        }]))
+        .unwrap();
+        db.create_features("extent", &features, BulkMode::Insert)
+            .await
+            .unwrap();
+
+        let collection = db.update_extent("extent", false).await.unwrap().unwrap();
+        let extent = collection.extent.unwrap();
+
+        let spatial = extent.spatial.unwrap();
+        assert_eq!(spatial.bbox, vec![Bbox::from([7.0, 45.0, 9.0, 47.0])]);
+        assert_eq!(
+            spatial.storage_crs_bbox,
+            Some(vec![Bbox::from([45.0, 7.0, 47.0, 9.0])])
+        );
+
+        let temporal = extent.temporal.unwrap();
+        assert_eq!(
+            temporal.interval,
+            vec![vec![
+                Some("2021-06-01T00:00:00Z".parse().unwrap()),
+                Some("2022-01-01T00:00:00Z".parse().unwrap())
+            ]]
+        );
+
+        // persisted with the collection
+        let stored = db.read_collection("extent").await.unwrap().unwrap();
+        assert_eq!(stored.extent.unwrap().temporal, Some(temporal));
+
+        #[cfg(feature = "stac")]
+        {
+            let collection = db.update_extent("extent", true).await.unwrap().unwrap();
+            assert_eq!(
+                collection.summaries["size"],
+                json!({ "minimum": 1.0, "maximum": 5.0 })
+            );
+            assert_eq!(collection.summaries["kind"], json!(["a", "b"]));
+        }
+
+        assert!(db.update_extent("missing", false).await.unwrap().is_none());
+    }
+
+    #[sqlx::test]
+    async fn migrate_and_rename(pool: sqlx::PgPool) -> () {
+        let db = Db::with_pool(pool);
+
+        let mut collection = Collection {
+            id: "old".to_string(),
+            crs: vec![Crs::default(), Crs::from_epsg(2056)],
+            ..Default::default()
+        };
+        db.create_collection(&collection).await.unwrap();
+
+        let feature: Feature = serde_json::from_value(json!({
+            "id": "bern",
+            "collection": "old",
+            "type": "Feature",
+            "properties": { "datetime": "2022-01-01T00:00:00Z" },
+            "geometry": { "type": "Point", "coordinates": [7.44, 46.95] }
+        }))
+        .unwrap();
+        db.create_feature(&feature).await.unwrap();
+
+        sqlx::query(
+            r#"ALTER TABLE items."old" ALTER COLUMN geom TYPE geometry(GeometryZ, 4326) USING ST_Force3D(geom)"#,
+        )
+        .execute(&db.pool)
+        .await
+        .unwrap();
+
+        // reprojected into the new storage crs, keeping the dimensions
+        collection.storage_crs = Some(Crs::from_epsg(2056));
+        db.update_collection(&collection).await.unwrap();
+
+        let (srid, r#type): (i32, String) = sqlx::query_as(
+            r#"
+            SELECT ST_SRID(geom), postgis_typmod_type(atttypmod)
+            FROM items."old", pg_attribute
+            WHERE attrelid = 'items."old"'::regclass AND attname = 'geom'
+            "#,
+        )
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+        assert_eq!(srid, 2056);
+        assert_eq!(r#type, "GeometryZ");
+
+        let bern = db
+            .read_feature("old", "bern", &Default::default())
+            .await
+            .unwrap()
+            .unwrap();
+        let geometry = serde_json::to_value(bern.geometry).unwrap();
+        let position: Vec<f64> = serde_json::from_value(geometry["coordinates"].clone()).unwrap();
+        assert!((position[0] - 7.44).abs() < 1e-6);
+        assert!((position[1] - 46.95).abs() < 1e-6);
+
+        // renamed along with the items and the metadata
+        collection.id = "new".to_string();
+        collection.title = Some("New".to_string());
+        db.rename_collection("old", &collection).await.unwrap();
+        assert!(db.read_collection("old").await.unwrap().is_none());
+        let new = db.read_collection("new").await.unwrap().unwrap();
+        assert_eq!(new.id, "new");
+        assert_eq!(new.title.as_deref(), Some("New"));
+
+        let bern = db
+            .read_feature("new", "bern", &Default::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(bern.collection.as_deref(), Some("new"));
+
+        let feature: Feature = serde_json::from_value(json!({
+            "collection": "new",
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": [8.54, 47.37] }
+        }))
+        .unwrap();
+        db.create_feature(&feature).await.unwrap();
+
+        let query = features::Query {
+            datetime: Some("2022-01-01T00:00:00Z".parse().unwrap()),
+            ..Default::default()
+        };
+        let fc = db.list_items("new", &query).await.unwrap();
+        assert_eq!(fc.features.len(), 1);
+
+        collection.id = "other".to_string();
+        assert!(db.rename_collection("missing", &collection).await.is_err());
+
+        // neither renamed nor updated if the update fails
+        collection.storage_crs = Some(Crs::from_epsg(99999));
+        assert!(db.rename_collection("new", &collection).await.is_err());
+        assert!(db.read_collection("other").await.unwrap().is_none());
+        assert_eq!(
+            db.read_collection("new")
+                .await
+                .unwrap()
+                .unwrap()
+                .storage_crs,
+            Some(Crs::from_epsg(2056))
+        );
+    }
+
+    #[sqlx::test]
+    async fn temporal_columns(pool: sqlx::PgPool) -> () {
+        let db = Db::with_pool(pool);
+
+        let temporal = |name: &str| {
+            Some(TemporalProperties {
+                instant: Some(TemporalProperty::Column(name.to_string())),
+                start: None,
+                end: None,
+            })
+        };
+
+        // names of the columns of the driver are reserved
+        let collection = Collection {
+            id: "reserved".to_string(),
+            temporal_properties: temporal("geom"),
+            ..Default::default()
+        };
+        let error = db.create_collection(&collection).await.unwrap_err();
+        assert!(error.is::<InvalidCollection>());
+
+        // missing collections are not updated
+        let collection = Collection {
+            id: "missing".to_string(),
+            temporal_properties: temporal("observed"),
+            ..Default::default()
+        };
+        db.update_collection(&collection).await.unwrap();
+        assert!(db.read_collection("missing").await.unwrap().is_none());
+
+        // indexes of long names do not collide
+        let id = "c".repeat(63);
+        let mut collection = Collection {
+            id: id.to_owned(),
+            temporal_properties: Some(TemporalProperties {
+                instant: None,
+                start: Some(TemporalProperty::Property("observed_start".to_string())),
+                end: Some(TemporalProperty::Property("observed_end".to_string())),
+            }),
+            ..Default::default()
+        };
+        db.create_collection(&collection).await.unwrap();
+
+        let indexes = |table: String| {
+            let db = db.to_owned();
+            async move {
+                let count: i64 = sqlx::query_scalar(
+                    "SELECT count(*) FROM pg_indexes WHERE schemaname = 'items' AND tablename = $1 AND indexname LIKE 'temporal%'",
+                )
+                .bind(table)
+                .fetch_one(&db.pool)
+                .await
+                .unwrap();
+                count
+            }
+        };
+        assert_eq!(indexes(id.to_owned()).await, 2);
+
+        // and follow a renamed collection
+        collection.id = "renamed".to_string();
+        db.rename_collection(&id, &collection).await.unwrap();
+        assert_eq!(indexes("renamed".to_string()).await, 2);
+    }
+
+    #[sqlx::test]
+    async fn cache_invalidation(pool: sqlx::PgPool) -> () {
+        // two instances sharing the database
+        let a = Db::with_pool(pool.clone());
+        let b = Db::with_pool(pool);
+        a.cache_collections().await.unwrap();
+        b.cache_collections().await.unwrap();
+
+        let mut collection = Collection {
+            id: "cached".to_string(),
+            title: Some("first".to_string()),
+            ..Default::default()
+        };
+        a.create_collection(&collection).await.unwrap();
+
+        let title = |db: &Db| {
+            let db = db.to_owned();
+            async move {
+                let collection = db.read_collection("cached").await.unwrap();
+                collection.and_then(|c| c.title)
+            }
+        };
+
+        // eventually consistent across instances
+        let eventually = |db: &Db, expected: Option<&'static str>| {
+            let db = db.to_owned();
+            async move {
+                for _ in 0..50 {
+                    if title(&db).await.as_deref() == expected {
+                        return;
+                    }
+                    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                }
+                panic!("collection not invalidated");
+            }
+        };
+
+        assert_eq!(title(&b).await.as_deref(), Some("first"));
+
+        collection.title = Some("second".to_string());
+        a.update_collection(&collection).await.unwrap();
+        assert_eq!(title(&a).await.as_deref(), Some("second"));
+        eventually(&b, Some("second")).await;
+
+        // changed outside of the driver
+        sqlx::query(
+            "UPDATE meta.collections SET collection = collection || '{\"title\": \"third\"}'",
+        )
+        .execute(&a.pool)
+        .await
+        .unwrap();
+        eventually(&a, Some("third")).await;
+        eventually(&b, Some("third")).await;
+
+        a.delete_collection("cached").await.unwrap();
+        assert!(a.read_collection("cached").await.unwrap().is_none());
+        eventually(&b, None).await;
+    }
+}
@@ -0,0 +1,762 @@
+#[cfg(feature = "postgres")]
+mod postgres {
+    use std::collections::HashMap;
+
+    use ogcapi_drivers::{
+        postgres::Db, CollectionTransactions, FeatureTransactions, InvalidFilter, InvalidToken,
+        StacSeach, UnknownCrs,
+    };
+    use ogcapi_types::{
+        common::{Bbox, Collection, Crs, Cursor, TemporalProperties, TemporalProperty},
+        features::{BulkMode, Direction, Feature, FeatureCollection, FilterLang, Query, SortBy},
+        stac::SearchParams,
+    };
+    use serde_json::{json, Value};
+
+    /// Database with the collection and its features
+    async fn setup(pool: sqlx::PgPool, collection: Collection, features: &[Value]) -> Db {
+        let db = Db::with_pool(pool);
+        db.create_collection(&collection).await.unwrap();
+
+        let features: Vec<Feature> = features
+            .iter()
+            .map(|feature| serde_json::from_value(feature.to_owned()).unwrap())
+            .collect();
+        if !features.is_empty() {
+            db.create_features(&collection.id, &features, BulkMode::Insert)
+                .await
+                .unwrap();
+        }
+
+        db
+    }
+
+    /// Feature located in Bern
+    fn point(id: &str, properties: Value) -> Value {
+        json!({
+            "id": id,
+            "type": "Feature",
+            "properties": properties,
+            "geometry": { "type": "Point", "coordinates": [7.44, 46.95] }
+        })
+    }
+
+    /// Sorted ids of the features
+    fn ids(fc: FeatureCollection) -> Vec<String> {
+        let mut ids: Vec<String> = fc.features.into_iter().filter_map(|f| f.id).collect();
+        ids.sort();
+        ids
+    }
+
+    #[sqlx::test]
+    async fn bbox_across_antimeridian(pool: sqlx::PgPool) -> () {
+        let collection = Collection {
+            id: "pacific".to_string(),
+            crs: vec![Crs::default(), Crs::from_epsg(4326)],
+            ..Default::default()
+        };
+        let features = [("fiji", 178.4), ("samoa", -171.8), ("bern", 7.4)].map(|(id, lon)| {
+            json!({
+                "id": id,
+                "type": "Feature",
+                "properties": {},
+                "geometry": { "type": "Point", "coordinates": [lon, -15.0] }
+            })
+        });
+        let db = setup(pool, collection, &features).await;
+
+        // longitude/latitude
+        let query = Query {
+            bbox: Some(Bbox::from([170.0, -20.0, -170.0, -10.0])),
+            ..Default::default()
+        };
+        let fc = db.list_items("pacific", &query).await.unwrap();
+        assert_eq!(ids(fc), ["fiji", "samoa"]);
+
+        // latitude/longitude
+        let query = Query {
+            bbox: Some(Bbox::from([-20.0, 170.0, -10.0, -170.0])),
+            bbox_crs: Crs::from_epsg(4326),
+            ..Default::default()
+        };
+        let fc = db.list_items("pacific", &query).await.unwrap();
+        assert_eq!(fc.features.len(), 2);
+
+        // 2D features match the horizontal extent of a 3D bbox
+        let query = Query {
+            bbox: Some(Bbox::from([0.0, -20.0, 100.0, 10.0, -10.0, 200.0])),
+            ..Default::default()
+        };
+        let fc = db.list_items("pacific", &query).await.unwrap();
+        assert_eq!(fc.features[0].id.as_deref(), Some("bern"));
+    }
+
+    #[sqlx::test]
+    async fn simplified_geometry(pool: sqlx::PgPool) -> () {
+        let collection = Collection {
+            id: "outlines".to_string(),
+            crs: vec![Crs::default()],
+            ..Default::default()
+        };
+        // nearly straight line with many vertices
+        let coordinates: Vec<[f64; 2]> = (0..=100)
+            .map(|i| [i as f64 / 10.0, 0.000001 * (i % 2) as f64])
+            .collect();
+        let feature = json!({
+            "id": "coast",
+            "type": "Feature",
+            "properties": {},
+            "geometry": { "type": "LineString", "coordinates": coordinates }
+        });
+        let db = setup(pool, collection, &[feature]).await;
+
+        let coordinates = |feature: &Feature| {
+            serde_json::to_value(feature.geometry.as_ref().unwrap()).unwrap()["coordinates"].take()
+        };
+        let vertices = |feature: &Feature| coordinates(feature).as_array().unwrap().len();
+
+        let fc = db.list_items("outlines", &Query::default()).await.unwrap();
+        assert_eq!(vertices(&fc.features[0]), 101);
+
+        let query = Query {
+            max_allowable_offset: Some(0.001),
+            ..Default::default()
+        };
+        let fc = db.list_items("outlines", &query).await.unwrap();
+        assert_eq!(vertices(&fc.features[0]), 2);
+
+        let query = Query {
+            zoom_level: Some(4),
+            coordinate_precision: Some(1),
+            ..Default::default()
+        };
+        let feature = db
+            .read_feature("outlines", "coast", &query)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(vertices(&feature), 2);
+        assert_eq!(coordinates(&feature), json!([[0.0, 0.0], [10.0, 0.0]]));
+    }
+
+    /// Features with and without STAC temporal properties
+    fn events() -> Vec<Value> {
+        [
+            ("instant", json!({ "datetime": "2022-06-01T00:00:00Z" })),
+            (
+                "ongoing",
+                json!({ "start_datetime": "2021-01-01T00:00:00Z" }),
+            ),
+            ("past", json!({ "end_datetime": "2020-01-01T00:00:00Z" })),
+            ("future", json!({ "datetime": "2030-01-01T00:00:00Z" })),
+            ("timeless", json!({})),
+            // neither are timestamps with an offset
+            ("unknown", json!({ "datetime": "soon" })),
+            ("local", json!({ "datetime": "2022-06-01" })),
+        ]
+        .into_iter()
+        .map(|(id, properties)| point(id, properties))
+        .collect()
+    }
+
+    #[sqlx::test]
+    async fn stac_temporal_properties(pool: sqlx::PgPool) -> () {
+        let collection = Collection {
+            id: "events".to_string(),
+            ..Default::default()
+        };
+        let db = setup(pool, collection, &events()).await;
+
+        let query = Query {
+            datetime: Some("2022-01-01T00:00:00Z/..".parse().unwrap()),
+            ..Default::default()
+        };
+        let fc = db.list_items("events", &query).await.unwrap();
+        assert_eq!(ids(fc), ["future", "instant", "ongoing"]);
+
+        let query = Query {
+            datetime: Some("../2020-06-01T00:00:00Z".parse().unwrap()),
+            ..Default::default()
+        };
+        let fc = db.list_items("events", &query).await.unwrap();
+        assert_eq!(ids(fc), ["past"]);
+    }
+
+    #[sqlx::test]
+    async fn temporal_column(pool: sqlx::PgPool) -> () {
+        // instant in a dedicated column only
+        let collection = Collection {
+            id: "instants".to_string(),
+            temporal_properties: Some(TemporalProperties {
+                instant: Some(TemporalProperty::Column("datetime".to_string())),
+                start: None,
+                end: None,
+            }),
+            ..Default::default()
+        };
+        let db = setup(pool, collection, &events()).await;
+
+        let query = Query {
+            datetime: Some("2022-06-01T00:00:00Z".parse().unwrap()),
+            ..Default::default()
+        };
+        let fc = db.list_items("instants", &query).await.unwrap();
+        assert_eq!(ids(fc), ["instant"]);
+
+        let query = Query {
+            datetime: Some("2021-01-01T00:00:00Z/..".parse().unwrap()),
+            ..Default::default()
+        };
+        let fc = db.list_items("instants", &query).await.unwrap();
+        assert_eq!(ids(fc), ["future", "instant"]);
+    }
+
+    #[sqlx::test]
+    async fn property_filters(pool: sqlx::PgPool) -> () {
+        let collection = Collection {
+            id: "cities".to_string(),
+            ..Default::default()
+        };
+        let features = [
+            point(
+                "bern",
+                json!({ "name": "Bern", "population": 3, "capital": true, "founded": "1191-01-01T00:00:00Z" }),
+            ),
+            point(
+                "zug",
+                json!({ "name": "Zug", "population": 4.5, "capital": false, "founded": "2000-06-01T12:00:00+02:00" }),
+            ),
+            point("nowhere", json!({ "name": "Nowhere" })),
+        ];
+        let db = setup(pool, collection, &features).await;
+
+        let filter = |key: &str, value: &str| {
+            let query = Query {
+                additional_parameters: HashMap::from([(key.to_string(), value.to_string())]),
+                ..Default::default()
+            };
+            let db = &db;
+            async move { anyhow::Ok(ids(db.list_items("cities", &query).await?)) }
+        };
+
+        // typed comparison, features without the property do not match
+        assert_eq!(filter("population", "3.0").await.unwrap(), ["bern"]);
+        assert_eq!(
+            filter("population", "3,4.5").await.unwrap(),
+            ["bern", "zug"]
+        );
+        assert_eq!(filter("capital", "false").await.unwrap(), ["zug"]);
+        assert_eq!(
+            filter("founded", "2000-06-01T10:00:00Z").await.unwrap(),
+            ["zug"]
+        );
+        assert_eq!(filter("name", "Bern,Zug").await.unwrap(), ["bern", "zug"]);
+        assert!(filter("name", "bern").await.unwrap().is_empty());
+
+        // values must conform to the type
+        assert!(filter("population", "many").await.is_err());
+    }
+
+    #[sqlx::test]
+    async fn queryables(pool: sqlx::PgPool) -> () {
+        let mut collection = Collection {
+            id: "inferred".to_string(),
+            ..Default::default()
+        };
+        collection.additional_properties.insert(
+            "queryables".to_string(),
+            json!({
+                "name": { "title": "Name" },
+                "population": { "type": "number" },
+                "canton": { "type": "string", "enum": ["BE", "ZG"] }
+            }),
+        );
+        let features = [
+            point(
+                "bern",
+                json!({ "name": "Bern", "population": 3, "area": 51.6, "capital": true, "founded": "1191-01-01T00:00:00Z", "code": "BE" }),
+            ),
+            point(
+                "zug",
+                json!({ "name": "Zug", "population": 4, "area": 21, "capital": false, "founded": null, "code": 1 }),
+            ),
+        ];
+        let db = setup(pool, collection, &features).await;
+
+        let queryables = db.queryables("inferred").await.unwrap().unwrap();
+        let schema = |key: &str| serde_json::to_value(&queryables.properties[key]).unwrap();
+
+        // inferred from the values, nulls are ignored
+        assert_eq!(schema("area"), json!({ "type": "number" }));
+        assert_eq!(schema("capital"), json!({ "type": "boolean" }));
+        assert_eq!(
+            schema("founded"),
+            json!({ "type": "string", "format": "date-time" })
+        );
+        assert_eq!(schema("code"), json!({}));
+        assert!(queryables.properties.contains_key("id"));
+        assert!(queryables.properties.contains_key("geometry"));
+        assert!(!queryables.contains("nmae"));
+
+        // refined by the collection metadata
+        assert_eq!(schema("name"), json!({ "title": "Name", "type": "string" }));
+        assert_eq!(schema("population"), json!({ "type": "number" }));
+        assert_eq!(
+            schema("canton"),
+            json!({ "type": "string", "enum": ["BE", "ZG"] })
+        );
+
+        assert!(db.queryables("missing").await.unwrap().is_none());
+    }
+
+    #[sqlx::test]
+    async fn property_selection(pool: sqlx::PgPool) -> () {
+        let collection = Collection {
+            id: "selection".to_string(),
+            ..Default::default()
+        };
+        let feature = point("bern", json!({ "name": "Bern", "population": 3 }));
+        let db = setup(pool, collection, &[feature]).await;
+
+        let query = Query {
+            properties: Some(vec!["name".to_string(), "missing".to_string()]),
+            skip_geometry: Some(true),
+            ..Default::default()
+        };
+        let expected = json!({ "name": "Bern" });
+
+        let fc = db.list_items("selection", &query).await.unwrap();
+        let properties = fc.features[0].properties.to_owned().unwrap();
+        assert_eq!(serde_json::to_value(properties).unwrap(), expected);
+
+        // single features alike
+        let feature = db
+            .read_feature("selection", "bern", &query)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(feature.properties.unwrap()).unwrap(),
+            expected
+        );
+        assert!(feature.geometry.is_none());
+
+        let query = Query {
+            properties: Some(Vec::new()),
+            ..Default::default()
+        };
+        let feature = db
+            .read_feature("selection", "bern", &query)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(feature.properties.unwrap_or_default().is_empty());
+        assert!(feature.geometry.is_some());
+    }
+
+    #[sqlx::test]
+    async fn null_geometry(pool: sqlx::PgPool) -> () {
+        let collection = Collection {
+            id: "nulls".to_string(),
+            ..Default::default()
+        };
+        let feature = point("bern", json!({ "name": "Bern" }));
+        let db = setup(pool, collection, &[feature.to_owned()]).await;
+
+        let mut feature: Feature = serde_json::from_value(feature).unwrap();
+        feature.collection = Some("nulls".to_string());
+
+        // kept for patches
+        feature.geometry = None;
+        db.update_feature(&feature, None, true).await.unwrap();
+        let stored = db
+            .read_feature("nulls", "bern", &Query::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stored.geometry.is_some());
+
+        // removed on replacement
+        db.update_feature(&feature, None, false).await.unwrap();
+        let stored = db
+            .read_feature("nulls", "bern", &Query::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stored.geometry.is_none());
+    }
+
+    #[sqlx::test]
+    async fn bulk_upload(pool: sqlx::PgPool) -> () {
+        let collection = Collection {
+            id: "bulk".to_string(),
+            ..Default::default()
+        };
+        let db = setup(pool, collection, &[]).await;
+
+        let features: Vec<Feature> = [Some("z"), Some("a"), None, Some("m")]
+            .into_iter()
+            .enumerate()
+            .map(|(i, id)| {
+                let mut feature = point("", json!({ "i": i }));
+                feature["id"] = json!(id);
+                if i == 1 {
+                    feature["geometry"] = Value::Null;
+                }
+                serde_json::from_value(feature).unwrap()
+            })
+            .collect();
+
+        // ids are reported in the order of the upload
+        let report = db
+            .create_features("bulk", &features, BulkMode::Insert)
+            .await
+            .unwrap();
+        assert!(report.is_ok());
+        assert_eq!(report.ids.len(), 4);
+        assert_eq!(report.ids[0], "z");
+        assert_eq!(report.ids[1], "a");
+        assert_eq!(report.ids[3], "m");
+
+        let generated = db
+            .read_feature("bulk", &report.ids[2], &Query::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(generated.properties.unwrap()["i"], 2);
+
+        // null geometries are accepted
+        let stored = db
+            .read_feature("bulk", "a", &Query::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stored.geometry.is_none());
+
+        let report = db
+            .create_features("bulk", &features[..2], BulkMode::Upsert)
+            .await
+            .unwrap();
+        assert_eq!(report.ids, ["z", "a"]);
+    }
+
+    #[sqlx::test]
+    async fn invalid_filters(pool: sqlx::PgPool) -> () {
+        let collection = Collection {
+            id: "invalid".to_string(),
+            ..Default::default()
+        };
+        let feature = point(
+            "bern",
+            json!({ "name": "Bern", "founded": "1191-01-01T00:00:00Z" }),
+        );
+        let db = setup(pool, collection, &[feature]).await;
+
+        let list = |filter: &str, filter_lang: FilterLang| {
+            let query = Query {
+                filter: Some(filter.to_string()),
+                filter_lang: Some(filter_lang),
+                ..Default::default()
+            };
+            let db = &db;
+            async move { db.list_items("invalid", &query).await }
+        };
+
+        // parse, but with arguments of the wrong type
+        for filter in [
+            "S_INTERSECTS(name, 'x')",
+            "T_AFTER(founded, 5)",
+            "T_AFTER(founded, '2022-13-45')",
+        ] {
+            let error = list(filter, FilterLang::CqlText).await.unwrap_err();
+            assert!(error.is::<InvalidFilter>(), "{filter}: {error}");
+        }
+
+        let filter = r#"{"op": "t_after", "args": [{"property": "founded"}, "not a time"]}"#;
+        let error = list(filter, FilterLang::CqlJson).await.unwrap_err();
+        assert!(error.is::<InvalidFilter>());
+
+        // geometries the database would fail on
+        let error = list(
+            "S_INTERSECTS(geometry, LINESTRING(7 46))",
+            FilterLang::CqlText,
+        )
+        .await
+        .unwrap_err();
+        assert!(error.is::<InvalidFilter>());
+
+        // properties without a time match nothing
+        let fc = list(
+            "T_AFTER(name, TIMESTAMP('2020-01-01T00:00:00Z'))",
+            FilterLang::CqlText,
+        )
+        .await
+        .unwrap();
+        assert!(fc.features.is_empty());
+
+        // timestamps and dates as strings
+        for filter in [
+            "T_AFTER(founded, '1000-01-01')",
+            "T_AFTER(founded, '1000-01-01T00:00:00Z')",
+        ] {
+            let fc = list(filter, FilterLang::CqlText).await.unwrap();
+            assert_eq!(fc.features.len(), 1);
+        }
+    }
+
+    #[sqlx::test]
+    async fn pagination_tokens(pool: sqlx::PgPool) -> () {
+        let collection = Collection {
+            id: "pages".to_string(),
+            ..Default::default()
+        };
+        let features = ["a", "b", "c"].map(|id| point(id, json!({})));
+        let db = setup(pool, collection, &features).await;
+
+        // the offset does not apply after the token
+        let query = Query {
+            sortby: Some(vec!["id".parse().unwrap()]),
+            token: Some(Cursor(vec![json!("a"), json!("a")])),
+            offset: Some(10),
+            ..Default::default()
+        };
+        let fc = db.list_items("pages", &query).await.unwrap();
+        let ids: Vec<String> = fc.features.into_iter().filter_map(|f| f.id).collect();
+        assert_eq!(ids, ["b", "c"]);
+
+        // a number for the text of the id, or too few values
+        for token in [vec![json!(1), json!("a")], vec![json!("a")]] {
+            let query = Query {
+                sortby: Some(vec!["id".parse().unwrap()]),
+                token: Some(Cursor(token)),
+                ..Default::default()
+            };
+            let error = db.list_items("pages", &query).await.unwrap_err();
+            assert!(error.is::<InvalidToken>());
+        }
+    }
+
+    /// Square of the given size with a name and possibly a population
+    fn square(id: &str, size: f64, properties: Value) -> Value {
+        json!({
+            "id": id,
+            "type": "Feature",
+            "properties": properties,
+            "geometry": {
+                "type": "Polygon",
+                "coordinates": [[[7.0, 46.0], [7.0 + size, 46.0], [7.0 + size, 46.0 + size], [7.0, 46.0 + size], [7.0, 46.0]]]
+            }
+        })
+    }
+
+    #[sqlx::test]
+    async fn sorting(pool: sqlx::PgPool) -> () {
+        let collection = Collection {
+            id: "sorted".to_string(),
+            ..Default::default()
+        };
+        let features = [
+            square(
+                "small",
+                0.1,
+                json!({ "name": "Bern", "population": 3, "datetime": "2022-01-01T00:00:00Z" }),
+            ),
+            square(
+                "large",
+                1.0,
+                json!({ "name": "Aarau", "population": 10, "datetime": "2021-01-01T00:00:00Z" }),
+            ),
+            square(
+                "medium",
+                0.5,
+                json!({ "name": "Chur", "datetime": "2023-01-01T00:00:00Z" }),
+            ),
+        ];
+        let db = setup(pool, collection, &features).await;
+
+        let sorted = |sortby: &str, token: Option<Cursor>| {
+            let query = Query {
+                sortby: Some(sortby.split(',').map(|s| s.parse().unwrap()).collect()),
+                token,
+                ..Default::default()
+            };
+            let db = &db;
+            async move {
+                let fc = db.list_items("sorted", &query).await.unwrap();
+                fc.features
+                    .into_iter()
+                    .filter_map(|f| f.id)
+                    .collect::<Vec<String>>()
+            }
+        };
+
+        assert_eq!(sorted("+name", None).await, ["large", "small", "medium"]);
+        assert_eq!(sorted("-name", None).await, ["medium", "small", "large"]);
+        assert_eq!(sorted("id", None).await, ["large", "medium", "small"]);
+        assert_eq!(
+            sorted("-geometry.area", None).await,
+            ["large", "medium", "small"]
+        );
+
+        // missing properties sort like `null`, i.e. first
+        assert_eq!(
+            sorted("population", None).await,
+            ["medium", "small", "large"]
+        );
+        assert_eq!(
+            sorted("-population", None).await,
+            ["large", "small", "medium"]
+        );
+
+        // pages continue after the sort keys of the last feature
+        let token = Cursor(vec![json!(10), json!("large")]);
+        assert_eq!(
+            sorted("-population", Some(token)).await,
+            ["small", "medium"]
+        );
+        let token = Cursor(vec![json!(null), json!("medium")]);
+        assert_eq!(sorted("population", Some(token)).await, ["small", "large"]);
+
+        // STAC item search alike, with the collection as tiebreaker
+        let search = |sortby: Vec<SortBy>, token: Option<Cursor>| {
+            let query = SearchParams {
+                collections: Some(vec!["sorted".to_string()]),
+                sortby: Some(sortby),
+                token,
+                ..Default::default()
+            };
+            let db = &db;
+            async move {
+                let fc = db.search(&query).await.unwrap();
+                fc.features
+                    .into_iter()
+                    .filter_map(|f| f.id)
+                    .collect::<Vec<String>>()
+            }
+        };
+
+        let latest = vec![SortBy::new("datetime", Direction::Desc)];
+        assert_eq!(
+            search(latest.to_owned(), None).await,
+            ["medium", "small", "large"]
+        );
+        let token = Cursor(vec![
+            json!("2023-01-01T00:00:00Z"),
+            json!("sorted"),
+            json!("medium"),
+        ]);
+        assert_eq!(search(latest, Some(token)).await, ["small", "large"]);
+    }
+
+    #[sqlx::test]
+    async fn crs_lookup(pool: sqlx::PgPool) -> () {
+        // storage crs of other authorities
+        let collection = Collection {
+            id: "robinson".to_string(),
+            crs: vec![Crs::default()],
+            storage_crs: Some(
+                "http://www.opengis.net/def/crs/ESRI/0/54030"
+                    .parse()
+                    .unwrap(),
+            ),
+            ..Default::default()
+        };
+        let db = setup(pool, collection, &[point("bern", json!({}))]).await;
+
+        let unknown = |crs: &str| UnknownCrs(crs.parse().unwrap());
+
+        let query = Query {
+            bbox: Some(Bbox::from([7.0, 46.0, 8.0, 47.0])),
+            ..Default::default()
+        };
+        let fc = db.list_items("robinson", &query).await.unwrap();
+        assert_eq!(fc.features.len(), 1);
+
+        // unknown crs are errors of their own
+        for crs in [
+            "http://www.opengis.net/def/crs/OGC/1.3/CRS27",
+            "http://www.opengis.net/def/crs/EPSG/0/999999",
+            "http://www.opengis.net/def/crs/IAU/2015/0",
+            "http://www.opengis.net/def/crs/MyOrg/1/abc",
+        ] {
+            let query = Query {
+                crs: crs.parse().unwrap(),
+                ..Default::default()
+            };
+            let error = db.list_items("robinson", &query).await.unwrap_err();
+            assert_eq!(error.downcast_ref::<UnknownCrs>(), Some(&unknown(crs)));
+
+            let query = Query {
+                bbox: Some(Bbox::from([7.0, 46.0, 8.0, 47.0])),
+                bbox_crs: crs.parse().unwrap(),
+                ..Default::default()
+            };
+            let error = db.list_items("robinson", &query).await.unwrap_err();
+            assert_eq!(error.downcast_ref::<UnknownCrs>(), Some(&unknown(crs)));
+
+            let collection = Collection {
+                id: "unknown".to_string(),
+                storage_crs: Some(crs.parse().unwrap()),
+                ..Default::default()
+            };
+            let error = db.create_collection(&collection).await.unwrap_err();
+            assert_eq!(error.downcast_ref::<UnknownCrs>(), Some(&unknown(crs)));
+        }
+    }
+
+    #[sqlx::test]
+    async fn axis_order(pool: sqlx::PgPool) -> () {
+        let collection = Collection {
+            id: "axes".to_string(),
+            crs: vec![Crs::default(), Crs::from_epsg(4326), Crs::from_epsg(2056)],
+            ..Default::default()
+        };
+        let db = setup(pool, collection, &[point("bern", json!({}))]).await;
+
+        let coordinates = |crs: Crs| {
+            let db = db.to_owned();
+            async move {
+                let query = Query {
+                    crs,
+                    ..Default::default()
+                };
+                let feature = db.read_feature("axes", "bern", &query).await.unwrap();
+                let geometry = serde_json::to_value(feature.unwrap().geometry).unwrap();
+                serde_json::from_value::<Vec<f64>>(geometry["coordinates"].clone()).unwrap()
+            }
+        };
+
+        // longitude/latitude
+        assert_eq!(coordinates(Crs::default()).await, [7.44, 46.95]);
+        // latitude/longitude
+        assert_eq!(coordinates(Crs::from_epsg(4326)).await, [46.95, 7.44]);
+        // easting/northing
+        let position = coordinates(Crs::from_epsg(2056)).await;
+        assert!(position[0] > 2_000_000.0 && position[1] > 1_000_000.0);
+
+        // filter geometries in the axis order of the filter crs
+        let filter = |filter_crs: Crs| Query {
+            filter: Some("S_INTERSECTS(geometry, BBOX(46, 7, 47, 8))".to_string()),
+            filter_crs: Some(filter_crs),
+            ..Default::default()
+        };
+        let fc = db
+            .list_items("axes", &filter(Crs::from_epsg(4326)))
+            .await
+            .unwrap();
+        assert_eq!(fc.features.len(), 1);
+        let fc = db
+            .list_items("axes", &filter(Crs::default()))
+            .await
+            .unwrap();
+        assert!(fc.features.is_empty());
+
+        let query = Query {
+            filter: Some("S_INTERSECTS(geometry, POINT(46.95 7.44))".to_string()),
+            filter_crs: Some(Crs::from_epsg(4326)),
+            ..Default::default()
+        };
+        let fc = db.list_items("axes", &query).await.unwrap();
+        assert_eq!(fc.features.len(), 1);
+    }
+}
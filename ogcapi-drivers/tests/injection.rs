@@ -0,0 +1,225 @@
+#[cfg(feature = "postgres")]
+mod postgres {
+    use std::collections::HashMap;
+
+    use ogcapi_drivers::{
+        postgres::Db, CollectionTransactions, EdrQuerier, FeatureTransactions, Outcome,
+    };
+    use ogcapi_types::{
+        common::{Collection, Crs, TemporalProperties, TemporalProperty},
+        edr::{self, QueryType},
+        features::{BulkMode, Direction, Feature, Query, SortBy},
+    };
+    use serde_json::json;
+
+    const HOSTILE: [&str; 5] = [
+        "'",
+        "' OR '1'='1",
+        "x'); DROP TABLE meta.collections; --",
+        r#"x"; DROP TABLE meta.collections; --"#,
+        "$1",
+    ];
+
+    async fn setup(db: &Db, id: &str) -> Collection {
+        let collection = Collection {
+            id: id.to_string(),
+            crs: vec![Crs::default()],
+            ..Default::default()
+        };
+        db.create_collection(&collection).await.unwrap();
+
+        let features: Vec<Feature> = HOSTILE
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                serde_json::from_value(json!({
+                    "id": value,
+                    "type": "Feature",
+                    "properties": { "name": format!("feature {i}"), *value: i },
+                    "geometry": { "type": "Point", "coordinates": [7.0 + i as f64, 46.0] }
+                }))
+                .unwrap()
+            })
+            .collect();
+        db.create_features(&collection.id, &features, BulkMode::Insert)
+            .await
+            .unwrap();
+
+        collection
+    }
+
+    #[sqlx::test]
+    async fn hostile_collection_ids(pool: sqlx::PgPool) -> () {
+        let db = Db::with_pool(pool);
+
+        for id in HOSTILE {
+            let collection = setup(&db, id).await;
+
+            let fc = db.list_items(id, &Query::default()).await.unwrap();
+            assert_eq!(fc.features.len(), HOSTILE.len());
+            assert!(fc
+                .features
+                .iter()
+                .all(|f| f.collection.as_deref() == Some(id)));
+
+            let feature = db.read_feature(id, "'", &Query::default()).await.unwrap();
+            assert_eq!(feature.unwrap().id.as_deref(), Some("'"));
+
+            let queryables = db.queryables(id).await.unwrap();
+            assert!(queryables.unwrap().properties.contains_key(id));
+
+            let outcome = db.delete_feature(id, "$1", None).await.unwrap();
+            assert_eq!(outcome, Outcome::Applied);
+            db.delete_collection(&collection.id).await.unwrap();
+        }
+
+        // too long for a table name
+        let collection = Collection {
+            id: "x".repeat(64),
+            ..Default::default()
+        };
+        assert!(db.create_collection(&collection).await.is_err());
+    }
+
+    #[sqlx::test]
+    async fn hostile_query_parameters(pool: sqlx::PgPool) -> () {
+        let db = Db::with_pool(pool);
+        let collection = setup(&db, "hostile").await;
+
+        for (i, value) in HOSTILE.into_iter().enumerate() {
+            // property filters
+            let query = Query {
+                additional_parameters: HashMap::from([("name".to_string(), value.to_string())]),
+                ..Default::default()
+            };
+            let fc = db.list_items(&collection.id, &query).await.unwrap();
+            assert!(fc.features.is_empty());
+
+            let query = Query {
+                additional_parameters: HashMap::from([(value.to_string(), i.to_string())]),
+                ..Default::default()
+            };
+            let fc = db.list_items(&collection.id, &query).await.unwrap();
+            assert!(fc.features.iter().any(|f| f.id.as_deref() == Some(value)));
+
+            // property selection and sorting
+            let query = Query {
+                properties: Some(vec![value.to_string()]),
+                sortby: Some(vec![SortBy::new(value, Direction::Desc)]),
+                ..Default::default()
+            };
+            let fc = db.list_items(&collection.id, &query).await.unwrap();
+            assert_eq!(fc.features.len(), HOSTILE.len());
+
+            // unknown collection
+            assert!(db.list_items(value, &Query::default()).await.is_err());
+        }
+
+        let fc = db
+            .list_items(&collection.id, &Query::default())
+            .await
+            .unwrap();
+        assert_eq!(fc.features.len(), HOSTILE.len());
+    }
+
+    #[sqlx::test]
+    async fn hostile_temporal_properties(pool: sqlx::PgPool) -> () {
+        let db = Db::with_pool(pool);
+
+        for (i, name) in HOSTILE.iter().enumerate() {
+            let collection = Collection {
+                id: format!("temporal{i}"),
+                crs: vec![Crs::default()],
+                temporal_properties: Some(TemporalProperties {
+                    instant: Some(TemporalProperty::Property(name.to_string())),
+                    start: Some(TemporalProperty::Column(name.to_string())),
+                    end: None,
+                }),
+                ..Default::default()
+            };
+            db.create_collection(&collection).await.unwrap();
+
+            let feature: Feature = serde_json::from_value(json!({
+                "id": "a",
+                "collection": collection.id,
+                "type": "Feature",
+                "properties": { *name: "2022-10-17T12:00:00Z" },
+                "geometry": { "type": "Point", "coordinates": [7.0, 46.0] }
+            }))
+            .unwrap();
+            db.create_feature(&feature).await.unwrap();
+
+            let query = Query {
+                datetime: Some("2022-10-17T12:00:00Z".parse().unwrap()),
+                ..Default::default()
+            };
+            let fc = db.list_items(&collection.id, &query).await.unwrap();
+            assert_eq!(fc.features.len(), 1);
+        }
+    }
+
+    #[sqlx::test]
+    async fn hostile_edr_queries(pool: sqlx::PgPool) -> () {
+        let db = Db::with_pool(pool);
+        let collection = setup(&db, "edr").await;
+
+        for value in HOSTILE {
+            let query = edr::Query {
+                coords: format!("POINT(7 46)'{value}"),
+                ..Default::default()
+            };
+            assert!(db
+                .query(&collection.id, &QueryType::Position, &query)
+                .await
+                .is_err());
+
+            let query = edr::Query {
+                coords: format!("6,45,9{value},47"),
+                ..Default::default()
+            };
+            assert!(db
+                .query(&collection.id, &QueryType::Cube, &query)
+                .await
+                .is_err());
+
+            let query = edr::Query {
+                coords: "6,45,9,47".to_string(),
+                parameter_name: Some(value.to_string()),
+                ..Default::default()
+            };
+            let fc = db
+                .query(&collection.id, &QueryType::Cube, &query)
+                .await
+                .unwrap();
+            assert_eq!(fc.features.len(), 3);
+        }
+    }
+
+    #[cfg(feature = "stac")]
+    #[sqlx::test]
+    async fn hostile_search(pool: sqlx::PgPool) -> () {
+        use ogcapi_drivers::StacSeach;
+        use ogcapi_types::stac::SearchParams;
+
+        let db = Db::with_pool(pool);
+        setup(&db, "search").await;
+
+        for value in HOSTILE {
+            let params = SearchParams::new().with_ids([value]);
+            let fc = db.search(&params).await.unwrap();
+            assert_eq!(fc.features.len(), 1);
+            assert_eq!(fc.features[0].id.as_deref(), Some(value));
+
+            let params = SearchParams::new().with_intersects(
+                serde_json::from_value(json!({
+                    "type": "Point",
+                    "coordinates": [7.0, 46.0],
+                    value: value
+                }))
+                .unwrap(),
+            );
+            let fc = db.search(&params).await.unwrap();
+            assert_eq!(fc.features.len(), 1);
+        }
+    }
+}
@@ -5,7 +5,7 @@ mod postgres {
 
     #[sqlx::test]
     async fn job_handling(pool: sqlx::PgPool) -> () {
-        let db = Db { pool };
+        let db = Db::with_pool(pool);
 
         let job = StatusInfo {
             job_id: "test-job".to_string(),
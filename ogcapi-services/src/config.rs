@@ -1,5 +1,8 @@
 use clap::Parser;
 
+/// Default maximum size of a request body to create features, in bytes
+pub const UPLOAD_LIMIT: usize = 64 * 1024 * 1024;
+
 /// Application configuration
 #[derive(Parser, Debug)]
 pub struct Config {
@@ -15,4 +18,10 @@ pub struct Config {
     /// OpenAPI definition
     #[clap(long, env, parse(from_os_str))]
     pub openapi: Option<std::path::PathBuf>,
+    /// Recompute the extent and summaries of a collection on every write
+    #[clap(long, env)]
+    pub update_extent: bool,
+    /// Maximum size of a request body to create features, in bytes
+    #[clap(long, env, default_value_t = UPLOAD_LIMIT)]
+    pub upload_limit: usize,
 }
@@ -0,0 +1,227 @@
+//! CSV encoding of features according to [RFC 4180](https://www.rfc-editor.org/rfc/rfc4180)
+//!
+//! Every feature is a row with its id, the geometry and the properties, where
+//! nested objects are flattened into one column per member, e.g. `address.city`.
+
+use geojson::Value as Geometry;
+use serde_json::{Map, Value};
+
+use ogcapi_types::{
+    common::Crs,
+    features::{CsvGeometry, Feature, Queryables},
+};
+
+use super::positions;
+
+/// Property columns from the queryables of a collection, optionally limited to
+/// a selection of properties
+pub(crate) fn columns(
+    queryables: &Queryables,
+    selection: Option<&[String]>,
+    features: &[Feature],
+) -> Vec<String> {
+    let keys = queryables
+        .properties
+        .iter()
+        .filter(|(key, queryable)| {
+            *key != "id"
+                && *key != "geometry"
+                && queryable.r#ref.is_none()
+                && selection.map_or(true, |selection| selection.contains(key))
+        })
+        .map(|(key, _)| key.as_str());
+
+    flattened_columns(keys, features)
+}
+
+/// Property columns from the properties of a single feature
+pub(crate) fn feature_columns(feature: &Feature) -> Vec<String> {
+    let keys = feature.properties.iter().flat_map(|p| p.keys());
+
+    flattened_columns(keys.map(String::as_str), std::slice::from_ref(feature))
+}
+
+/// Expand properties with object values to the flattened members found in the
+/// features
+fn flattened_columns<'a>(keys: impl Iterator<Item = &'a str>, features: &[Feature]) -> Vec<String> {
+    let cells: Vec<Vec<(String, String)>> = features.iter().map(cells).collect();
+
+    let mut columns = Vec::new();
+    for key in keys {
+        let prefix = format!("{key}.");
+        let mut members: Vec<&String> = Vec::new();
+        for (column, _) in cells.iter().flatten() {
+            if (column == key || column.starts_with(&prefix)) && !members.contains(&column) {
+                members.push(column);
+            }
+        }
+
+        if members.is_empty() {
+            columns.push(key.to_owned());
+        } else {
+            columns.extend(members.into_iter().cloned());
+        }
+    }
+    columns
+}
+
+/// Encode features as CSV with a header row. The geometry columns are omitted
+/// without `geometry`.
+pub(crate) fn encode(
+    features: &[Feature],
+    columns: &[String],
+    geometry: Option<CsvGeometry>,
+    crs: &Crs,
+) -> String {
+    let mut csv = String::new();
+
+    let mut header = vec!["id"];
+    match geometry {
+        Some(CsvGeometry::Wkt) => header.push("WKT"),
+        Some(CsvGeometry::Xy) if *crs == Crs::default() => header.extend(["lon", "lat"]),
+        Some(CsvGeometry::Xy) => header.extend(["x", "y"]),
+        None => {}
+    }
+    header.extend(columns.iter().map(String::as_str));
+    row(&mut csv, header.into_iter());
+
+    for feature in features {
+        let mut fields = vec![feature.id.to_owned().unwrap_or_default()];
+
+        let value = feature.geometry.as_ref().map(|g| &g.value);
+        match geometry {
+            Some(CsvGeometry::Wkt) => fields.push(value.map(wkt).unwrap_or_default()),
+            Some(CsvGeometry::Xy) => {
+                // only points have a single coordinate
+                let position = match value {
+                    Some(Geometry::Point(position)) => position.as_slice(),
+                    _ => &[],
+                };
+                for i in 0..2 {
+                    fields.push(position.get(i).map(f64::to_string).unwrap_or_default());
+                }
+            }
+            None => {}
+        }
+
+        let mut cells = cells(feature);
+        for column in columns {
+            let cell = cells
+                .iter()
+                .position(|(c, _)| c == column)
+                .map(|i| cells.swap_remove(i).1);
+            fields.push(cell.unwrap_or_default());
+        }
+
+        row(&mut csv, fields.iter().map(String::as_str));
+    }
+
+    csv
+}
+
+fn row<'a>(csv: &mut String, fields: impl Iterator<Item = &'a str>) {
+    for (i, field) in fields.enumerate() {
+        if i > 0 {
+            csv.push(',');
+        }
+        if field.contains([',', '"', '\r', '\n']) {
+            csv.push('"');
+            csv.push_str(&field.replace('"', "\"\""));
+            csv.push('"');
+        } else {
+            csv.push_str(field);
+        }
+    }
+    csv.push_str("\r\n");
+}
+
+/// Flattened properties of a feature as column and cell text
+fn cells(feature: &Feature) -> Vec<(String, String)> {
+    let mut cells = Vec::new();
+    if let Some(properties) = feature.properties.as_ref() {
+        flatten(None, properties, &mut cells);
+    }
+    cells
+}
+
+fn flatten(prefix: Option<&str>, object: &Map<String, Value>, cells: &mut Vec<(String, String)>) {
+    for (key, value) in object {
+        let column = match prefix {
+            Some(prefix) => format!("{prefix}.{key}"),
+            None => key.to_owned(),
+        };
+        let cell = match value {
+            Value::Object(object) if !object.is_empty() => {
+                flatten(Some(&column), object, cells);
+                continue;
+            }
+            Value::Null => String::new(),
+            // spreadsheets would evaluate text starting like a formula
+            Value::String(s) if s.starts_with(['=', '+', '-', '@', '\t', '\r']) => format!("'{s}"),
+            Value::String(s) => s.to_owned(),
+            value => value.to_string(),
+        };
+        cells.push((column, cell));
+    }
+}
+
+/// Well-known text representation of a geometry
+fn wkt(geometry: &Geometry) -> String {
+    let mut wkt = String::new();
+    let z = positions(geometry).all(|position| position.len() > 2);
+    write_wkt(&mut wkt, geometry, z);
+    wkt
+}
+
+fn write_wkt(wkt: &mut String, geometry: &Geometry, z: bool) {
+    wkt.push_str(match geometry {
+        Geometry::Point(_) => "POINT",
+        Geometry::MultiPoint(_) => "MULTIPOINT",
+        Geometry::LineString(_) => "LINESTRING",
+        Geometry::MultiLineString(_) => "MULTILINESTRING",
+        Geometry::Polygon(_) => "POLYGON",
+        Geometry::MultiPolygon(_) => "MULTIPOLYGON",
+        Geometry::GeometryCollection(_) => "GEOMETRYCOLLECTION",
+    });
+    wkt.push_str(if z { " Z " } else { " " });
+
+    let coordinates = |wkt: &mut String, position: &Vec<f64>| {
+        let n = if z { 3 } else { 2 };
+        let coordinates: Vec<String> = position.iter().take(n).map(f64::to_string).collect();
+        wkt.push_str(&coordinates.join(" "));
+    };
+
+    match geometry {
+        Geometry::Point(position) if position.is_empty() => wkt.push_str("EMPTY"),
+        Geometry::Point(position) => list(wkt, std::slice::from_ref(position), coordinates),
+        Geometry::LineString(positions) => list(wkt, positions, coordinates),
+        Geometry::MultiPoint(positions) => list(wkt, positions, |wkt, position| {
+            list(wkt, std::slice::from_ref(position), coordinates)
+        }),
+        Geometry::Polygon(lines) | Geometry::MultiLineString(lines) => {
+            list(wkt, lines, |wkt, line| list(wkt, line, coordinates))
+        }
+        Geometry::MultiPolygon(polygons) => list(wkt, polygons, |wkt, polygon| {
+            list(wkt, polygon, |wkt, ring| list(wkt, ring, coordinates))
+        }),
+        Geometry::GeometryCollection(geometries) => {
+            list(wkt, geometries, |wkt, g| write_wkt(wkt, &g.value, z))
+        }
+    }
+}
+
+/// Parenthesized and comma separated items or `EMPTY`
+fn list<T>(wkt: &mut String, items: &[T], item: impl Fn(&mut String, &T)) {
+    if items.is_empty() {
+        wkt.push_str("EMPTY");
+        return;
+    }
+    wkt.push('(');
+    for (i, value) in items.iter().enumerate() {
+        if i > 0 {
+            wkt.push_str(", ");
+        }
+        item(wkt, value);
+    }
+    wkt.push(')');
+}
@@ -0,0 +1,654 @@
+//! [FlatGeobuf](https://flatgeobuf.org) encoding of features
+//!
+//! A file starts with the magic bytes, followed by the size prefixed header,
+//! an optional packed Hilbert R-tree and the size prefixed features. Header and
+//! features are FlatBuffers tables as defined by the `header.fbs` and
+//! `feature.fbs` schemas of the specification.
+
+use geojson::Value as Geometry;
+use serde_json::Value;
+
+use ogcapi_types::{
+    common::Crs,
+    features::{Feature, Queryables},
+};
+
+use super::positions;
+
+const MAGIC: [u8; 8] = [0x66, 0x67, 0x62, 0x03, 0x66, 0x67, 0x62, 0x00];
+
+/// Number of children per node of the spatial index
+const NODE_SIZE: u16 = 16;
+
+const HILBERT_MAX: f64 = ((1 << 16) - 1) as f64;
+
+/// Column types of the `ColumnType` enum
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub(crate) enum ColumnType {
+    Bool = 2,
+    Long = 7,
+    Double = 10,
+    String = 11,
+    Json = 12,
+    DateTime = 13,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Column {
+    pub(crate) name: String,
+    pub(crate) r#type: ColumnType,
+}
+
+/// Layer level metadata of the header
+pub(crate) struct Layer<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) title: Option<&'a str>,
+    pub(crate) crs: &'a Crs,
+    pub(crate) columns: &'a [Column],
+}
+
+/// Derive the columns from the queryables of a collection, optionally limited to
+/// a selection of properties. The feature id is always the first column.
+pub(crate) fn columns(queryables: &Queryables, selection: Option<&[String]>) -> Vec<Column> {
+    let mut columns = vec![Column {
+        name: "id".to_string(),
+        r#type: ColumnType::String,
+    }];
+
+    for (name, queryable) in queryables.properties.iter() {
+        if name == "id" || name == "geometry" || queryable.r#ref.is_some() {
+            continue;
+        }
+        if selection.map_or(false, |selection| !selection.contains(name)) {
+            continue;
+        }
+
+        let r#type = match (queryable.r#type.as_deref(), queryable.format.as_deref()) {
+            (Some("integer"), _) => ColumnType::Long,
+            (Some("number"), _) => ColumnType::Double,
+            (Some("boolean"), _) => ColumnType::Bool,
+            (Some("string"), Some("date-time")) => ColumnType::DateTime,
+            (Some("string"), _) => ColumnType::String,
+            _ => ColumnType::Json,
+        };
+        columns.push(Column {
+            name: name.to_owned(),
+            r#type,
+        });
+    }
+
+    columns
+}
+
+/// Encode features as a complete FlatGeobuf file.
+///
+/// Unlike GeoJSON, the file can't be written while the features are read. The
+/// header holds the geometry type, the dimension, the number of features and
+/// the extent, and the spatial index with the offsets of the features precedes
+/// them, so all features are required before the first byte.
+///
+/// The spatial index is only built with `index` if all features have a geometry,
+/// in which case the features are written in the order of the index.
+pub(crate) fn encode(layer: &Layer, features: &[Feature], index: bool) -> Vec<u8> {
+    let geometries: Vec<Option<&Geometry>> = features
+        .iter()
+        .map(|f| f.geometry.as_ref().map(|g| &g.value))
+        .collect();
+
+    // common geometry type or unknown if mixed
+    let mut types = geometries.iter().flatten().map(|g| geometry_type(g));
+    let first = types.next().unwrap_or(0);
+    let r#type = if types.all(|t| t == first) { first } else { 0 };
+
+    let has_z = geometries.iter().flatten().any(|g| has_z(g));
+
+    let envelopes: Vec<Option<[f64; 4]>> =
+        geometries.iter().map(|g| g.and_then(envelope)).collect();
+    let extent = envelopes
+        .iter()
+        .flatten()
+        .copied()
+        .reduce(|a, b| expand(a, &b));
+
+    let index = index && !features.is_empty() && envelopes.iter().all(Option::is_some);
+
+    let mut order: Vec<usize> = (0..features.len()).collect();
+    if index {
+        let extent = extent.unwrap_or_default();
+        order.sort_by_cached_key(|i| hilbert(&envelopes[*i].unwrap_or_default(), &extent));
+    }
+
+    let mut encoded = Vec::new();
+    let mut nodes = Vec::new();
+    let mut offset = 0;
+    for i in order {
+        let feature = feature(layer.columns, &features[i], r#type, has_z);
+        if let Some(envelope) = envelopes[i] {
+            nodes.push((envelope, offset as u64));
+        }
+        offset += feature.len();
+        encoded.push(feature);
+    }
+
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend(header(
+        layer,
+        r#type,
+        has_z,
+        extent,
+        features.len() as u64,
+        index,
+    ));
+    if index {
+        bytes.extend(packed_rtree(nodes));
+    }
+    for feature in encoded {
+        bytes.extend(feature);
+    }
+    bytes
+}
+
+/// Size prefixed `Header` table
+fn header(
+    layer: &Layer,
+    r#type: u8,
+    has_z: bool,
+    extent: Option<[f64; 4]>,
+    features_count: u64,
+    index: bool,
+) -> Vec<u8> {
+    let mut b = Builder::default();
+
+    let name = b.string(layer.name);
+    let title = layer.title.map(|title| b.string(title));
+    let envelope = extent.map(|extent| b.doubles(&extent));
+
+    let columns: Vec<usize> = layer
+        .columns
+        .iter()
+        .map(|column| {
+            let name = b.string(&column.name);
+            b.start();
+            b.add_offset(0, name);
+            b.add_u8(1, column.r#type as u8);
+            b.end()
+        })
+        .collect();
+    let columns = b.offsets(&columns);
+
+    let org = b.string("EPSG");
+    b.start();
+    b.add_offset(0, org);
+    // the code is left at zero for crs without an EPSG one
+    b.add_i32(1, layer.crs.as_srid().unwrap_or_default());
+    let crs = b.end();
+
+    b.start();
+    b.add_offset(0, name);
+    if let Some(envelope) = envelope {
+        b.add_offset(1, envelope);
+    }
+    b.add_u8(2, r#type);
+    b.add_u8(3, has_z as u8);
+    b.add_offset(7, columns);
+    b.add_u64(8, features_count);
+    // the default node size of the schema implies an index
+    b.add_u16(9, if index { NODE_SIZE } else { 0 });
+    b.add_offset(10, crs);
+    if let Some(title) = title {
+        b.add_offset(11, title);
+    }
+    let header = b.end();
+
+    b.finish(header)
+}
+
+/// Size prefixed `Feature` table
+fn feature(columns: &[Column], feature: &Feature, r#type: u8, has_z: bool) -> Vec<u8> {
+    let mut b = Builder::default();
+
+    let geometry = feature.geometry.as_ref().map(|g| {
+        // parts and geometries of unknown type carry their own type
+        let r#type = (r#type == 0).then(|| geometry_type(&g.value));
+        geometry(&mut b, &g.value, has_z, r#type)
+    });
+    let properties = b.bytes(&properties(columns, feature));
+
+    b.start();
+    if let Some(geometry) = geometry {
+        b.add_offset(0, geometry);
+    }
+    b.add_offset(1, properties);
+    let feature = b.end();
+
+    b.finish(feature)
+}
+
+/// Column index and little endian value of all non null properties
+fn properties(columns: &[Column], feature: &Feature) -> Vec<u8> {
+    let mut bytes = Vec::new();
+
+    for (i, column) in columns.iter().enumerate() {
+        let value = if column.name == "id" {
+            feature.id.to_owned().map(Value::String)
+        } else {
+            feature
+                .properties
+                .as_ref()
+                .and_then(|p| p.get(&column.name))
+                .cloned()
+        };
+
+        let encoded = match (column.r#type, value) {
+            (_, None | Some(Value::Null)) => None,
+            (ColumnType::Bool, Some(Value::Bool(b))) => Some(vec![b as u8]),
+            (ColumnType::Long, Some(Value::Number(n))) => {
+                n.as_i64().map(|n| n.to_le_bytes().to_vec())
+            }
+            (ColumnType::Double, Some(Value::Number(n))) => {
+                n.as_f64().map(|n| n.to_le_bytes().to_vec())
+            }
+            (ColumnType::String | ColumnType::DateTime, Some(Value::String(s))) => Some(string(&s)),
+            (ColumnType::String | ColumnType::Json, Some(value)) => {
+                Some(string(&value.to_string()))
+            }
+            // values not matching the column type are omitted
+            _ => None,
+        };
+
+        if let Some(encoded) = encoded {
+            bytes.extend((i as u16).to_le_bytes());
+            bytes.extend(encoded);
+        }
+    }
+
+    bytes
+}
+
+fn string(s: &str) -> Vec<u8> {
+    let mut bytes = (s.len() as u32).to_le_bytes().to_vec();
+    bytes.extend(s.as_bytes());
+    bytes
+}
+
+/// Coordinates of a geometry as flat arrays
+#[derive(Default)]
+struct Coordinates {
+    xy: Vec<f64>,
+    z: Vec<f64>,
+    ends: Vec<u32>,
+}
+
+impl Coordinates {
+    fn push(&mut self, position: &[f64]) {
+        self.xy.push(position.first().copied().unwrap_or(f64::NAN));
+        self.xy.push(position.get(1).copied().unwrap_or(f64::NAN));
+        self.z.push(position.get(2).copied().unwrap_or(f64::NAN));
+    }
+
+    fn extend(&mut self, positions: &[Vec<f64>]) {
+        for position in positions {
+            self.push(position);
+        }
+        self.ends.push((self.xy.len() / 2) as u32);
+    }
+}
+
+/// `Geometry` table, multi polygons and collections are written as parts
+fn geometry(b: &mut Builder, geometry: &Geometry, has_z: bool, r#type: Option<u8>) -> usize {
+    let mut coordinates = Coordinates::default();
+    let mut parts = Vec::new();
+
+    match geometry {
+        Geometry::Point(position) => coordinates.push(position),
+        Geometry::MultiPoint(positions) | Geometry::LineString(positions) => {
+            coordinates.extend(positions)
+        }
+        Geometry::MultiLineString(lines) | Geometry::Polygon(lines) => {
+            for line in lines {
+                coordinates.extend(line);
+            }
+        }
+        Geometry::MultiPolygon(polygons) => {
+            for polygon in polygons {
+                let polygon = Geometry::Polygon(polygon.to_owned());
+                parts.push(self::geometry(b, &polygon, has_z, Some(3)));
+            }
+        }
+        Geometry::GeometryCollection(geometries) => {
+            for g in geometries {
+                let r#type = geometry_type(&g.value);
+                parts.push(self::geometry(b, &g.value, has_z, Some(r#type)));
+            }
+        }
+    }
+
+    let parts = (!parts.is_empty()).then(|| b.offsets(&parts));
+    // a single part needs no ends
+    let ends = (coordinates.ends.len() > 1).then(|| b.uints(&coordinates.ends));
+    let xy = (!coordinates.xy.is_empty()).then(|| b.doubles(&coordinates.xy));
+    let z = (has_z && !coordinates.z.is_empty()).then(|| b.doubles(&coordinates.z));
+
+    b.start();
+    if let Some(ends) = ends {
+        b.add_offset(0, ends);
+    }
+    if let Some(xy) = xy {
+        b.add_offset(1, xy);
+    }
+    if let Some(z) = z {
+        b.add_offset(2, z);
+    }
+    if let Some(r#type) = r#type {
+        b.add_u8(6, r#type);
+    }
+    if let Some(parts) = parts {
+        b.add_offset(7, parts);
+    }
+    b.end()
+}
+
+/// Value of the `GeometryType` enum
+fn geometry_type(geometry: &Geometry) -> u8 {
+    match geometry {
+        Geometry::Point(_) => 1,
+        Geometry::LineString(_) => 2,
+        Geometry::Polygon(_) => 3,
+        Geometry::MultiPoint(_) => 4,
+        Geometry::MultiLineString(_) => 5,
+        Geometry::MultiPolygon(_) => 6,
+        Geometry::GeometryCollection(_) => 7,
+    }
+}
+
+fn has_z(geometry: &Geometry) -> bool {
+    positions(geometry).any(|position| position.len() > 2)
+}
+
+/// Bounding box `[minx, miny, maxx, maxy]` of a non empty geometry
+fn envelope(geometry: &Geometry) -> Option<[f64; 4]> {
+    positions(geometry)
+        .filter(|position| position.len() >= 2)
+        .map(|position| [position[0], position[1], position[0], position[1]])
+        .reduce(|a, b| expand(a, &b))
+}
+
+fn expand(a: [f64; 4], b: &[f64; 4]) -> [f64; 4] {
+    [
+        a[0].min(b[0]),
+        a[1].min(b[1]),
+        a[2].max(b[2]),
+        a[3].max(b[3]),
+    ]
+}
+
+/// Packed Hilbert R-tree with the leaves holding the byte offsets of the
+/// features, which must be in Hilbert order.
+///
+/// The nodes are stored level by level from the root, every node refers to the
+/// position of its first child.
+fn packed_rtree(leaves: Vec<([f64; 4], u64)>) -> Vec<u8> {
+    let node_size = NODE_SIZE as usize;
+
+    // number of nodes per level, from the leaves up to the root
+    let mut levels = vec![leaves.len()];
+    let mut n = leaves.len();
+    loop {
+        n = (n + node_size - 1) / node_size;
+        levels.push(n);
+        if n == 1 {
+            break;
+        }
+    }
+    let num_nodes: usize = levels.iter().sum();
+
+    // position of the first node of each level
+    let mut starts = Vec::new();
+    let mut end = num_nodes;
+    for count in levels.iter() {
+        starts.push(end - count);
+        end -= count;
+    }
+
+    let mut nodes = vec![([f64::NAN; 4], 0); num_nodes];
+    nodes[starts[0]..].copy_from_slice(&leaves);
+
+    for level in 0..levels.len() - 1 {
+        let mut pos = starts[level];
+        let end = pos + levels[level];
+        let mut parent = starts[level + 1];
+        while pos < end {
+            let last = end.min(pos + node_size);
+            let envelope = nodes[pos..last]
+                .iter()
+                .map(|(envelope, _)| *envelope)
+                .reduce(|a, b| expand(a, &b))
+                .unwrap_or_default();
+            nodes[parent] = (envelope, pos as u64);
+            pos = last;
+            parent += 1;
+        }
+    }
+
+    let mut bytes = Vec::with_capacity(num_nodes * 40);
+    for (envelope, offset) in nodes {
+        for value in envelope {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.extend(offset.to_le_bytes());
+    }
+    bytes
+}
+
+/// Position of the envelope center on a Hilbert curve across the extent
+fn hilbert(envelope: &[f64; 4], extent: &[f64; 4]) -> u32 {
+    let scale = |min: f64, max: f64, lower: f64, upper: f64| {
+        let width = upper - lower;
+        if width > 0.0 {
+            (HILBERT_MAX * ((min + max) / 2.0 - lower) / width) as u32
+        } else {
+            0
+        }
+    };
+    let x = scale(envelope[0], envelope[2], extent[0], extent[2]);
+    let y = scale(envelope[1], envelope[3], extent[1], extent[3]);
+
+    // https://github.com/rawrunprotected/hilbert_curves
+    let mut a = x ^ y;
+    let mut b = 0xFFFF ^ a;
+    let mut c = 0xFFFF ^ (x | y);
+    let mut d = x & (y ^ 0xFFFF);
+
+    let mut aa = a | (b >> 1);
+    let mut bb = (a >> 1) ^ a;
+    let mut cc = ((c >> 1) ^ (b & (d >> 1))) ^ c;
+    let mut dd = ((a & (c >> 1)) ^ (d >> 1)) ^ d;
+
+    for shift in [2, 4] {
+        a = aa;
+        b = bb;
+        c = cc;
+        d = dd;
+        aa = (a & (a >> shift)) ^ (b & (b >> shift));
+        bb = (a & (b >> shift)) ^ (b & ((a ^ b) >> shift));
+        cc ^= (a & (c >> shift)) ^ (b & (d >> shift));
+        dd ^= (b & (c >> shift)) ^ ((a ^ b) & (d >> shift));
+    }
+
+    a = aa;
+    b = bb;
+    c = cc;
+    d = dd;
+    cc ^= (a & (c >> 8)) ^ (b & (d >> 8));
+    dd ^= (b & (c >> 8)) ^ ((a ^ b) & (d >> 8));
+
+    a = cc ^ (cc >> 1);
+    b = dd ^ (dd >> 1);
+
+    let interleave = |mut i: u32| {
+        i = (i | (i << 8)) & 0x00FF00FF;
+        i = (i | (i << 4)) & 0x0F0F0F0F;
+        i = (i | (i << 2)) & 0x33333333;
+        (i | (i << 1)) & 0x55555555
+    };
+
+    let i0 = x ^ y;
+    let i1 = b | (0xFFFF ^ (i0 | a));
+
+    (interleave(i1) << 1) | interleave(i0)
+}
+
+/// Minimal FlatBuffers builder, writing the buffer back to front.
+///
+/// The buffer is kept in reverse order, so positions are counted from its end.
+/// Child objects must be written before the table referring to them.
+#[derive(Default)]
+struct Builder {
+    buf: Vec<u8>,
+    min_align: usize,
+    table_start: usize,
+    fields: Vec<(u16, usize)>,
+}
+
+impl Builder {
+    fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Push little endian bytes
+    fn push(&mut self, bytes: &[u8]) {
+        self.buf.extend(bytes.iter().rev());
+    }
+
+    /// Pad so that `size` is aligned after writing `additional` bytes
+    fn prep(&mut self, size: usize, additional: usize) {
+        self.min_align = self.min_align.max(size);
+        let padding = (size - (self.len() + additional) % size) % size;
+        self.buf.resize(self.len() + padding, 0);
+    }
+
+    fn scalar<const N: usize>(&mut self, bytes: [u8; N]) -> usize {
+        self.prep(N, 0);
+        self.push(&bytes);
+        self.len()
+    }
+
+    /// Offset pointing forward to `target`
+    fn offset(&mut self, target: usize) -> usize {
+        self.prep(4, 0);
+        let offset = (self.len() + 4 - target) as u32;
+        self.push(&offset.to_le_bytes());
+        self.len()
+    }
+
+    fn string(&mut self, s: &str) -> usize {
+        self.prep(4, s.len() + 1);
+        self.push(&[0]);
+        self.push(s.as_bytes());
+        self.push(&(s.len() as u32).to_le_bytes());
+        self.len()
+    }
+
+    fn vector<const N: usize>(
+        &mut self,
+        elements: impl DoubleEndedIterator<Item = [u8; N]>,
+    ) -> usize {
+        let elements: Vec<[u8; N]> = elements.collect();
+        self.prep(4, N * elements.len());
+        self.prep(N, N * elements.len());
+        for element in elements.iter().rev() {
+            self.push(element);
+        }
+        self.push(&(elements.len() as u32).to_le_bytes());
+        self.len()
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) -> usize {
+        self.vector(bytes.iter().map(|b| [*b]))
+    }
+
+    fn uints(&mut self, values: &[u32]) -> usize {
+        self.vector(values.iter().map(|v| v.to_le_bytes()))
+    }
+
+    fn doubles(&mut self, values: &[f64]) -> usize {
+        self.vector(values.iter().map(|v| v.to_le_bytes()))
+    }
+
+    /// Vector of tables
+    fn offsets(&mut self, targets: &[usize]) -> usize {
+        self.prep(4, 4 * targets.len());
+        for target in targets.iter().rev() {
+            self.offset(*target);
+        }
+        self.push(&(targets.len() as u32).to_le_bytes());
+        self.len()
+    }
+
+    fn start(&mut self) {
+        self.fields.clear();
+        self.table_start = self.len();
+    }
+
+    fn add_u8(&mut self, slot: u16, value: u8) {
+        let position = self.scalar(value.to_le_bytes());
+        self.fields.push((slot, position));
+    }
+
+    fn add_u16(&mut self, slot: u16, value: u16) {
+        let position = self.scalar(value.to_le_bytes());
+        self.fields.push((slot, position));
+    }
+
+    fn add_i32(&mut self, slot: u16, value: i32) {
+        let position = self.scalar(value.to_le_bytes());
+        self.fields.push((slot, position));
+    }
+
+    fn add_u64(&mut self, slot: u16, value: u64) {
+        let position = self.scalar(value.to_le_bytes());
+        self.fields.push((slot, position));
+    }
+
+    fn add_offset(&mut self, slot: u16, target: usize) {
+        let position = self.offset(target);
+        self.fields.push((slot, position));
+    }
+
+    /// Write the vtable in front of the table and return the table position
+    fn end(&mut self) -> usize {
+        // placeholder for the offset to the vtable
+        let table = self.scalar(0i32.to_le_bytes());
+
+        let slots = self.fields.iter().map(|(slot, _)| *slot as usize + 1).max();
+        let mut vtable = vec![0u16; slots.unwrap_or_default()];
+        for (slot, position) in self.fields.iter() {
+            vtable[*slot as usize] = (table - position) as u16;
+        }
+
+        for entry in vtable.iter().rev() {
+            self.push(&entry.to_le_bytes());
+        }
+        self.push(&((table - self.table_start) as u16).to_le_bytes());
+        self.push(&((4 + 2 * vtable.len()) as u16).to_le_bytes());
+
+        // the vtable precedes the table
+        let soffset = ((self.len() - table) as i32).to_le_bytes();
+        for (i, byte) in soffset.into_iter().enumerate() {
+            self.buf[table - 1 - i] = byte;
+        }
+
+        table
+    }
+
+    /// Finish a size prefixed buffer with the root table
+    fn finish(mut self, root: usize) -> Vec<u8> {
+        self.prep(self.min_align.max(4), 8);
+        self.offset(root);
+        let size = self.len() as u32;
+        self.push(&size.to_le_bytes());
+        self.buf.reverse();
+        self.buf
+    }
+}
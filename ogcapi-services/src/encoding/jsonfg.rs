@@ -0,0 +1,67 @@
+//! [JSON-FG](https://docs.ogc.org/DRAFTS/21-045.html) encoding of features
+//!
+//! JSON-FG extends GeoJSON with the geometry in other coordinate reference
+//! systems as `place`, the temporal extent as `time` and the feature type.
+//! The `geometry` remains reserved for WGS 84, as required by RFC 7946.
+
+use serde_json::{Map, Value};
+
+use ogcapi_types::{
+    common::{Crs, TemporalProperties, TemporalProperty},
+    features::{Feature, FeatureCollection, Time},
+};
+
+/// Conformance classes of JSON-FG documents
+const CONFORMS_TO: [&str; 1] = ["http://www.opengis.net/spec/json-fg-1/0.1/conf/core"];
+
+/// Move the geometry to `place` unless it is in WGS 84 and set the `time` from
+/// the temporal properties
+pub(crate) fn feature(feature: &mut Feature, crs: &Crs, temporal: &TemporalProperties) {
+    if *crs != Crs::default() {
+        feature.place = feature.geometry.take();
+    }
+    feature.time = feature
+        .properties
+        .as_ref()
+        .and_then(|properties| time(properties, temporal));
+}
+
+/// Single feature as JSON-FG document
+pub(crate) fn document(
+    feature: &mut Feature,
+    feature_type: &str,
+    crs: &Crs,
+    temporal: &TemporalProperties,
+) {
+    self::feature(feature, crs, temporal);
+    feature.feature_type = Some(feature_type.to_owned());
+    feature.coord_ref_sys = Some(crs.to_string());
+    feature.conforms_to = Some(CONFORMS_TO.map(String::from).to_vec());
+}
+
+/// Members of a feature collection as JSON-FG document, the features are
+/// encoded separately
+pub(crate) fn collection(fc: &mut FeatureCollection, feature_type: &str, crs: &Crs) {
+    fc.feature_type = Some(feature_type.to_owned());
+    fc.coord_ref_sys = Some(crs.to_string());
+    fc.conforms_to = Some(CONFORMS_TO.map(String::from).to_vec());
+}
+
+/// Temporal extent from the instant property or else the interval properties
+fn time(properties: &Map<String, Value>, temporal: &TemporalProperties) -> Option<Time> {
+    let property = |property: &Option<TemporalProperty>| {
+        property
+            .as_ref()
+            .and_then(|p| properties.get(p.name()))
+            .and_then(Value::as_str)
+    };
+
+    if let Some(instant) = property(&temporal.instant) {
+        return Some(Time::instant(instant));
+    }
+
+    match (property(&temporal.start), property(&temporal.end)) {
+        (None, None) => None,
+        (start, end) => Some(Time::interval(start, end)),
+    }
+}
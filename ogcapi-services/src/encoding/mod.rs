@@ -0,0 +1,25 @@
+//! Encodings of features besides GeoJSON and HTML
+
+pub(crate) mod csv;
+pub(crate) mod fgb;
+pub(crate) mod jsonfg;
+pub(crate) mod seq;
+
+use geojson::Value as Geometry;
+
+/// All positions of a geometry
+fn positions(geometry: &Geometry) -> Box<dyn Iterator<Item = &Vec<f64>> + '_> {
+    match geometry {
+        Geometry::Point(position) => Box::new(std::iter::once(position)),
+        Geometry::MultiPoint(positions) | Geometry::LineString(positions) => {
+            Box::new(positions.iter())
+        }
+        Geometry::MultiLineString(lines) | Geometry::Polygon(lines) => {
+            Box::new(lines.iter().flatten())
+        }
+        Geometry::MultiPolygon(polygons) => Box::new(polygons.iter().flatten().flatten()),
+        Geometry::GeometryCollection(geometries) => {
+            Box::new(geometries.iter().flat_map(|g| positions(&g.value)))
+        }
+    }
+}
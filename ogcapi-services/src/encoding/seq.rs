@@ -0,0 +1,55 @@
+//! GeoJSON text sequences, see [RFC 8142](https://www.rfc-editor.org/rfc/rfc8142)
+
+/// Record separator, which starts every record of a text sequence
+const RS: u8 = 0x1e;
+
+/// Splits the chunks of a text sequence into its records as they arrive.
+///
+/// Records start with a record separator and may span several lines. Bodies
+/// without a leading record separator are read as newline-delimited GeoJSON,
+/// one record per line.
+#[derive(Default)]
+pub(crate) struct Records {
+    buffer: Vec<u8>,
+    delimiter: Option<u8>,
+}
+
+impl Records {
+    /// Append a chunk and return the records it completes
+    pub(crate) fn push(&mut self, chunk: &[u8]) -> Vec<Vec<u8>> {
+        let start = self.buffer.len();
+        self.buffer.extend_from_slice(chunk);
+
+        let delimiter = match self.delimiter {
+            Some(delimiter) => delimiter,
+            None => match self.buffer.iter().find(|b| !b.is_ascii_whitespace()) {
+                Some(&RS) => *self.delimiter.insert(RS),
+                Some(_) => *self.delimiter.insert(b'\n'),
+                None => return Vec::new(),
+            },
+        };
+
+        // everything before the last delimiter is complete
+        match chunk.iter().rposition(|b| *b == delimiter) {
+            Some(position) => {
+                let rest = self.buffer.split_off(start + position);
+                let complete = std::mem::replace(&mut self.buffer, rest);
+                split(&complete, delimiter)
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// The remaining record at the end of the body
+    pub(crate) fn finish(self) -> Vec<Vec<u8>> {
+        split(&self.buffer, self.delimiter.unwrap_or(RS))
+    }
+}
+
+fn split(bytes: &[u8], delimiter: u8) -> Vec<Vec<u8>> {
+    bytes
+        .split(|b| *b == delimiter)
+        .filter(|record| !record.iter().all(u8::is_ascii_whitespace))
+        .map(<[u8]>::to_vec)
+        .collect()
+}
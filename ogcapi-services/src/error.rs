@@ -5,6 +5,7 @@ use axum::{
     Json,
 };
 
+use ogcapi_drivers::{InvalidCollection, InvalidFilter, InvalidToken, UnknownCrs};
 use ogcapi_types::common::{media_type::PROBLEM_JSON, Exception};
 
 /// A common error type that can be used throughout the API.
@@ -22,7 +23,7 @@ pub enum Error {
 
     /// Return `500 Internal Server Error` on a `anyhow::Error`.
     #[error("an internal server error occurred")]
-    Anyhow(#[from] anyhow::Error),
+    Anyhow(anyhow::Error),
 
     /// Return `500 Internal Server Error` on a `url::ParseError`.
     #[error("an internal server error occurred")]
@@ -37,6 +38,25 @@ pub enum Error {
     Exception(StatusCode, String),
 }
 
+/// Errors of the drivers about invalid requests are exceptions of the client
+impl From<anyhow::Error> for Error {
+    fn from(error: anyhow::Error) -> Self {
+        if let Some(unknown) = error.downcast_ref::<UnknownCrs>() {
+            return Error::Exception(StatusCode::BAD_REQUEST, unknown.to_string());
+        }
+        if let Some(invalid) = error.downcast_ref::<InvalidFilter>() {
+            return Error::Exception(StatusCode::BAD_REQUEST, invalid.to_string());
+        }
+        if let Some(invalid) = error.downcast_ref::<InvalidToken>() {
+            return Error::Exception(StatusCode::BAD_REQUEST, invalid.to_string());
+        }
+        if let Some(invalid) = error.downcast_ref::<InvalidCollection>() {
+            return Error::Exception(StatusCode::BAD_REQUEST, invalid.to_string());
+        }
+        Error::Anyhow(error)
+    }
+}
+
 impl Error {
     fn status_code(&self) -> StatusCode {
         match self {
@@ -1,10 +1,14 @@
 use anyhow::Context;
 use axum::{
     extract::{FromRequestParts, Host, OriginalUri},
-    http::{request::Parts, StatusCode},
+    http::{header::ACCEPT, request::Parts, StatusCode},
 };
 use url::Url;
 
+use ogcapi_types::common::media_type::{
+    CSV, FLAT_GEOBUF, GEO_JSON, GEO_JSON_SEQ, HTML, JSON, JSON_FG,
+};
+
 use crate::Error;
 
 /// Extractor for the remote URL
@@ -61,3 +65,158 @@ where
         }
     }
 }
+
+/// Requested encoding of the response, from the `f` query parameter or else
+/// the `Accept` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Format {
+    Json,
+    Html,
+    JsonFg,
+    GeoJsonSeq,
+    FlatGeobuf,
+    Csv,
+}
+
+impl Format {
+    /// Encodings of all resources
+    const DOCUMENT: [Format; 2] = [Format::Json, Format::Html];
+
+    /// Encodings of a single feature
+    const FEATURE: [Format; 4] = [Format::Json, Format::Html, Format::JsonFg, Format::Csv];
+
+    /// Encodings of the features of a collection
+    const ITEMS: [Format; 6] = [
+        Format::Json,
+        Format::Html,
+        Format::JsonFg,
+        Format::GeoJsonSeq,
+        Format::FlatGeobuf,
+        Format::Csv,
+    ];
+
+    /// Value of the `f` query parameter
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Html => "html",
+            Format::JsonFg => "jsonfg",
+            Format::GeoJsonSeq => "geojsonseq",
+            Format::FlatGeobuf => "fgb",
+            Format::Csv => "csv",
+        }
+    }
+
+    fn from_media_type(media_type: &str) -> Option<Self> {
+        match media_type {
+            HTML | "application/xhtml+xml" => Some(Format::Html),
+            JSON | GEO_JSON | "application/*" | "*/*" => Some(Format::Json),
+            JSON_FG => Some(Format::JsonFg),
+            GEO_JSON_SEQ => Some(Format::GeoJsonSeq),
+            FLAT_GEOBUF => Some(Format::FlatGeobuf),
+            CSV => Some(Format::Csv),
+            _ => None,
+        }
+    }
+
+    /// Negotiate the format among the supported ones
+    fn negotiate(parts: &Parts, supported: &[Format]) -> Result<Self, Error> {
+        let f = url::form_urlencoded::parse(parts.uri.query().unwrap_or("").as_bytes())
+            .find(|(key, _)| key == "f")
+            .map(|(_, value)| value);
+
+        if let Some(f) = f {
+            return f
+                .parse()
+                .ok()
+                .filter(|format| supported.contains(format))
+                .ok_or_else(|| {
+                    Error::Exception(StatusCode::BAD_REQUEST, format!("Unsupported format `{f}`"))
+                });
+        }
+
+        // media ranges by descending quality
+        let mut media_ranges: Vec<(&str, f32)> = parts
+            .headers
+            .get_all(ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|range| {
+                let mut params = range.split(';').map(str::trim);
+                let media_type = params.next().unwrap_or_default();
+                let quality = params
+                    .find_map(|p| p.strip_prefix("q="))
+                    .and_then(|q| q.parse().ok())
+                    .unwrap_or(1.0);
+                (media_type, quality)
+            })
+            .collect();
+        media_ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        Ok(media_ranges
+            .into_iter()
+            .filter_map(|(media_type, _)| Format::from_media_type(media_type))
+            .find(|format| supported.contains(format))
+            .unwrap_or(Format::Json))
+    }
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" | "geojson" => Ok(Format::Json),
+            "html" => Ok(Format::Html),
+            "jsonfg" => Ok(Format::JsonFg),
+            "geojsonseq" => Ok(Format::GeoJsonSeq),
+            "fgb" | "flatgeobuf" => Ok(Format::FlatGeobuf),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("Unsupported format `{s}`")),
+        }
+    }
+}
+
+#[axum::async_trait]
+impl<S> FromRequestParts<S> for Format
+where
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        Format::negotiate(parts, &Format::DOCUMENT)
+    }
+}
+
+/// Requested encoding of a single feature
+pub(crate) struct FeatureFormat(pub(crate) Format);
+
+#[axum::async_trait]
+impl<S> FromRequestParts<S> for FeatureFormat
+where
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        Format::negotiate(parts, &Format::FEATURE).map(FeatureFormat)
+    }
+}
+
+/// Requested encoding of the features of a collection, which are available in
+/// more formats than other resources
+pub(crate) struct ItemsFormat(pub(crate) Format);
+
+#[axum::async_trait]
+impl<S> FromRequestParts<S> for ItemsFormat
+where
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        Format::negotiate(parts, &Format::ITEMS).map(ItemsFormat)
+    }
+}
@@ -0,0 +1,394 @@
+//! Server-side rendering of resources as HTML pages
+
+use axum::response::Html;
+use serde_json::Value;
+use url::Url;
+
+use ogcapi_types::common::{
+    link_rel::{ALTERNATE, COLLECTION, ROOT, SELF},
+    media_type::HTML,
+    Collection, Collections, Conformance, LandingPage, Link, Links,
+};
+#[cfg(feature = "features")]
+use ogcapi_types::features::{Feature, FeatureCollection};
+
+use crate::extractors::Format;
+
+static PAGE: &str = include_str!("../assets/templates/page.html");
+
+/// Add a link to the resource in the other encoding and set the media type of
+/// the `self` link for HTML.
+pub(crate) fn alternate(links: &mut Links, url: &Url, format: Format, media_type: &str) {
+    let link = match format {
+        Format::Html => {
+            if let Some(link) = links.iter_mut().find(|l| l.rel == SELF) {
+                link.r#type = Some(HTML.to_string());
+            }
+            Link::new(with_format(url, Format::Json), ALTERNATE)
+                .mediatype(media_type)
+                .title("This document as JSON")
+        }
+        _ => Link::new(with_format(url, Format::Html), ALTERNATE)
+            .mediatype(HTML)
+            .title("This document as HTML"),
+    };
+    links.retain(|l| !(l.rel == ALTERNATE && l.r#type == link.r#type));
+    links.push(link);
+}
+
+/// Set the `f` query parameter of the url
+pub(crate) fn with_format(url: &Url, format: Format) -> Url {
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(key, _)| key != "f")
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+
+    let mut url = url.to_owned();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(pairs)
+        .append_pair("f", format.as_str());
+    url
+}
+
+pub(crate) fn landing_page(landing_page: &LandingPage) -> Html<String> {
+    let title = landing_page.title.as_deref().unwrap_or("OGC API");
+
+    let mut content = paragraph(landing_page.description.as_deref());
+    if let Some(attribution) = landing_page.attribution.as_deref() {
+        // may include markup by definition
+        content.push_str(&format!("<p><small>{attribution}</small></p>"));
+    }
+
+    render(title, &landing_page.links, &content, "")
+}
+
+pub(crate) fn conformance(conformance: &Conformance) -> Html<String> {
+    let rows = conformance
+        .conforms_to
+        .iter()
+        .map(|class| format!("<li><code>{}</code></li>", escape(class)))
+        .collect::<String>();
+
+    render("Conformance", &[], &format!("<ul>{rows}</ul>"), "")
+}
+
+pub(crate) fn collections(collections: &Collections) -> Html<String> {
+    let rows = collections
+        .collections
+        .iter()
+        .map(|collection| {
+            let title = escape(collection.title.as_deref().unwrap_or(&collection.id));
+            let title = match collection.links.iter().find(|l| l.rel == SELF) {
+                Some(link) => anchor(&link.href, &title, true),
+                None => title,
+            };
+            format!(
+                "<tr><td>{title}</td><td>{}</td></tr>",
+                escape(collection.description.as_deref().unwrap_or_default())
+            )
+        })
+        .collect::<String>();
+
+    let content = format!(
+        "<table><thead><tr><th>Collection</th><th>Description</th></tr></thead>\
+        <tbody>{rows}</tbody></table>"
+    );
+
+    render("Collections", &collections.links, &content, "")
+}
+
+pub(crate) fn collection(collection: &Collection) -> Html<String> {
+    let mut rows = vec![("Id", escape(&collection.id))];
+    if !collection.keywords.is_empty() {
+        rows.push(("Keywords", escape(&collection.keywords.join(", "))));
+    }
+    if let Some(extent) = collection.extent.as_ref() {
+        let extent = serde_json::to_value(extent).unwrap_or_default();
+        rows.push(("Extent", value(&extent)));
+    }
+    if !collection.crs.is_empty() {
+        let crs = collection
+            .crs
+            .iter()
+            .map(|crs| format!("<code>{}</code>", escape(&crs.to_string())))
+            .collect::<Vec<String>>()
+            .join("<br>");
+        rows.push(("CRS", crs));
+    }
+    if let Some(storage_crs) = collection.storage_crs.as_ref() {
+        rows.push((
+            "Storage CRS",
+            format!("<code>{}</code>", escape(&storage_crs.to_string())),
+        ));
+    }
+
+    let content = format!(
+        "{}{}",
+        paragraph(collection.description.as_deref()),
+        table(&rows)
+    );
+
+    render(
+        collection.title.as_deref().unwrap_or(&collection.id),
+        &collection.links,
+        &content,
+        "",
+    )
+}
+
+/// Features of a collection, with a map if the geometries are in CRS84
+#[cfg(feature = "features")]
+pub(crate) fn items(collection: &Collection, fc: &FeatureCollection, map: bool) -> Html<String> {
+    let mut keys: Vec<&String> = Vec::new();
+    for feature in fc.features.iter() {
+        for key in feature.properties.iter().flat_map(|p| p.keys()) {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+    }
+
+    let header = keys
+        .iter()
+        .map(|key| format!("<th>{}</th>", escape(key)))
+        .collect::<String>();
+
+    let rows = fc
+        .features
+        .iter()
+        .map(|feature| {
+            let id = escape(feature.id.as_deref().unwrap_or_default());
+            let id = match feature.links.iter().find(|l| l.rel == SELF) {
+                Some(link) => anchor(&link.href, &id, true),
+                None => id,
+            };
+            let cells = keys
+                .iter()
+                .map(|key| {
+                    let cell = feature
+                        .properties
+                        .as_ref()
+                        .and_then(|p| p.get(*key))
+                        .map(value)
+                        .unwrap_or_default();
+                    format!("<td>{cell}</td>")
+                })
+                .collect::<String>();
+            format!("<tr><td>{id}</td>{cells}</tr>")
+        })
+        .collect::<String>();
+
+    let mut content = String::new();
+    if let Some(number_matched) = fc.number_matched {
+        content.push_str(&format!(
+            "<p>{} of {number_matched} features</p>",
+            fc.features.len()
+        ));
+    }
+    if map {
+        content.push_str(r#"<div id="map"></div>"#);
+    }
+    content.push_str(&format!(
+        "<table><thead><tr><th>Id</th>{header}</tr></thead><tbody>{rows}</tbody></table>"
+    ));
+
+    let script = if map {
+        map_script(&serde_json::to_value(fc).unwrap_or_default())
+    } else {
+        String::new()
+    };
+
+    render(
+        collection.title.as_deref().unwrap_or(&collection.id),
+        &fc.links,
+        &content,
+        &script,
+    )
+}
+
+/// Single feature, with a map if the geometry is in CRS84
+#[cfg(feature = "features")]
+pub(crate) fn feature(feature: &Feature, map: bool) -> Html<String> {
+    let rows = feature
+        .properties
+        .iter()
+        .flatten()
+        .map(|(key, v)| (key.as_str(), value(v)))
+        .collect::<Vec<(&str, String)>>();
+
+    let mut content = String::new();
+    if map {
+        content.push_str(r#"<div id="map"></div>"#);
+    }
+    content.push_str(&table(&rows));
+
+    let script = if map {
+        map_script(&serde_json::to_value(feature).unwrap_or_default())
+    } else {
+        String::new()
+    };
+
+    render(
+        feature.id.as_deref().unwrap_or("Feature"),
+        &feature.links,
+        &content,
+        &script,
+    )
+}
+
+fn render(title: &str, links: &[Link], content: &str, script: &str) -> Html<String> {
+    let mut breadcrumbs = Vec::new();
+    for (rel, label) in [(ROOT, "Home"), (COLLECTION, "Collection")] {
+        if let Some(link) = links.iter().find(|l| l.rel == rel) {
+            breadcrumbs.push(anchor(&link.href, label, true));
+        }
+    }
+
+    let rows = links
+        .iter()
+        .map(|link| {
+            // resources of this API are browsable as HTML
+            let html = link.r#type.as_deref() != Some(HTML)
+                && matches!(
+                    link.rel.as_str(),
+                    ROOT | COLLECTION | "data" | "items" | "next" | "prev" | "conformance"
+                );
+            let title = escape(link.title.as_deref().unwrap_or(&link.href));
+            format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(&link.rel),
+                anchor(&link.href, &title, html),
+                escape(link.r#type.as_deref().unwrap_or_default())
+            )
+        })
+        .collect::<String>();
+    let links = format!(
+        "<table><thead><tr><th>Relation</th><th>Link</th><th>Media type</th></tr></thead>\
+        <tbody>{rows}</tbody></table>"
+    );
+
+    Html(template(
+        PAGE,
+        &[
+            ("title", &escape(title)),
+            ("breadcrumbs", &breadcrumbs.join(" / ")),
+            ("content", content),
+            ("links", &links),
+            ("script", script),
+        ],
+    ))
+}
+
+/// Substitute `{{name}}` placeholders in a single pass, so values are never
+/// interpreted as placeholders themselves.
+fn template(template: &str, values: &[(&str, &str)]) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find("}}") {
+            Some(end) => {
+                let name = &after[..end];
+                match values.iter().find(|(key, _)| *key == name) {
+                    Some((_, value)) => output.push_str(value),
+                    None => output.push_str(&rest[start..start + end + 4]),
+                }
+                rest = &after[end + 2..];
+            }
+            None => {
+                output.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(feature = "features")]
+fn map_script(geojson: &Value) -> String {
+    // `</script>` must not end the script element early
+    let geojson = geojson.to_string().replace("</", r"<\/");
+    format!(
+        r#"<script src="https://unpkg.com/leaflet@1.9.2/dist/leaflet.js" crossorigin=""></script>
+    <script>
+        const map = L.map("map");
+        L.tileLayer("https://tile.openstreetmap.org/{{z}}/{{x}}/{{y}}.png", {{
+            maxZoom: 19,
+            attribution: "&copy; OpenStreetMap contributors"
+        }}).addTo(map);
+        const layer = L.geoJSON({geojson}).addTo(map);
+        const bounds = layer.getBounds();
+        if (bounds.isValid()) {{
+            map.fitBounds(bounds, {{ maxZoom: 16 }});
+        }} else {{
+            map.setView([0, 0], 1);
+        }}
+    </script>"#
+    )
+}
+
+fn paragraph(text: Option<&str>) -> String {
+    text.map(|text| format!("<p>{}</p>", escape(text)))
+        .unwrap_or_default()
+}
+
+fn table(rows: &[(&str, String)]) -> String {
+    let rows = rows
+        .iter()
+        .map(|(key, value)| format!("<tr><th>{}</th><td>{value}</td></tr>", escape(key)))
+        .collect::<String>();
+    format!("<table><tbody>{rows}</tbody></table>")
+}
+
+fn value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => escape(s),
+        Value::Bool(_) | Value::Number(_) => value.to_string(),
+        _ => format!("<code>{}</code>", escape(&value.to_string())),
+    }
+}
+
+/// Anchor around an escaped label, with `html` to the HTML encoding of a
+/// resource of this API. Links other than http, https and relative ones, like
+/// `javascript:`, are not followable and only the label is rendered.
+fn anchor(href: &str, label: &str, html: bool) -> String {
+    let followable = match Url::parse(href) {
+        Ok(url) => matches!(url.scheme(), "http" | "https"),
+        Err(e) => e == url::ParseError::RelativeUrlWithoutBase,
+    };
+    if !followable {
+        return label.to_owned();
+    }
+
+    let href = if html { html_href(href) } else { escape(href) };
+    format!(r#"<a href="{href}">{label}</a>"#)
+}
+
+/// Link to the HTML encoding of a resource of this API
+fn html_href(href: &str) -> String {
+    match Url::parse(href) {
+        Ok(url) => escape(with_format(&url, Format::Html).as_str()),
+        Err(_) => escape(href),
+    }
+}
+
+/// Escape text for HTML content and attribute values
+pub(crate) fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#x27;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -1,7 +1,11 @@
 mod config;
+#[cfg(feature = "features")]
+mod encoding;
 mod error;
 mod extractors;
+mod html;
 mod openapi;
+mod patch;
 #[cfg(feature = "processes")]
 mod processor;
 mod routes;
@@ -1,26 +1,36 @@
+use anyhow::Context;
 use axum::{
     extract::{Path, State},
     headers::HeaderMap,
     http::{header::LOCATION, StatusCode},
-    Json,
-    {routing::get, Router},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
 };
+use serde::Deserialize;
+use serde_json::Value;
+use url::Url;
 
 use ogcapi_types::common::{
-    link_rel::{DATA, ITEMS, ROOT, SELF},
-    media_type::{GEO_JSON, JSON},
-    Collection, Collections, Crs, Link, Linked, Query,
+    link_rel::{DATA, ITEMS, NEXT, PREV, QUERYABLES, ROOT, SELF},
+    media_type::{GEO_JSON, JSON, SCHEMA_JSON},
+    Collection, Link, Linked, Query,
 };
 
 use crate::{
-    extractors::{Qs, RemoteUrl},
-    AppState, Error, Result,
+    extractors::{Format, Qs, RemoteUrl},
+    html, patch, AppState, Error, Result,
 };
 
-const CONFORMANCE: [&str; 3] = [
+#[cfg(feature = "edr")]
+use super::crs;
+
+const CONFORMANCE: [&str; 5] = [
     "http://www.opengis.net/spec/ogcapi-common-1/1.0/conf/core",
+    "http://www.opengis.net/spec/ogcapi-common-1/1.0/conf/html",
     "http://www.opengis.net/spec/ogcapi-common-2/1.0/conf/collections",
     "http://www.opengis.net/spec/ogcapi_common-2/1.0/conf/json",
+    "http://www.opengis.net/spec/ogcapi-common-2/1.0/conf/html",
 ];
 
 /// Create new collection metadata
@@ -29,6 +39,8 @@ async fn create(
     RemoteUrl(url): RemoteUrl,
     Json(collection): Json<Collection>,
 ) -> Result<(StatusCode, HeaderMap)> {
+    is_valid_id(&collection.id)?;
+
     if state
         .drivers
         .collections
@@ -56,12 +68,30 @@ async fn create(
     Ok((StatusCode::CREATED, headers))
 }
 
+/// Check that a collection id is a path segment of unreserved characters
+/// which fits a table name
+fn is_valid_id(id: &str) -> Result<()> {
+    if id.is_empty()
+        || id.len() > 63
+        || !id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-._~".contains(c))
+    {
+        return Err(Error::Exception(
+            StatusCode::BAD_REQUEST,
+            format!("Collection id `{id}` not valid, use up to 63 of `A-Za-z0-9-._~`"),
+        ));
+    }
+    Ok(())
+}
+
 /// Get collection metadata
 async fn read(
     State(state): State<AppState>,
     Path(collection_id): Path<String>,
     RemoteUrl(url): RemoteUrl,
-) -> Result<Json<Collection>> {
+    format: Format,
+) -> Result<Response> {
     let mut collection = state
         .drivers
         .collections
@@ -75,24 +105,49 @@ async fn read(
     ]);
 
     #[cfg(not(feature = "stac"))]
-    collection.links.insert_or_update(&[Link::new(
-        &url.join(&format!("{}/items", collection.id))?,
-        ITEMS,
-    )
-    .mediatype(GEO_JSON)]);
+    collection.links.insert_or_update(&[
+        Link::new(&url.join(&format!("{}/items", collection.id))?, ITEMS).mediatype(GEO_JSON),
+        Link::new(
+            &url.join(&format!("{}/queryables", collection.id))?,
+            QUERYABLES,
+        )
+        .mediatype(SCHEMA_JSON),
+    ]);
 
     #[cfg(feature = "stac")]
     if collection.r#type == "Collection" {
-        collection.links.insert_or_update(&[Link::new(
-            &url.join(&format!("{}/items", collection.id))?,
-            ITEMS,
-        )
-        .mediatype(GEO_JSON)]);
+        collection.links.insert_or_update(&[
+            Link::new(&url.join(&format!("{}/items", collection.id))?, ITEMS).mediatype(GEO_JSON),
+            Link::new(
+                &url.join(&format!("{}/queryables", collection.id))?,
+                QUERYABLES,
+            )
+            .mediatype(SCHEMA_JSON),
+        ]);
     }
 
     collection.links.resolve_relative_links();
+    html::alternate(&mut collection.links, &url, format, JSON);
 
-    Ok(Json(collection))
+    #[cfg(feature = "edr")]
+    if let Some(position) = collection
+        .data_queries
+        .as_mut()
+        .and_then(|queries| queries.position.as_mut())
+    {
+        if position.variables.crs_details.is_empty() {
+            position.variables.crs_details = crs::definitions(&state, &collection.crs)
+                .await?
+                .iter()
+                .map(Into::into)
+                .collect();
+        }
+    }
+
+    Ok(match format {
+        Format::Html => html::collection(&collection).into_response(),
+        _ => Json(collection).into_response(),
+    })
 }
 
 /// Update collection metadata
@@ -101,6 +156,13 @@ async fn update(
     Path(collection_id): Path<String>,
     Json(mut collection): Json<Collection>,
 ) -> Result<StatusCode> {
+    state
+        .drivers
+        .collections
+        .read_collection(&collection_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
     collection.id = collection_id;
 
     state
@@ -112,6 +174,75 @@ async fn update(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Update collection metadata with a JSON Merge Patch, a changed id renames
+/// the collection
+async fn patch(
+    State(state): State<AppState>,
+    Path(collection_id): Path<String>,
+    RemoteUrl(url): RemoteUrl,
+    Json(merge_patch): Json<Value>,
+) -> Result<(StatusCode, HeaderMap)> {
+    if !merge_patch.is_object() {
+        return Err(Error::Exception(
+            StatusCode::BAD_REQUEST,
+            "Expected a JSON object as merge patch".to_string(),
+        ));
+    }
+
+    let collection = state
+        .drivers
+        .collections
+        .read_collection(&collection_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let mut value = serde_json::to_value(collection).context("Unable to serialize collection")?;
+    patch::merge(&mut value, merge_patch);
+
+    let collection: Collection = serde_json::from_value(value).map_err(|e| {
+        Error::Exception(
+            StatusCode::BAD_REQUEST,
+            format!("Patched collection is not valid: {e}"),
+        )
+    })?;
+
+    let mut headers = HeaderMap::new();
+
+    if collection.id != collection_id {
+        is_valid_id(&collection.id)?;
+
+        if state
+            .drivers
+            .collections
+            .read_collection(&collection.id)
+            .await?
+            .is_some()
+        {
+            return Err(Error::Exception(
+                StatusCode::CONFLICT,
+                format!("Collection with id `{}` already exists.", collection.id),
+            ));
+        }
+
+        state
+            .drivers
+            .collections
+            .rename_collection(&collection_id, &collection)
+            .await?;
+
+        let location = url.join(&collection.id)?;
+        headers.insert(LOCATION, location.as_str().parse().unwrap());
+    } else {
+        state
+            .drivers
+            .collections
+            .update_collection(&collection)
+            .await?;
+    }
+
+    Ok((StatusCode::NO_CONTENT, headers))
+}
+
 /// Delete collection metadata
 async fn remove(
     Path(collection_id): Path<String>,
@@ -126,11 +257,37 @@ async fn remove(
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[derive(Deserialize, Debug)]
+struct ExtentParams {
+    /// Also compute the summaries of the item properties
+    #[serde(default)]
+    summaries: bool,
+}
+
+/// Recompute the extent of a collection from its items
+async fn update_extent(
+    Path(collection_id): Path<String>,
+    State(state): State<AppState>,
+    Qs(params): Qs<ExtentParams>,
+) -> Result<Json<Collection>> {
+    let collection = state
+        .drivers
+        .collections
+        .update_extent(&collection_id, params.summaries)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    Ok(Json(collection))
+}
+
 async fn collections(
-    Qs(query): Qs<Query>,
+    Qs(mut query): Qs<Query>,
     RemoteUrl(url): RemoteUrl,
     State(state): State<AppState>,
-) -> Result<Json<Collections>> {
+    format: Format,
+) -> Result<Response> {
+    query.limit = Some(query.limit.unwrap_or(100).min(10000));
+
     let mut collections = state.drivers.collections.list_collections(&query).await?;
 
     for collection in collections.collections.iter_mut() {
@@ -152,9 +309,42 @@ async fn collections(
         Link::new(&url.join(".")?, ROOT).mediatype(JSON),
     ];
 
-    collections.crs = vec![Crs::default(), Crs::from_epsg(3857)];
+    // offset pagination
+    let limit = query.limit.unwrap_or_default();
+    let offset = query.offset.unwrap_or(0);
+    if offset > 0 {
+        let query = Query {
+            offset: Some(offset.saturating_sub(limit)),
+            ..query.to_owned()
+        };
+        collections.links.push(link(&url, &query, PREV));
+    }
+    if collections
+        .number_matched
+        .map_or(false, |matched| ((offset + limit) as u64) < matched)
+    {
+        let query = Query {
+            offset: Some(offset + limit),
+            ..query.to_owned()
+        };
+        collections.links.push(link(&url, &query, NEXT));
+    }
+
+    html::alternate(&mut collections.links, &url, format, JSON);
+
+    // coordinate reference systems of all collections
+    collections.crs = state.drivers.crs.list_crs().await?;
+
+    Ok(match format {
+        Format::Html => html::collections(&collections).into_response(),
+        _ => Json(collections).into_response(),
+    })
+}
 
-    Ok(Json(collections))
+fn link(url: &Url, query: &Query, rel: &str) -> Link {
+    let mut url = url.to_owned();
+    url.set_query(serde_qs::to_string(query).ok().as_deref());
+    Link::new(&url, rel).mediatype(JSON)
 }
 
 pub(crate) fn router(state: &AppState) -> Router<AppState> {
@@ -171,6 +361,7 @@ pub(crate) fn router(state: &AppState) -> Router<AppState> {
         .route("/collections", get(collections).post(create))
         .route(
             "/collections/:collection_id",
-            get(read).put(update).delete(remove),
+            get(read).put(update).patch(patch).delete(remove),
         )
+        .route("/collections/:collection_id/extent", post(update_extent))
 }
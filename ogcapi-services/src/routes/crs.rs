@@ -0,0 +1,67 @@
+use axum::{
+    extract::{Path, State},
+    routing::get,
+    Json, Router,
+};
+
+use ogcapi_types::common::{
+    link_rel::{ROOT, SELF},
+    media_type::JSON,
+    Crs, CrsDefinition, CrsDefinitions, Link,
+};
+
+use crate::{extractors::RemoteUrl, AppState, Error, Result};
+
+/// Definitions of the crs of all collections
+async fn crs(
+    RemoteUrl(url): RemoteUrl,
+    State(state): State<AppState>,
+) -> Result<Json<CrsDefinitions>> {
+    let crs = state.drivers.crs.list_crs().await?;
+
+    Ok(Json(CrsDefinitions {
+        links: vec![
+            Link::new(&url, SELF).mediatype(JSON),
+            Link::new(&url.join(".")?, ROOT).mediatype(JSON),
+        ],
+        crs: definitions(&state, &crs).await?,
+    }))
+}
+
+/// Definitions of the crs of a collection
+async fn collection_crs(
+    Path(collection_id): Path<String>,
+    RemoteUrl(url): RemoteUrl,
+    State(state): State<AppState>,
+) -> Result<Json<CrsDefinitions>> {
+    let collection = state
+        .drivers
+        .collections
+        .read_collection(&collection_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    Ok(Json(CrsDefinitions {
+        links: vec![
+            Link::new(&url, SELF).mediatype(JSON),
+            Link::new(&url.join("../..")?, ROOT).mediatype(JSON),
+        ],
+        crs: definitions(&state, &collection.crs).await?,
+    }))
+}
+
+/// Definitions of the crs, with only the URI of those unknown to the backend
+pub(crate) async fn definitions(state: &AppState, crs: &[Crs]) -> Result<Vec<CrsDefinition>> {
+    let mut definitions = Vec::new();
+    for crs in crs {
+        let definition = state.drivers.crs.crs_definition(crs).await?;
+        definitions.push(definition.unwrap_or_else(|| CrsDefinition::new(crs.to_owned())));
+    }
+    Ok(definitions)
+}
+
+pub(crate) fn router(state: &AppState) -> Router<AppState> {
+    Router::with_state(state.clone())
+        .route("/crs", get(crs))
+        .route("/collections/:collection_id/crs", get(collection_crs))
+}
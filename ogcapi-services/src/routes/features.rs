@@ -1,51 +1,195 @@
 use anyhow::Context;
 use axum::{
-    extract::{Path, State},
+    body::{self, Body, Bytes},
+    extract::{BodyStream, Path, State},
     http::{
-        header::{CONTENT_TYPE, LOCATION},
-        HeaderMap, StatusCode,
+        header::{CONTENT_LENGTH, CONTENT_TYPE, ETAG, IF_MATCH, LINK, LOCATION},
+        HeaderMap, HeaderValue, StatusCode,
     },
+    response::{IntoResponse, Response},
     routing::get,
     Json, Router,
 };
+use futures_util::StreamExt;
+use hyper::body::Sender;
+use serde::Deserialize;
+use serde_json::Value;
+use url::Url;
 
 use ogcapi_types::{
     common::{
-        link_rel::{COLLECTION, NEXT, PREV, ROOT, SELF},
-        media_type::{GEO_JSON, JSON},
-        Collection, Crs, Link, Linked,
+        link_rel::{ALTERNATE, COLLECTION, NEXT, PREV, ROOT, SELF},
+        media_type::{CSV, FLAT_GEOBUF, GEO_JSON, GEO_JSON_SEQ, JSON, JSON_FG, SCHEMA_JSON},
+        Authority, Collection, Crs, Cursor, Link, Linked, Links, TemporalProperties,
+    },
+    features::{
+        BulkError, BulkMode, BulkReport, Feature, FeatureCollection, Query, Queryables, SortBy,
     },
-    features::{Feature, FeatureCollection, Query},
 };
 
+use ogcapi_drivers::{FeatureStream, Outcome};
+
 use crate::{
-    extractors::{Qs, RemoteUrl},
-    AppState, Error, Result,
+    encoding::{csv, fgb, jsonfg, seq},
+    extractors::{FeatureFormat, Format, ItemsFormat, Qs, RemoteUrl},
+    html, patch, AppState, Error, Result,
 };
 
-const CONFORMANCE: [&str; 4] = [
+const CONFORMANCE: [&str; 19] = [
     "http://www.opengis.net/spec/ogcapi-features-1/1.0/conf/core",
     "http://www.opengis.net/spec/ogcapi-features-1/1.0/conf/oas30",
+    "http://www.opengis.net/spec/ogcapi-features-1/1.0/conf/html",
     "http://www.opengis.net/spec/ogcapi-features-1/1.0/conf/geojson",
     "http://www.opengis.net/spec/ogcapi-features-2/1.0/conf/crs",
+    "http://www.opengis.net/spec/ogcapi-features-3/1.0/conf/filter",
+    "http://www.opengis.net/spec/ogcapi-features-3/1.0/conf/features-filter",
+    "http://www.opengis.net/spec/ogcapi-features-3/1.0/conf/queryables",
+    "http://www.opengis.net/spec/ogcapi-features-3/1.0/conf/queryables-query-parameters",
+    "http://www.opengis.net/spec/cql2/1.0/conf/cql2-text",
+    "http://www.opengis.net/spec/cql2/1.0/conf/cql2-json",
+    "http://www.opengis.net/spec/cql2/1.0/conf/basic-cql2",
+    "http://www.opengis.net/spec/cql2/1.0/conf/advanced-comparison-operators",
+    "http://www.opengis.net/spec/cql2/1.0/conf/basic-spatial-operators",
+    "http://www.opengis.net/spec/cql2/1.0/conf/spatial-operators",
+    "http://www.opengis.net/spec/cql2/1.0/conf/temporal-operators",
+    "http://www.opengis.net/spec/ogcapi-features-4/1.0/conf/create-replace-delete",
+    "http://www.opengis.net/spec/ogcapi-features-4/1.0/conf/update",
+    "http://www.opengis.net/spec/ogcapi-features-4/1.0/conf/features",
 ];
 
+/// Media type of the CSV encoding
+const CSV_UTF8: &str = "text/csv; charset=utf-8";
+
+/// Size of the chunks of streamed responses, in bytes
+const CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Deserialize, Debug)]
+struct CreateParams {
+    /// How a bulk upload treats existing features
+    #[serde(default)]
+    mode: BulkMode,
+}
+
+/// Create a single feature, or many at once from a `FeatureCollection` or a
+/// GeoJSON text sequence.
 async fn create(
     State(state): State<AppState>,
     RemoteUrl(url): RemoteUrl,
     Path(collection_id): Path<String>,
-    Json(mut feature): Json<Feature>,
-) -> Result<(StatusCode, HeaderMap)> {
-    feature.collection = Some(collection_id);
+    Qs(params): Qs<CreateParams>,
+    headers: HeaderMap,
+    mut body: BodyStream,
+) -> Result<Response> {
+    let too_large = || {
+        Error::Exception(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("Request body exceeds {} bytes", state.upload_limit),
+        )
+    };
+    let content_length = headers
+        .get(CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok());
+    if content_length.map_or(false, |length| length > state.upload_limit) {
+        return Err(too_large());
+    }
+
+    let is_seq = headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map_or(false, |v| v.starts_with(GEO_JSON_SEQ));
 
-    let id = state.drivers.features.create_feature(&feature).await?;
+    // records of a sequence are parsed as they arrive, other bodies as a whole
+    let mut records = seq::Records::default();
+    let mut values: Vec<Result<Feature, serde_json::Error>> = Vec::new();
+    let mut buffer = Vec::new();
+    let mut size = 0;
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk
+            .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, format!("Invalid body: {e}")))?;
+        size += chunk.len();
+        if size > state.upload_limit {
+            return Err(too_large());
+        }
 
-    let location = url.join(&format!("items/{}", id))?;
+        if is_seq {
+            let features = records.push(&chunk);
+            values.extend(features.iter().map(|r| serde_json::from_slice(r)));
+        } else {
+            buffer.extend_from_slice(&chunk);
+        }
+    }
 
-    let mut headers = HeaderMap::new();
-    headers.insert(LOCATION, location.as_str().parse().unwrap());
+    if is_seq {
+        let features = records.finish();
+        values.extend(features.iter().map(|r| serde_json::from_slice(r)));
+    } else {
+        let mut value: Value = serde_json::from_slice(&buffer)
+            .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, format!("Invalid JSON: {e}")))?;
+
+        if value["type"] != "FeatureCollection" {
+            let mut feature: Feature = serde_json::from_value(value).map_err(|e| {
+                Error::Exception(StatusCode::BAD_REQUEST, format!("Invalid feature: {e}"))
+            })?;
+            feature.collection = Some(collection_id.to_owned());
+
+            let id = state.drivers.features.create_feature(&feature).await?;
+            written(&state, &collection_id).await?;
+
+            let location = url.join(&format!("items/{}", id))?;
+
+            let mut headers = HeaderMap::new();
+            headers.insert(LOCATION, location.as_str().parse().unwrap());
+
+            return Ok((StatusCode::CREATED, headers).into_response());
+        }
+
+        match value["features"].take() {
+            Value::Array(features) => {
+                values.extend(features.into_iter().map(serde_json::from_value))
+            }
+            _ => {
+                return Err(Error::Exception(
+                    StatusCode::BAD_REQUEST,
+                    "Expected array of `features`".to_string(),
+                ))
+            }
+        }
+    }
+
+    let mut features = Vec::new();
+    let mut errors = Vec::new();
+    for (index, value) in values.into_iter().enumerate() {
+        match value {
+            Ok(mut feature) => {
+                feature.collection = Some(collection_id.to_owned());
+                features.push(feature);
+            }
+            Err(e) => errors.push(BulkError::new(index, None, e)),
+        }
+    }
+
+    let report = if errors.is_empty() {
+        state
+            .drivers
+            .features
+            .create_features(&collection_id, &features, params.mode)
+            .await?
+    } else {
+        BulkReport {
+            ids: Vec::new(),
+            errors,
+        }
+    };
 
-    Ok((StatusCode::CREATED, headers))
+    let status = if report.is_ok() {
+        written(&state, &collection_id).await?;
+        StatusCode::CREATED
+    } else {
+        StatusCode::BAD_REQUEST
+    };
+
+    Ok((status, Json(report)).into_response())
 }
 
 async fn read(
@@ -53,7 +197,8 @@ async fn read(
     RemoteUrl(url): RemoteUrl,
     Path((collection_id, id)): Path<(String, String)>,
     Qs(query): Qs<Query>,
-) -> Result<(HeaderMap, Json<Feature>)> {
+    FeatureFormat(format): FeatureFormat,
+) -> Result<Response> {
     let collection = state
         .drivers
         .collections
@@ -61,20 +206,34 @@ async fn read(
         .await?
         .ok_or(Error::NotFound)?;
     is_supported_crs(&collection, &query.crs).await?;
+    is_geojson_crs(format, &query.crs)?;
+    is_valid_simplification(&query)?;
 
-    let mut feature = state
+    let (mut feature, etag) = state
         .drivers
         .features
-        .read_feature(&collection_id, &id, &query.crs)
+        .read_feature_with_etag(&collection_id, &id, &query)
         .await?
         .ok_or(Error::NotFound)?;
 
     feature.links.insert_or_update(&[
-        Link::new(&url, SELF).mediatype(GEO_JSON),
+        Link::new(&url, SELF).mediatype(media_type(format)),
         Link::new(&url.join("../../..")?, ROOT).mediatype(JSON),
         Link::new(&url.join(&format!("../../{}", collection_id))?, COLLECTION).mediatype(JSON),
     ]);
     feature.links.resolve_relative_links();
+    html::alternate(&mut feature.links, &url, format, GEO_JSON);
+    alternates(
+        &mut feature.links,
+        &url,
+        format,
+        &query.crs,
+        &[
+            (Format::Json, GEO_JSON, "GeoJSON"),
+            (Format::JsonFg, JSON_FG, "JSON-FG"),
+            (Format::Csv, CSV, "CSV"),
+        ],
+    );
 
     let mut headers = HeaderMap::new();
     headers.insert(
@@ -85,43 +244,273 @@ async fn read(
             .parse()
             .context("Unable to parse `Content-Crs` header value")?,
     );
-    headers.insert(CONTENT_TYPE, GEO_JSON.parse().unwrap());
+    headers.insert(
+        ETAG,
+        etag_header(&representation_etag(&etag, format, &query))?,
+    );
 
-    Ok((headers, Json(feature)))
+    Ok(match format {
+        Format::Html => {
+            let map = query.crs == Crs::default();
+            (headers, html::feature(&feature, map)).into_response()
+        }
+        Format::Csv => {
+            headers.insert(CONTENT_TYPE, CSV_UTF8.parse().unwrap());
+            let columns = csv::feature_columns(&feature);
+            let geometry = query.csv_geometry.unwrap_or_default();
+            let csv = csv::encode(&[feature], &columns, Some(geometry), &query.crs);
+            (headers, csv).into_response()
+        }
+        Format::JsonFg => {
+            headers.insert(CONTENT_TYPE, JSON_FG.parse().unwrap());
+            let temporal = collection.temporal_properties.unwrap_or_default();
+            jsonfg::document(&mut feature, &collection_id, &query.crs, &temporal);
+            (headers, Json(feature)).into_response()
+        }
+        _ => {
+            headers.insert(CONTENT_TYPE, GEO_JSON.parse().unwrap());
+            (headers, Json(feature)).into_response()
+        }
+    })
 }
 
 async fn update(
     State(state): State<AppState>,
     Path((collection_id, id)): Path<(String, String)>,
+    headers: HeaderMap,
     Json(mut feature): Json<Feature>,
-) -> Result<StatusCode> {
+) -> Result<(StatusCode, HeaderMap)> {
+    let if_match = precondition(&state, &collection_id, &id, &headers).await?;
+
     feature.id = Some(id);
     feature.collection = Some(collection_id);
 
-    state.drivers.features.update_feature(&feature).await?;
+    let outcome = state
+        .drivers
+        .features
+        .update_feature(&feature, if_match.as_deref(), false)
+        .await?;
+    check(outcome)?;
+    written(&state, feature.collection.as_ref().unwrap()).await?;
 
-    Ok(StatusCode::NO_CONTENT)
+    modified(
+        &state,
+        feature.collection.as_ref().unwrap(),
+        feature.id.as_ref().unwrap(),
+    )
+    .await
+}
+
+async fn patch(
+    State(state): State<AppState>,
+    Path((collection_id, id)): Path<(String, String)>,
+    headers: HeaderMap,
+    Json(merge_patch): Json<Value>,
+) -> Result<(StatusCode, HeaderMap)> {
+    if !merge_patch.is_object() {
+        return Err(Error::Exception(
+            StatusCode::BAD_REQUEST,
+            "Expected a JSON object as merge patch".to_string(),
+        ));
+    }
+
+    let if_match = precondition(&state, &collection_id, &id, &headers).await?;
+
+    let (feature, etag) = state
+        .drivers
+        .features
+        .read_feature_with_etag(&collection_id, &id, &Query::default())
+        .await?
+        .ok_or(Error::NotFound)?;
+    if if_match.map_or(false, |if_match| if_match != etag) {
+        check(Outcome::PreconditionFailed)?;
+    }
+
+    let keeps_geometry = merge_patch.get("geometry").is_none();
+
+    let mut value = serde_json::to_value(feature).context("Unable to serialize feature")?;
+    patch::merge(&mut value, merge_patch);
+
+    let mut feature: Feature = serde_json::from_value(value).map_err(|e| {
+        Error::Exception(
+            StatusCode::BAD_REQUEST,
+            format!("Patched feature is not valid: {e}"),
+        )
+    })?;
+    feature.id = Some(id);
+    feature.collection = Some(collection_id);
+
+    // the feature must not have changed since it was read, and its stored
+    // geometry is left untouched instead of round tripping it
+    let outcome = state
+        .drivers
+        .features
+        .update_feature(&feature, Some(&etag), keeps_geometry)
+        .await?;
+    check(outcome)?;
+    written(&state, feature.collection.as_ref().unwrap()).await?;
+
+    modified(
+        &state,
+        feature.collection.as_ref().unwrap(),
+        feature.id.as_ref().unwrap(),
+    )
+    .await
 }
 
 async fn remove(
     State(state): State<AppState>,
     Path((collection_id, id)): Path<(String, String)>,
+    headers: HeaderMap,
 ) -> Result<StatusCode> {
-    state
+    let if_match = precondition(&state, &collection_id, &id, &headers).await?;
+
+    let outcome = state
         .drivers
         .features
-        .delete_feature(&collection_id, &id)
+        .delete_feature(&collection_id, &id, if_match.as_deref())
         .await?;
+    check(outcome)?;
+    written(&state, &collection_id).await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Keep the extent and summaries of the collection current, if configured
+async fn written(state: &AppState, collection_id: &str) -> Result<()> {
+    if state.update_extent {
+        state
+            .drivers
+            .collections
+            .update_extent(collection_id, cfg!(feature = "stac"))
+            .await?;
+    }
+    Ok(())
+}
+
+/// Evaluate the `If-Match` header against the current entity tag of the feature.
+///
+/// Returns the matched entity tag, which is to be passed on to the driver so the
+/// modification only applies if the feature did not change in the meantime.
+async fn precondition(
+    state: &AppState,
+    collection_id: &str,
+    id: &str,
+    headers: &HeaderMap,
+) -> Result<Option<String>> {
+    let if_match = match headers.get(IF_MATCH) {
+        Some(value) => value.to_str().map_err(|_| {
+            Error::Exception(
+                StatusCode::BAD_REQUEST,
+                "Invalid `If-Match` header".to_string(),
+            )
+        })?,
+        None => return Ok(None),
+    };
+
+    let etag = state
+        .drivers
+        .features
+        .feature_etag(collection_id, id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    // strong comparison, weak tags never match. The tag of any representation
+    // of the current feature does, as the modification applies to the feature.
+    let matches = if_match.split(',').any(|tag| {
+        let tag = tag.trim();
+        tag == "*"
+            || tag
+                .strip_prefix('"')
+                .and_then(|tag| tag.strip_suffix('"'))
+                .and_then(|tag| tag.strip_prefix(etag.as_str()))
+                .map_or(false, |rest| rest.is_empty() || rest.starts_with('-'))
+    });
+
+    if matches {
+        Ok(Some(etag))
+    } else {
+        Err(Error::Exception(
+            StatusCode::PRECONDITION_FAILED,
+            format!("Feature `{id}` does not match `If-Match` header"),
+        ))
+    }
+}
+
+fn check(outcome: Outcome) -> Result<()> {
+    match outcome {
+        Outcome::Applied => Ok(()),
+        Outcome::NotFound => Err(Error::NotFound),
+        Outcome::PreconditionFailed => Err(Error::Exception(
+            StatusCode::PRECONDITION_FAILED,
+            "Feature has been modified concurrently".to_string(),
+        )),
+    }
+}
+
+/// Response to a successful modification with the new entity tag
+async fn modified(
+    state: &AppState,
+    collection_id: &str,
+    id: &str,
+) -> Result<(StatusCode, HeaderMap)> {
+    let mut headers = HeaderMap::new();
+    if let Some(etag) = state
+        .drivers
+        .features
+        .feature_etag(collection_id, id)
+        .await?
+    {
+        headers.insert(ETAG, etag_header(&etag)?);
+    }
+
+    Ok((StatusCode::NO_CONTENT, headers))
+}
+
+/// Entity tag of a representation of a feature. The encoding, crs and other
+/// parameters shaping the representation are appended as hash to the tag of the
+/// stored feature, unless they are the ones of the default GeoJSON.
+fn representation_etag(etag: &str, format: Format, query: &Query) -> String {
+    let describe = |format: Format, query: &Query| {
+        format!(
+            "{format:?} {} {:?} {:?} {:?} {:?} {:?} {:?}",
+            query.crs,
+            query.properties,
+            query.skip_geometry,
+            query.max_allowable_offset,
+            query.zoom_level,
+            query.coordinate_precision,
+            query.csv_geometry
+        )
+    };
+
+    let representation = describe(format, query);
+    if representation == describe(Format::Json, &Query::default()) {
+        return etag.to_owned();
+    }
+
+    // FNV-1a, which is stable across builds unlike the default hasher
+    let hash = representation
+        .bytes()
+        .fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+    format!("{etag}-{hash:016x}")
+}
+
+fn etag_header(etag: &str) -> Result<HeaderValue> {
+    Ok(format!("\"{etag}\"")
+        .parse()
+        .context("Unable to parse `ETag` header value")?)
+}
+
 async fn items(
     State(state): State<AppState>,
-    RemoteUrl(mut url): RemoteUrl,
+    RemoteUrl(url): RemoteUrl,
     Path(collection_id): Path<String>,
     Qs(mut query): Qs<Query>,
-) -> Result<(HeaderMap, Json<FeatureCollection>)> {
+    ItemsFormat(format): ItemsFormat,
+) -> Result<Response> {
     tracing::debug!("{:#?}", query);
 
     // Limit
@@ -140,63 +529,499 @@ async fn items(
         .await?
         .ok_or(Error::NotFound)?;
     is_supported_crs(&collection, &query.crs).await?;
+    is_geojson_crs(format, &query.crs)?;
+    is_valid_simplification(&query)?;
 
-    // TODO: validate additional parameters
+    // Bbox
+    if let Some(bbox) = query.bbox.as_ref() {
+        is_supported_crs(&collection, &query.bbox_crs).await?;
+
+        // the horizontal axes may wrap around the antimeridian, depending on the crs
+        if bbox.lower().get(2) > bbox.upper().get(2) {
+            return Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                "query parameter `bbox` not valid".to_string(),
+            ));
+        }
+    }
+
+    // Sortby
+    let sortby = query.sortby.clone().unwrap_or_default();
+    let keyset = !sortby.iter().any(|key| key.is_geometry_derived());
+
+    // Token, holds the sort key values and the id of the last feature
+    if let Some(token) = query.token.as_ref() {
+        if !keyset
+            || token.0.len() != sortby.len() + 1
+            || !token.0.last().map_or(false, Value::is_string)
+        {
+            return Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                "query parameter `token` not valid".to_string(),
+            ));
+        }
+    }
 
+    // Filter
+    if let Some(filter_crs) = query.filter_crs.as_ref() {
+        is_supported_crs(&collection, filter_crs).await?;
+    }
+    let filter = query
+        .filter_expr()
+        .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, format!("Invalid filter: {e}")))?;
+
+    // Queryables, also define the columns of FlatGeobuf and CSV
+    let queryables = if filter.is_some()
+        || !query.additional_parameters.is_empty()
+        || !sortby.is_empty()
+        || matches!(format, Format::FlatGeobuf | Format::Csv)
+    {
+        state.drivers.features.queryables(&collection_id).await?
+    } else {
+        None
+    };
+
+    if let Some(queryables) = queryables.as_ref() {
+        if let Some(property) = filter.as_ref().and_then(|f| queryables.unknown_property(f)) {
+            return Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                format!("Unknown property `{property}` in filter"),
+            ));
+        }
+
+        if let Some(key) = query
+            .additional_parameters
+            .keys()
+            .find(|k| !queryables.contains(k))
+        {
+            return Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                format!("Unknown query parameter `{key}`"),
+            ));
+        }
+
+        // values must conform to the type of the property
+        if let Some(key) = query.additional_parameters.iter().find_map(|(key, value)| {
+            let queryable = queryables.properties.get(key)?;
+            value
+                .split(',')
+                .any(|v| queryable.value(v).is_none())
+                .then_some(key)
+        }) {
+            return Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                format!("query parameter `{key}` not valid"),
+            ));
+        }
+
+        if let Some(key) = sortby
+            .iter()
+            .find(|k| !k.is_geometry_derived() && !queryables.contains(&k.field))
+        {
+            return Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                format!("Unknown sort key `{}`", key.field),
+            ));
+        }
+    }
+
+    // Properties, sort keys are required for the continuation token
+    let selection = query.properties.clone();
+    if let Some(properties) = query.properties.as_mut() {
+        for key in sortby.iter() {
+            if !properties.contains(&key.field) {
+                properties.push(key.field.to_owned());
+            }
+        }
+    }
+
+    let mut links = vec![
+        Link::new(&url, SELF).mediatype(media_type(format)),
+        Link::new(&url.join("../..")?, ROOT).mediatype(JSON),
+        Link::new(&url.join(".")?, COLLECTION).mediatype(JSON),
+    ];
+    html::alternate(&mut links, &url, format, GEO_JSON);
+    alternates(
+        &mut links,
+        &url,
+        format,
+        &query.crs,
+        &[
+            (Format::Json, GEO_JSON, "GeoJSON"),
+            (Format::JsonFg, JSON_FG, "JSON-FG"),
+            (Format::GeoJsonSeq, GEO_JSON_SEQ, "GeoJSON text sequence"),
+            (Format::FlatGeobuf, FLAT_GEOBUF, "FlatGeobuf"),
+            (Format::Csv, CSV, "CSV"),
+        ],
+    );
+
+    let mut headers = HeaderMap::new();
+    headers.insert("Content-Crs", query.crs.to_string().parse().unwrap());
+
+    let items = Items {
+        url,
+        collection_id: collection.id.to_owned(),
+        temporal: collection
+            .temporal_properties
+            .to_owned()
+            .unwrap_or_default(),
+        selection: selection.to_owned(),
+        pagination: Pagination {
+            query: Query {
+                properties: selection,
+                ..query.to_owned()
+            },
+            sortby,
+            keyset,
+        },
+    };
+    links.extend(items.previous());
+
+    // GeoJSON and JSON-FG are written while the features are read
+    if matches!(format, Format::Json | Format::JsonFg | Format::GeoJsonSeq) {
+        let stream = state
+            .drivers
+            .features
+            .stream_items(&collection_id, &query)
+            .await?;
+
+        headers.insert(CONTENT_TYPE, media_type(format).parse().unwrap());
+
+        let (mut sender, chunks) = Body::channel();
+        tokio::spawn(async move {
+            if let Err(e) = items.write(stream, links, format, &mut sender).await {
+                tracing::error!("Failed to stream items: {e}");
+                // the client must not mistake the truncated body for a complete one
+                sender.abort();
+            }
+        });
+
+        return Ok((headers, body::boxed(chunks)).into_response());
+    }
+
+    // the other formats need the whole page up front, and the link to the next
+    // page, which depends on the last feature, goes into the `Link` header
     let mut fc = state
         .drivers
         .features
         .list_items(&collection_id, &query)
         .await?;
 
-    fc.links.insert_or_update(&[
-        Link::new(&url, SELF).mediatype(GEO_JSON),
-        Link::new(&url.join("../..")?, ROOT).mediatype(JSON),
-        Link::new(&url.join(".")?, COLLECTION).mediatype(JSON),
-    ]);
+    // the spatial index of FlatGeobuf requires all matching features
+    let complete = query.offset.unwrap_or(0) == 0
+        && query.token.is_none()
+        && match fc.number_matched {
+            Some(number_matched) => number_matched == fc.features.len() as u64,
+            None => fc.features.len() < query.limit.unwrap_or_default(),
+        };
 
-    // pagination
-    if let Some(limit) = query.limit {
-        if query.offset.is_none() {
-            query.offset = Some(0);
-        }
+    let cursor = fc.features.last().map(|f| items.pagination.cursor(f));
+    fc.links.extend(links);
+    fc.links.extend(items.pagination.next(
+        &items.url,
+        fc.features.len(),
+        fc.number_matched,
+        cursor,
+    ));
+
+    for feature in fc.features.iter_mut() {
+        items.decorate(feature)?;
+    }
 
-        if let Some(offset) = query.offset {
-            if offset != 0 && offset >= limit {
-                query.offset = Some(offset - limit);
-                url.set_query(serde_qs::to_string(&query).ok().as_deref());
-                let previous = Link::new(&url, PREV).mediatype(GEO_JSON);
-                fc.links.insert_or_update(&[previous]);
+    Ok(match format {
+        Format::Html => {
+            let map = query.crs == Crs::default();
+            (headers, html::items(&collection, &fc, map)).into_response()
+        }
+        Format::FlatGeobuf => {
+            headers.insert(CONTENT_TYPE, FLAT_GEOBUF.parse().unwrap());
+            // the file itself has no links
+            if let Some(next) = fc.links.iter().find(|l| l.rel == NEXT) {
+                headers.insert(LINK, link_header(next)?);
             }
 
-            if let Some(number_matched) = fc.number_matched {
-                if number_matched > (offset + limit) as u64 {
-                    query.offset = Some(offset + limit);
-                    url.set_query(serde_qs::to_string(&query).ok().as_deref());
-                    let next = Link::new(&url, NEXT).mediatype(GEO_JSON);
-                    fc.links.insert_or_update(&[next]);
-                }
+            let columns = fgb::columns(&queryables.unwrap_or_default(), items.selection.as_deref());
+            let layer = fgb::Layer {
+                name: &collection.id,
+                title: collection.title.as_deref(),
+                crs: &query.crs,
+                columns: &columns,
+            };
+            (headers, fgb::encode(&layer, &fc.features, complete)).into_response()
+        }
+        Format::Csv => {
+            headers.insert(CONTENT_TYPE, CSV_UTF8.parse().unwrap());
+            if let Some(next) = fc.links.iter().find(|l| l.rel == NEXT) {
+                headers.insert(LINK, link_header(next)?);
             }
+
+            let columns = csv::columns(
+                &queryables.unwrap_or_default(),
+                items.selection.as_deref(),
+                &fc.features,
+            );
+            let geometry = (!query.skip_geometry.unwrap_or_default())
+                .then(|| query.csv_geometry.unwrap_or_default());
+            let csv = csv::encode(&fc.features, &columns, geometry, &query.crs);
+            (headers, csv).into_response()
+        }
+        Format::Json | Format::JsonFg | Format::GeoJsonSeq => {
+            unreachable!("GeoJSON is streamed")
         }
+    })
+}
+
+/// Page of items to respond with
+struct Items {
+    url: Url,
+    collection_id: String,
+    temporal: TemporalProperties,
+    /// Properties to include, the features may have additional ones for sorting
+    selection: Option<Vec<String>>,
+    pagination: Pagination,
+}
+
+impl Items {
+    fn previous(&self) -> Option<Link> {
+        self.pagination.previous(&self.url)
     }
 
-    for feature in fc.features.iter_mut() {
+    /// Restrict the properties to the selection and add links to the feature
+    fn decorate(&self, feature: &mut Feature) -> Result<()> {
+        if let (Some(selection), Some(properties)) = (&self.selection, feature.properties.as_mut())
+        {
+            properties.retain(|key, _| selection.contains(key));
+        }
+
         feature.links.insert_or_update(&[
             Link::new(
-                &url.join(&format!("items/{}", feature.id.as_ref().unwrap()))?,
+                &self
+                    .url
+                    .join(&format!("items/{}", feature.id.as_ref().unwrap()))?,
                 SELF,
             )
             .mediatype(GEO_JSON),
-            Link::new(&url.join("../..")?, ROOT).mediatype(JSON),
-            Link::new(&url.join(&format!("../{}", collection.id))?, COLLECTION).mediatype(JSON),
-        ])
+            Link::new(&self.url.join("../..")?, ROOT).mediatype(JSON),
+            Link::new(
+                &self.url.join(&format!("../{}", self.collection_id))?,
+                COLLECTION,
+            )
+            .mediatype(JSON),
+        ]);
+
+        Ok(())
+    }
+
+    /// Write the features to the body in chunks, either as GeoJSON text sequence
+    /// or as (JSON-FG) feature collection with the remaining members after the
+    /// features.
+    async fn write(
+        &self,
+        mut stream: FeatureStream,
+        links: Vec<Link>,
+        format: Format,
+        sender: &mut Sender,
+    ) -> anyhow::Result<()> {
+        let seq = format == Format::GeoJsonSeq;
+        let fg = format == Format::JsonFg;
+        let crs = &self.pagination.query.crs;
+
+        let mut buffer = Vec::with_capacity(CHUNK_SIZE);
+        if !seq {
+            buffer.extend(br#"{"type":"FeatureCollection","features":["#);
+        }
+
+        let mut number_returned = 0;
+        let mut cursor = None;
+        while let Some(feature) = stream.features.next().await {
+            let mut feature = feature?;
+
+            cursor = Some(self.pagination.cursor(&feature));
+            self.decorate(&mut feature)?;
+            if fg {
+                jsonfg::feature(&mut feature, crs, &self.temporal);
+            }
+
+            if seq {
+                buffer.push(0x1e);
+            } else if number_returned > 0 {
+                buffer.push(b',');
+            }
+            serde_json::to_writer(&mut buffer, &feature)?;
+            if seq {
+                buffer.push(b'\n');
+            }
+            number_returned += 1;
+
+            if buffer.len() >= CHUNK_SIZE {
+                sender
+                    .send_data(Bytes::from(std::mem::take(&mut buffer)))
+                    .await?;
+            }
+        }
+
+        // the link to the next page depends on the last feature, a sequence has
+        // no place for links
+        if !seq {
+            let mut fc = FeatureCollection::new(Vec::new());
+            fc.links = links;
+            fc.links.extend(self.pagination.next(
+                &self.url,
+                number_returned,
+                stream.number_matched,
+                cursor,
+            ));
+            fc.number_matched = stream.number_matched;
+            fc.number_returned = Some(number_returned as u64);
+            if fg {
+                jsonfg::collection(&mut fc, &self.collection_id, crs);
+            }
+
+            let mut members = serde_json::to_value(fc)?;
+            let members = members.as_object_mut().expect("object");
+            members.remove("type");
+            members.remove("features");
+
+            buffer.push(b']');
+            for (key, value) in members.iter() {
+                buffer.push(b',');
+                serde_json::to_writer(&mut buffer, key)?;
+                buffer.push(b':');
+                serde_json::to_writer(&mut buffer, value)?;
+            }
+            buffer.push(b'}');
+        }
+
+        sender.send_data(Bytes::from(buffer)).await?;
+
+        Ok(())
     }
+}
+
+/// Links to the adjacent pages
+struct Pagination {
+    query: Query,
+    sortby: Vec<SortBy>,
+    /// Keyset pagination with a continuation token instead of an offset
+    keyset: bool,
+}
+
+impl Pagination {
+    fn previous(&self, url: &Url) -> Option<Link> {
+        let limit = self.query.limit?;
+        let offset = self.query.offset.unwrap_or(0);
+
+        if self.query.token.is_some() || offset == 0 || offset < limit {
+            return None;
+        }
+
+        let query = Query {
+            offset: Some(offset - limit),
+            ..self.query.to_owned()
+        };
+        Some(link(url, &query, PREV))
+    }
+
+    /// Continue after the last feature of a full page
+    fn next(
+        &self,
+        url: &Url,
+        number_returned: usize,
+        number_matched: Option<u64>,
+        cursor: Option<Cursor>,
+    ) -> Option<Link> {
+        let limit = self.query.limit?;
+        let offset = self.query.offset.unwrap_or(0);
+
+        if number_returned != limit || number_matched == Some((offset + limit) as u64) {
+            return None;
+        }
+
+        let mut query = self.query.to_owned();
+        if self.keyset {
+            query.offset = None;
+            query.token = Some(cursor?);
+        } else {
+            // geometry derived sort keys are not part of the feature
+            query.offset = Some(offset + limit);
+        }
+        Some(link(url, &query, NEXT))
+    }
+
+    /// Sort key values and id of a feature, before the properties are restricted
+    /// to the selection
+    fn cursor(&self, feature: &Feature) -> Cursor {
+        let mut values: Vec<Value> = self.sortby.iter().map(|k| k.value(feature)).collect();
+        values.push(Value::from(feature.id.to_owned()));
+        Cursor(values)
+    }
+}
+
+/// Media type of the GeoJSON based encodings
+fn media_type(format: Format) -> &'static str {
+    match format {
+        Format::JsonFg => JSON_FG,
+        Format::GeoJsonSeq => GEO_JSON_SEQ,
+        _ => GEO_JSON,
+    }
+}
+
+/// Add links to the resource in the other encodings that support the crs, unless
+/// already present
+fn alternates(
+    links: &mut Links,
+    url: &Url,
+    format: Format,
+    crs: &Crs,
+    encodings: &[(Format, &str, &str)],
+) {
+    for (alternate, media_type, title) in encodings {
+        if *alternate != format
+            && is_geojson_crs(*alternate, crs).is_ok()
+            && !links
+                .iter()
+                .any(|l| l.rel == ALTERNATE && l.r#type.as_deref() == Some(media_type))
+        {
+            links.push(
+                Link::new(html::with_format(url, *alternate), ALTERNATE)
+                    .mediatype(media_type)
+                    .title(format!("This document as {title}")),
+            );
+        }
+    }
+}
+
+/// Web link for encodings without links of their own
+fn link_header(link: &Link) -> Result<HeaderValue> {
+    Ok(format!("<{}>; rel=\"{}\"", link.href, link.rel)
+        .parse()
+        .context("Unable to parse `Link` header value")?)
+}
+
+fn link(url: &Url, query: &Query, rel: &str) -> Link {
+    let mut url = url.to_owned();
+    url.set_query(serde_qs::to_string(query).ok().as_deref());
+    Link::new(&url, rel).mediatype(GEO_JSON)
+}
+
+async fn queryables(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    Path(collection_id): Path<String>,
+) -> Result<(HeaderMap, Json<Queryables>)> {
+    let mut queryables = state
+        .drivers
+        .features
+        .queryables(&collection_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    queryables.id = Some(url.to_string());
 
     let mut headers = HeaderMap::new();
-    headers.insert("Content-Crs", query.crs.to_string().parse().unwrap());
-    headers.insert(CONTENT_TYPE, GEO_JSON.parse().unwrap());
+    headers.insert(CONTENT_TYPE, SCHEMA_JSON.parse().unwrap());
 
-    Ok((headers, Json(fc)))
+    Ok((headers, Json(queryables)))
 }
 
 async fn is_supported_crs(collection: &Collection, crs: &Crs) -> Result<(), Error> {
@@ -210,13 +1035,48 @@ async fn is_supported_crs(collection: &Collection, crs: &Crs) -> Result<(), Erro
     }
 }
 
+/// Check that GeoJSON is requested in WGS 84 longitude/latitude, the only crs
+/// allowed by RFC 7946. Other crs are served as JSON-FG, FlatGeobuf or CSV.
+fn is_geojson_crs(format: Format, crs: &Crs) -> Result<(), Error> {
+    let wgs84 = crs.authority == Authority::OGC && matches!(crs.code.as_str(), "CRS84" | "CRS84h");
+    if matches!(format, Format::Json | Format::GeoJsonSeq) && !wgs84 {
+        return Err(Error::Exception(
+            StatusCode::BAD_REQUEST,
+            format!("GeoJSON does not support CRS `{crs}`, use `f=jsonfg` instead"),
+        ));
+    }
+    Ok(())
+}
+
+/// Check the simplification and precision of the geometries
+fn is_valid_simplification(query: &Query) -> Result<(), Error> {
+    let parameter = if query
+        .max_allowable_offset
+        .map_or(false, |offset| !offset.is_finite() || offset < 0.0)
+    {
+        "max-allowable-offset"
+    } else if query.zoom_level.map_or(false, |zoom| zoom > 30) {
+        "zoom-level"
+    } else if query.coordinate_precision.map_or(false, |p| p > 15) {
+        "coordinate-precision"
+    } else {
+        return Ok(());
+    };
+
+    Err(Error::Exception(
+        StatusCode::BAD_REQUEST,
+        format!("query parameter `{parameter}` not valid"),
+    ))
+}
+
 pub(crate) fn router(state: &AppState) -> Router<AppState> {
     state.conformance.write().unwrap().extend(&CONFORMANCE);
 
     Router::with_state(state.clone())
         .route("/collections/:collection_id/items", get(items).post(create))
+        .route("/collections/:collection_id/queryables", get(queryables))
         .route(
             "/collections/:collection_id/items/:id",
-            get(read).put(update).delete(remove),
+            get(read).put(update).patch(patch).delete(remove),
         )
 }
@@ -1,5 +1,6 @@
 pub(crate) mod api;
 pub(crate) mod collections;
+pub(crate) mod crs;
 #[cfg(feature = "edr")]
 pub(crate) mod edr;
 #[cfg(feature = "features")]
@@ -13,22 +14,30 @@ pub(crate) mod styles;
 #[cfg(feature = "tiles")]
 pub(crate) mod tiles;
 
-use axum::{extract::State, Json};
+use axum::{
+    extract::State,
+    response::{IntoResponse, Response},
+    Json,
+};
 
 #[cfg(feature = "stac")]
 use ogcapi_types::common::link_rel::SEARCH;
 use ogcapi_types::common::{
     link_rel::{CONFORMANCE, ROOT, SELF, SERVICE_DESC, SERVICE_DOC},
     media_type::{HTML, JSON, OPEN_API_JSON},
-    Conformance, LandingPage, Link, Linked,
+    Conformance, Link, Linked,
 };
 
-use crate::{extractors::RemoteUrl, AppState, Result};
+use crate::{
+    extractors::{Format, RemoteUrl},
+    html, AppState, Result,
+};
 
 pub(crate) async fn root(
     RemoteUrl(url): RemoteUrl,
     State(state): State<AppState>,
-) -> Result<Json<LandingPage>> {
+    format: Format,
+) -> Result<Response> {
     let mut root = state.root.read().unwrap().to_owned();
 
     root.links.insert_or_update(&[
@@ -52,13 +61,22 @@ pub(crate) async fn root(
             .mediatype(JSON),
     ]);
     root.links.resolve_relative_links();
+    html::alternate(&mut root.links, &url, format, JSON);
 
     #[cfg(feature = "stac")]
     let root = root.conforms_to(&state.conformance.read().unwrap().conforms_to[..]);
 
-    Ok(Json(root))
+    Ok(match format {
+        Format::Html => html::landing_page(&root).into_response(),
+        _ => Json(root).into_response(),
+    })
 }
 
-pub(crate) async fn conformance(State(state): State<AppState>) -> Json<Conformance> {
-    Json(state.conformance.read().unwrap().to_owned())
+pub(crate) async fn conformance(State(state): State<AppState>, format: Format) -> Response {
+    let conformance: Conformance = state.conformance.read().unwrap().to_owned();
+
+    match format {
+        Format::Html => html::conformance(&conformance).into_response(),
+        _ => Json(conformance).into_response(),
+    }
 }
@@ -9,11 +9,12 @@ use ogcapi_types::{
     common::{
         link_rel::{COLLECTION, NEXT, PREV, ROOT, SELF},
         media_type::{GEO_JSON, JSON},
-        Bbox, Link, Linked,
+        Bbox, Cursor, Link, Linked,
     },
     features::FeatureCollection,
     stac::{SearchBody, SearchParams},
 };
+use serde_json::Value;
 use url::Url;
 
 use crate::{
@@ -61,7 +62,8 @@ pub(crate) async fn search(
     if let Some(bbox) = params.bbox.as_ref() {
         match bbox {
             Bbox::Bbox2D(bbox) => {
-                if bbox[0] > bbox[2] || bbox[1] > bbox[3] {
+                // a west-most edge greater than the east-most edge crosses the antimeridian
+                if bbox[1] > bbox[3] {
                     return Err(Error::Exception(
                         StatusCode::BAD_REQUEST,
                         "query parameter `bbox` not valid".to_string(),
@@ -69,7 +71,7 @@ pub(crate) async fn search(
                 }
             }
             Bbox::Bbox3D(bbox) => {
-                if bbox[0] > bbox[3] || bbox[1] > bbox[4] || bbox[2] > bbox[5] {
+                if bbox[1] > bbox[4] || bbox[2] > bbox[5] {
                     return Err(Error::Exception(
                         StatusCode::BAD_REQUEST,
                         "query parameter `bbox` not valid".to_string(),
@@ -79,6 +81,36 @@ pub(crate) async fn search(
         }
     }
 
+    // Sortby, properties may be prefixed according to the STAC sort extension
+    let mut sortby = params.sortby.clone().unwrap_or_default();
+    for key in sortby.iter_mut() {
+        if let Some(field) = key.field.strip_prefix("properties.") {
+            key.field = field.to_string();
+        }
+    }
+    let keyset = !sortby.iter().any(|key| key.is_geometry_derived());
+    if !sortby.is_empty() {
+        params.sortby = Some(sortby.to_owned());
+    }
+
+    // Token, holds the sort key values, collection and id of the last feature
+    if let Some(token) = params.token.as_ref() {
+        if !keyset
+            || token.0.len() != sortby.len() + 2
+            || !token.0.iter().rev().take(2).all(Value::is_string)
+        {
+            return Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                "query parameter `token` not valid".to_string(),
+            ));
+        }
+    }
+
+    // Filter
+    params
+        .filter_expr()
+        .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, format!("Invalid filter: {e}")))?;
+
     let mut fc = state.db.search(&params).await?;
 
     fc.links.insert_or_update(&[
@@ -88,25 +120,31 @@ pub(crate) async fn search(
 
     // pagination
     if let Some(limit) = params.limit {
-        if params.offset.is_none() {
-            params.offset = Some(0);
-        }
+        let offset = params.offset.unwrap_or(0);
 
-        if let Some(offset) = params.offset {
-            if offset != 0 && offset >= limit {
-                params.offset = Some(offset - limit);
-                url.set_query(serde_qs::to_string(&params).ok().as_deref());
-                let previous = Link::new(&url, PREV).mediatype(GEO_JSON);
-                fc.links.insert_or_update(&[previous]);
-            }
+        if params.token.is_none() && offset != 0 && offset >= limit {
+            params.offset = Some(offset - limit);
+            url.set_query(serde_qs::to_string(&params).ok().as_deref());
+            let previous = Link::new(&url, PREV).mediatype(GEO_JSON);
+            fc.links.insert_or_update(&[previous]);
+        }
 
-            if let Some(number_matched) = fc.number_matched {
-                if number_matched > (offset + limit) as u64 {
+        // continue after the last feature of a full page
+        if fc.features.len() as u64 == limit && fc.number_matched != Some(offset + limit) {
+            if let Some(feature) = fc.features.last() {
+                if keyset {
+                    let mut values: Vec<Value> = sortby.iter().map(|k| k.value(feature)).collect();
+                    values.push(Value::from(feature.collection.to_owned()));
+                    values.push(Value::from(feature.id.to_owned()));
+                    params.offset = None;
+                    params.token = Some(Cursor(values));
+                } else {
+                    // geometry derived sort keys are not part of the feature
                     params.offset = Some(offset + limit);
-                    url.set_query(serde_qs::to_string(&params).ok().as_deref());
-                    let next = Link::new(&url, NEXT).mediatype(GEO_JSON);
-                    fc.links.insert_or_update(&[next]);
                 }
+                url.set_query(serde_qs::to_string(&params).ok().as_deref());
+                let next = Link::new(&url, NEXT).mediatype(GEO_JSON);
+                fc.links.insert_or_update(&[next]);
             }
         }
     }
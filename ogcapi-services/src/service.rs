@@ -57,6 +57,7 @@ impl Service {
             .route("/conformance", get(routes::conformance));
 
         let router = router.merge(routes::collections::router(&state));
+        let router = router.merge(routes::crs::router(&state));
 
         #[cfg(feature = "stac")]
         let router = router.route(
@@ -11,12 +11,12 @@ use ogcapi_drivers::StyleTransactions;
 #[cfg(feature = "tiles")]
 use ogcapi_drivers::TileTransactions;
 
-use ogcapi_drivers::{postgres::Db, CollectionTransactions};
+use ogcapi_drivers::{postgres::Db, CollectionTransactions, CrsCatalogue};
 use ogcapi_types::common::{Conformance, LandingPage};
 
 #[cfg(feature = "processes")]
 use crate::Processor;
-use crate::{openapi::OPENAPI, Config, ConfigParser, OpenAPI};
+use crate::{config::UPLOAD_LIMIT, openapi::OPENAPI, Config, ConfigParser, OpenAPI};
 
 /// Application state
 #[derive(Clone)]
@@ -26,6 +26,10 @@ pub struct AppState {
     pub openapi: OpenAPI,
     pub drivers: Arc<Drivers>,
     pub db: Db,
+    /// Keep the extent of the collections current on every write
+    pub update_extent: bool,
+    /// Maximum size of a request body to create features, in bytes
+    pub upload_limit: usize,
     #[cfg(feature = "stac")]
     pub s3: ogcapi_drivers::s3::S3,
     #[cfg(feature = "processes")]
@@ -35,6 +39,7 @@ pub struct AppState {
 // TODO: Introduce service trait
 pub struct Drivers {
     pub collections: Box<dyn CollectionTransactions>,
+    pub crs: Box<dyn CrsCatalogue>,
     #[cfg(feature = "features")]
     pub features: Box<dyn FeatureTransactions>,
     #[cfg(feature = "edr")]
@@ -62,7 +67,10 @@ impl AppState {
 
         let db = Db::setup(&config.database_url).await.unwrap();
 
-        AppState::new_with(db, openapi).await
+        AppState::new_with(db, openapi)
+            .await
+            .update_extent(config.update_extent)
+            .upload_limit(config.upload_limit)
     }
 
     pub async fn new_with(db: Db, openapi: OpenAPI) -> Self {
@@ -73,6 +81,7 @@ impl AppState {
         conformace.extend(&[
             "https://api.stacspec.org/v1.0.0-rc.1/core",
             "https://api.stacspec.org/v1.0.0-rc.1/item-search",
+            "https://api.stacspec.org/v1.0.0-rc.1/item-search#sort",
             "https://api.stacspec.org/v1.0.0-rc.1/collections",
             "https://api.stacspec.org/v1.0.0-rc.1/ogcapi-features",
             "https://api.stacspec.org/v1.0.0-rc.1/browseable",
@@ -81,6 +90,7 @@ impl AppState {
         // drivers
         let drivers = Drivers {
             collections: Box::new(db.clone()),
+            crs: Box::new(db.clone()),
             #[cfg(feature = "features")]
             features: Box::new(db.clone()),
             #[cfg(feature = "edr")]
@@ -99,6 +109,8 @@ impl AppState {
             openapi,
             drivers: Arc::new(drivers),
             db,
+            update_extent: false,
+            upload_limit: UPLOAD_LIMIT,
             #[cfg(feature = "stac")]
             s3: ogcapi_drivers::s3::S3::new().await,
             #[cfg(feature = "processes")]
@@ -116,6 +128,16 @@ impl AppState {
         self
     }
 
+    pub fn update_extent(mut self, update_extent: bool) -> Self {
+        self.update_extent = update_extent;
+        self
+    }
+
+    pub fn upload_limit(mut self, upload_limit: usize) -> Self {
+        self.upload_limit = upload_limit;
+        self
+    }
+
     #[cfg(feature = "stac")]
     pub async fn s3_client(mut self, client: ogcapi_drivers::s3::S3) -> Self {
         self.s3 = client;
@@ -5,8 +5,12 @@ use hyper::Body;
 use serde_json::json;
 
 use ogcapi_types::{
-    common::{media_type::JSON, Collection, Crs},
-    features::Feature,
+    common::{
+        link_rel::NEXT,
+        media_type::{GEO_JSON, GEO_JSON_SEQ, JSON, SCHEMA_JSON},
+        Collection, Collections, Crs, CrsDefinitions, Cursor,
+    },
+    features::{BulkReport, Feature, FeatureCollection, Queryables},
 };
 
 #[tokio::test]
@@ -89,10 +93,147 @@ async fn minimal_feature_crud() -> anyhow::Result<()> {
         .await?;
 
     assert_eq!(200, res.status());
+    let etag = res.headers().get("ETag").unwrap().to_owned();
     let body = hyper::body::to_bytes(res.into_body()).await?;
     let _feature: Feature = serde_json::from_slice(&body)?;
     // println!("{:#?}", feature);
 
+    // other representations have other entity tags
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::GET)
+                .uri(format!(
+                    "http://{}/collections/{}/items/{}?f=jsonfg&skipGeometry=true",
+                    addr, collection.id, &id
+                ))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(200, res.status());
+    let representation_etag = res.headers().get("ETag").unwrap().to_owned();
+    assert_ne!(etag, representation_etag);
+
+    // patch feature with stale entity tag
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::PATCH)
+                .uri(format!(
+                    "http://{}/collections/{}/items/{}",
+                    addr, collection.id, &id
+                ))
+                .header("Content-Type", "application/merge-patch+json")
+                .header("If-Match", "\"stale\"")
+                .body(Body::from(r#"{"properties":{"name":"Bern"}}"#))?,
+        )
+        .await?;
+
+    assert_eq!(412, res.status());
+
+    // weak entity tags never match
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::PATCH)
+                .uri(format!(
+                    "http://{}/collections/{}/items/{}",
+                    addr, collection.id, &id
+                ))
+                .header("Content-Type", "application/merge-patch+json")
+                .header("If-Match", format!("W/{}", etag.to_str()?))
+                .body(Body::from(r#"{"properties":{"name":"Bern"}}"#))?,
+        )
+        .await?;
+
+    assert_eq!(412, res.status());
+
+    // patch feature
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::PATCH)
+                .uri(format!(
+                    "http://{}/collections/{}/items/{}",
+                    addr, collection.id, &id
+                ))
+                .header("Content-Type", "application/merge-patch+json")
+                .header("If-Match", etag.to_owned())
+                .body(Body::from(r#"{"properties":{"name":"Bern"}}"#))?,
+        )
+        .await?;
+
+    assert_eq!(204, res.status());
+    assert_ne!(Some(&etag), res.headers().get("ETag"));
+
+    // the tag of the other representation refers to the same, now stale, state
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::PATCH)
+                .uri(format!(
+                    "http://{}/collections/{}/items/{}",
+                    addr, collection.id, &id
+                ))
+                .header("Content-Type", "application/merge-patch+json")
+                .header("If-Match", representation_etag)
+                .body(Body::from(r#"{"properties":{"name":"Bern"}}"#))?,
+        )
+        .await?;
+
+    assert_eq!(412, res.status());
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::GET)
+                .uri(format!(
+                    "http://{}/collections/{}/items/{}",
+                    addr, collection.id, &id
+                ))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    let feature: Feature = serde_json::from_slice(&body)?;
+    assert_eq!(feature.properties.unwrap()["name"], "Bern");
+    assert!(feature.geometry.is_some());
+
+    // replace feature, without geometry
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::PUT)
+                .uri(format!(
+                    "http://{}/collections/{}/items/{}",
+                    addr, collection.id, &id
+                ))
+                .header("Content-Type", GEO_JSON)
+                .body(Body::from(
+                    r#"{"type":"Feature","properties":{"name":"Bern"},"geometry":null}"#,
+                ))?,
+        )
+        .await?;
+
+    assert_eq!(204, res.status());
+
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!(
+                    "http://{}/collections/{}/items/{}",
+                    addr, collection.id, &id
+                ))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    let feature: Feature = serde_json::from_slice(&body)?;
+    assert!(feature.geometry.is_none());
+
     // delete feature
     let res = client
         .request(
@@ -111,6 +252,174 @@ async fn minimal_feature_crud() -> anyhow::Result<()> {
 
     assert_eq!(204, res.status());
 
+    // delete missing feature
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::DELETE)
+                .uri(format!(
+                    "http://{}/collections/{}/items/{}",
+                    addr, collection.id, &id
+                ))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(404, res.status());
+
+    // delete collection
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::DELETE)
+                .uri(format!("http://{}/collections/{}", addr, &collection.id).as_str())
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(204, res.status());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn bulk_feature_upload() -> anyhow::Result<()> {
+    // setup app
+    let (addr, _) = setup::spawn_app().await?;
+    let client = hyper::Client::new();
+
+    let collection = Collection {
+        id: "bulk".to_string(),
+        links: vec![],
+        crs: vec![Crs::default()],
+        ..Default::default()
+    };
+
+    // create collection
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/collections", addr))
+                .header("Content-Type", JSON)
+                .body(Body::from(serde_json::to_string(&collection)?))?,
+        )
+        .await?;
+
+    assert_eq!(201, res.status());
+
+    let fc = json!({
+        "type": "FeatureCollection",
+        "features": (0..100).map(|i| json!({
+            "type": "Feature",
+            "id": format!("f{i}"),
+            "properties": { "i": i },
+            "geometry": { "type": "Point", "coordinates": [7.0, 46.0] }
+        })).collect::<Vec<_>>()
+    });
+
+    // create features
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!(
+                    "http://{}/collections/{}/items",
+                    addr, collection.id
+                ))
+                .header("Content-Type", GEO_JSON)
+                .body(Body::from(fc.to_string()))?,
+        )
+        .await?;
+
+    assert_eq!(201, res.status());
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    let report: BulkReport = serde_json::from_slice(&body)?;
+    assert_eq!(report.ids.len(), 100);
+    assert_eq!(report.ids[0], "f0");
+
+    // insert existing features as sequence, one of them invalid
+    let seq = [
+        r#"{"type":"Feature","id":"f0","properties":{},"geometry":{"type":"Point","coordinates":[7,46]}}"#,
+        r#"{"type":"Feature","id":"f100","properties":{},"geometry":{"type":"Point"}}"#,
+    ]
+    .map(|record| format!("\x1e{record}\n"))
+    .concat();
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!(
+                    "http://{}/collections/{}/items",
+                    addr, collection.id
+                ))
+                .header("Content-Type", GEO_JSON_SEQ)
+                .body(Body::from(seq.to_owned()))?,
+        )
+        .await?;
+
+    assert_eq!(400, res.status());
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    let report: BulkReport = serde_json::from_slice(&body)?;
+    assert!(report.ids.is_empty());
+    assert_eq!(report.errors[0].index, 1);
+
+    // upsert existing features
+    let seq = seq.replace(
+        r#"{"type":"Point"}"#,
+        r#"{"type":"Point","coordinates":[7,46]}"#,
+    );
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!(
+                    "http://{}/collections/{}/items?mode=upsert",
+                    addr, collection.id
+                ))
+                .header("Content-Type", GEO_JSON_SEQ)
+                .body(Body::from(seq))?,
+        )
+        .await?;
+
+    assert_eq!(201, res.status());
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    let report: BulkReport = serde_json::from_slice(&body)?;
+    assert_eq!(report.ids, ["f0", "f100"]);
+
+    // records spanning several lines
+    let seq = ["f1", "f101"]
+        .map(|id| {
+            let feature = json!({
+                "type": "Feature",
+                "id": id,
+                "properties": {},
+                "geometry": { "type": "Point", "coordinates": [7, 46] }
+            });
+            format!("\x1e{}\n", serde_json::to_string_pretty(&feature).unwrap())
+        })
+        .concat();
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!(
+                    "http://{}/collections/{}/items?mode=upsert",
+                    addr, collection.id
+                ))
+                .header("Content-Type", GEO_JSON_SEQ)
+                .body(Body::from(seq))?,
+        )
+        .await?;
+
+    assert_eq!(201, res.status());
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    let report: BulkReport = serde_json::from_slice(&body)?;
+    assert_eq!(report.ids, ["f1", "f101"]);
+
     // delete collection
     let res = client
         .request(
@@ -125,3 +434,757 @@ async fn minimal_feature_crud() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn collection_patch() -> anyhow::Result<()> {
+    let (addr, _) = setup::spawn_app().await?;
+    let client = hyper::Client::new();
+
+    let collection = Collection {
+        id: "patch.me".to_string(),
+        crs: vec![Crs::default(), Crs::from_epsg(2056)],
+        ..Default::default()
+    };
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/collections", addr))
+                .header("Content-Type", JSON)
+                .body(Body::from(serde_json::to_string(&collection)?))?,
+        )
+        .await?;
+
+    assert_eq!(201, res.status());
+
+    let feature = json!({
+        "id": "bern",
+        "type": "Feature",
+        "geometry": { "type": "Point", "coordinates": [7.44, 46.95] }
+    });
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/collections/patch.me/items", addr))
+                .header("Content-Type", GEO_JSON)
+                .body(Body::from(feature.to_string()))?,
+        )
+        .await?;
+
+    assert_eq!(201, res.status());
+
+    // invalid patch
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::PATCH)
+                .uri(format!("http://{}/collections/patch.me", addr))
+                .header("Content-Type", "application/merge-patch+json")
+                .body(Body::from(r#"{"crs":"EPSG:2056"}"#))?,
+        )
+        .await?;
+
+    assert_eq!(400, res.status());
+
+    // missing collection
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::PUT)
+                .uri(format!("http://{}/collections/missing", addr))
+                .header("Content-Type", JSON)
+                .body(Body::from(serde_json::to_string(&collection)?))?,
+        )
+        .await?;
+
+    assert_eq!(404, res.status());
+
+    // new title, storage crs and id
+    let merge_patch = json!({
+        "id": "patched",
+        "title": "Patched",
+        "storageCrs": Crs::from_epsg(2056).to_string()
+    });
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::PATCH)
+                .uri(format!("http://{}/collections/patch.me", addr))
+                .header("Content-Type", "application/merge-patch+json")
+                .body(Body::from(merge_patch.to_string()))?,
+        )
+        .await?;
+
+    assert_eq!(204, res.status());
+    assert!(res.headers()["Location"]
+        .to_str()?
+        .ends_with("/collections/patched"));
+
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/collections/patch.me", addr))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(404, res.status());
+
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/collections/patched", addr))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(200, res.status());
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    let collection: Collection = serde_json::from_slice(&body)?;
+    assert_eq!(collection.title.as_deref(), Some("Patched"));
+    assert_eq!(collection.storage_crs, Some(Crs::from_epsg(2056)));
+
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/collections/patched/items/bern", addr))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(200, res.status());
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    let feature: Feature = serde_json::from_slice(&body)?;
+    assert_eq!(feature.collection.as_deref(), Some("patched"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn unknown_crs() -> anyhow::Result<()> {
+    let (addr, _) = setup::spawn_app().await?;
+    let client = hyper::Client::new();
+
+    let crs27: Crs = "http://www.opengis.net/def/crs/OGC/1.3/CRS27"
+        .parse()
+        .unwrap();
+
+    // unknown storage crs
+    let collection = Collection {
+        id: "crs27".to_string(),
+        crs: vec![Crs::default(), crs27.to_owned()],
+        storage_crs: Some(crs27.to_owned()),
+        ..Default::default()
+    };
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/collections", addr))
+                .header("Content-Type", JSON)
+                .body(Body::from(serde_json::to_string(&collection)?))?,
+        )
+        .await?;
+
+    assert_eq!(400, res.status());
+
+    // unknown output crs listed by the collection
+    let collection = Collection {
+        storage_crs: None,
+        ..collection
+    };
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/collections", addr))
+                .header("Content-Type", JSON)
+                .body(Body::from(serde_json::to_string(&collection)?))?,
+        )
+        .await?;
+
+    assert_eq!(201, res.status());
+
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!(
+                    "http://{}/collections/crs27/items?crs={}",
+                    addr, crs27
+                ))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(400, res.status());
+
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!(
+                    "http://{}/collections?bbox=7,46,8,47&bbox-crs={}",
+                    addr, crs27
+                ))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(400, res.status());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn crs_definitions() -> anyhow::Result<()> {
+    let (addr, _) = setup::spawn_app().await?;
+    let client = hyper::Client::new();
+
+    let crs27: Crs = "http://www.opengis.net/def/crs/OGC/1.3/CRS27"
+        .parse()
+        .unwrap();
+
+    let collection = Collection {
+        id: "swiss".to_string(),
+        crs: vec![Crs::default(), Crs::from_epsg(2056), crs27.to_owned()],
+        ..Default::default()
+    };
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/collections", addr))
+                .header("Content-Type", JSON)
+                .body(Body::from(serde_json::to_string(&collection)?))?,
+        )
+        .await?;
+
+    assert_eq!(201, res.status());
+
+    // definitions of the crs of the collection
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/collections/swiss/crs", addr))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(200, res.status());
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    let definitions: CrsDefinitions = serde_json::from_slice(&body)?;
+
+    let crs84 = &definitions.crs[0];
+    assert_eq!(crs84.name.as_deref(), Some("WGS 84 (CRS84)"));
+    assert!(crs84.area_of_use.is_some());
+    let projjson = crs84.projjson.as_ref().unwrap();
+    assert_eq!(projjson["type"], "GeographicCRS");
+    assert_eq!(
+        projjson["coordinate_system"]["axis"][0]["abbreviation"],
+        "Lon"
+    );
+
+    let lv95 = &definitions.crs[1];
+    assert_eq!(lv95.crs, Crs::from_epsg(2056));
+    assert!(lv95.name.as_deref().unwrap().contains("LV95"));
+    assert!(lv95.wkt.is_some());
+    let projjson = lv95.projjson.as_ref().unwrap();
+    assert_eq!(projjson["type"], "ProjectedCRS");
+    assert_eq!(projjson["id"]["code"], 2056);
+    assert_eq!(
+        projjson["conversion"]["method"]["name"],
+        "Hotine Oblique Mercator (variant B)"
+    );
+
+    // unknown to the database
+    assert_eq!(definitions.crs[2].crs, crs27);
+    assert!(definitions.crs[2].wkt.is_none());
+    assert!(definitions.crs[2].projjson.is_none());
+
+    // union of the crs of all collections
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/crs", addr))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(200, res.status());
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    let definitions: CrsDefinitions = serde_json::from_slice(&body)?;
+    assert_eq!(definitions.crs.len(), 3);
+
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/collections?limit=0", addr))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    let collections: Collections = serde_json::from_slice(&body)?;
+    assert!(collections.collections.is_empty());
+    assert!(collections.crs.contains(&Crs::from_epsg(2056)));
+
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/collections/missing/crs", addr))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(404, res.status());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn invalid_filter() -> anyhow::Result<()> {
+    let (addr, _) = setup::spawn_app().await?;
+    let client = hyper::Client::new();
+
+    let collection = Collection {
+        id: "filtered".to_string(),
+        ..Default::default()
+    };
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/collections", addr))
+                .header("Content-Type", JSON)
+                .body(Body::from(serde_json::to_string(&collection)?))?,
+        )
+        .await?;
+
+    assert_eq!(201, res.status());
+
+    let feature = json!({
+        "type": "Feature",
+        "properties": { "name": "Bern", "founded": "1191-01-01T00:00:00Z" },
+        "geometry": { "type": "Point", "coordinates": [7.44, 46.95] }
+    });
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/collections/filtered/items", addr))
+                .header("Content-Type", GEO_JSON)
+                .body(Body::from(serde_json::to_string(&feature)?))?,
+        )
+        .await?;
+
+    assert_eq!(201, res.status());
+
+    // arguments of the wrong type are errors of the client
+    for filter in [
+        "S_INTERSECTS(name,'x')",
+        "T_AFTER(founded,5)",
+        "T_AFTER(founded,'2022-13-45')",
+    ] {
+        let res = client
+            .request(
+                Request::builder()
+                    .uri(format!(
+                        "http://{}/collections/filtered/items?filter={}",
+                        addr,
+                        filter.replace('\'', "%27")
+                    ))
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(400, res.status(), "{filter}");
+    }
+
+    // so are tokens which do not fit the sort keys
+    let token = Cursor(vec![json!(1), json!("a")]);
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!(
+                    "http://{}/collections/filtered/items?sortby=id&token={}",
+                    addr, token
+                ))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(400, res.status());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn queryables() -> anyhow::Result<()> {
+    let (addr, _) = setup::spawn_app().await?;
+    let client = hyper::Client::new();
+
+    let collection = Collection {
+        id: "cities".to_string(),
+        ..Default::default()
+    };
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/collections", addr))
+                .header("Content-Type", JSON)
+                .body(Body::from(serde_json::to_string(&collection)?))?,
+        )
+        .await?;
+
+    assert_eq!(201, res.status());
+
+    let feature = json!({
+        "type": "Feature",
+        "properties": { "name": "Bern", "population": 3 },
+        "geometry": { "type": "Point", "coordinates": [7.44, 46.95] }
+    });
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/collections/cities/items", addr))
+                .header("Content-Type", GEO_JSON)
+                .body(Body::from(serde_json::to_string(&feature)?))?,
+        )
+        .await?;
+
+    assert_eq!(201, res.status());
+
+    // schema inferred from the features
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/collections/cities/queryables", addr))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(200, res.status());
+    assert_eq!(res.headers()["Content-Type"], SCHEMA_JSON);
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    let queryables: Queryables = serde_json::from_slice(&body)?;
+    assert_eq!(
+        queryables.id.as_deref(),
+        Some(format!("http://{}/collections/cities/queryables", addr).as_str())
+    );
+    assert_eq!(
+        queryables.properties["name"].r#type.as_deref(),
+        Some("string")
+    );
+    assert_eq!(
+        queryables.properties["population"].r#type.as_deref(),
+        Some("integer")
+    );
+
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/collections/missing/queryables", addr))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(404, res.status());
+
+    // misspelled properties and values of the wrong type do not match everything
+    for query in ["filter=nmae%3D%27Bern%27", "nmae=Bern", "population=many"] {
+        let res = client
+            .request(
+                Request::builder()
+                    .uri(format!(
+                        "http://{}/collections/cities/items?{}",
+                        addr, query
+                    ))
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(400, res.status(), "{query}");
+    }
+
+    for (query, matched) in [("name=Bern", 1), ("name=Zug", 0), ("population=3", 1)] {
+        let res = client
+            .request(
+                Request::builder()
+                    .uri(format!(
+                        "http://{}/collections/cities/items?{}",
+                        addr, query
+                    ))
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(200, res.status(), "{query}");
+        let body = hyper::body::to_bytes(res.into_body()).await?;
+        let fc: FeatureCollection = serde_json::from_slice(&body)?;
+        assert_eq!(fc.features.len(), matched, "{query}");
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn sorting() -> anyhow::Result<()> {
+    let (addr, _) = setup::spawn_app().await?;
+    let client = hyper::Client::new();
+
+    let collection = Collection {
+        id: "sorted".to_string(),
+        ..Default::default()
+    };
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/collections", addr))
+                .header("Content-Type", JSON)
+                .body(Body::from(serde_json::to_string(&collection)?))?,
+        )
+        .await?;
+
+    assert_eq!(201, res.status());
+
+    let features = [
+        (
+            "small",
+            json!({ "population": 3, "datetime": "2022-01-01T00:00:00Z" }),
+        ),
+        (
+            "large",
+            json!({ "population": 10, "datetime": "2021-01-01T00:00:00Z" }),
+        ),
+        ("medium", json!({ "datetime": "2023-01-01T00:00:00Z" })),
+    ]
+    .map(|(id, properties)| {
+        json!({
+            "type": "Feature",
+            "id": id,
+            "properties": properties,
+            "geometry": { "type": "Point", "coordinates": [7.44, 46.95] }
+        })
+    });
+    let fc = json!({ "type": "FeatureCollection", "features": features });
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/collections/sorted/items", addr))
+                .header("Content-Type", GEO_JSON)
+                .body(Body::from(fc.to_string()))?,
+        )
+        .await?;
+
+    assert_eq!(201, res.status());
+
+    // one feature per page, following the links to the next page
+    let mut ids = Vec::new();
+    let mut next = Some(format!(
+        "http://{}/collections/sorted/items?sortby=-population&limit=1",
+        addr
+    ));
+    while let Some(uri) = next {
+        let res = client
+            .request(Request::builder().uri(uri).body(Body::empty())?)
+            .await?;
+
+        assert_eq!(200, res.status());
+        let body = hyper::body::to_bytes(res.into_body()).await?;
+        let fc: FeatureCollection = serde_json::from_slice(&body)?;
+        ids.extend(fc.features.into_iter().filter_map(|f| f.id));
+        next = fc.links.into_iter().find(|l| l.rel == NEXT).map(|l| l.href);
+    }
+    assert_eq!(ids, ["large", "small", "medium"]);
+
+    // keys which are not queryable
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!(
+                    "http://{}/collections/sorted/items?sortby=-populaton",
+                    addr
+                ))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(400, res.status());
+
+    // STAC item search, keys may be prefixed with `properties.`
+    #[cfg(feature = "stac")]
+    {
+        let mut ids = Vec::new();
+        let mut next = Some(format!(
+            "http://{}/search?collections=sorted&sortby=-properties.datetime&limit=2",
+            addr
+        ));
+        while let Some(uri) = next {
+            let res = client
+                .request(Request::builder().uri(uri).body(Body::empty())?)
+                .await?;
+
+            assert_eq!(200, res.status());
+            let body = hyper::body::to_bytes(res.into_body()).await?;
+            let fc: FeatureCollection = serde_json::from_slice(&body)?;
+            ids.extend(fc.features.into_iter().filter_map(|f| f.id));
+            next = fc.links.into_iter().find(|l| l.rel == NEXT).map(|l| l.href);
+        }
+        assert_eq!(ids, ["medium", "small", "large"]);
+
+        let body = json!({
+            "collections": ["sorted"],
+            "sortby": [{ "field": "properties.population", "direction": "asc" }]
+        });
+        let res = client
+            .request(
+                Request::builder()
+                    .method(axum::http::Method::POST)
+                    .uri(format!("http://{}/search", addr))
+                    .header("Content-Type", JSON)
+                    .body(Body::from(body.to_string()))?,
+            )
+            .await?;
+
+        assert_eq!(200, res.status());
+        let body = hyper::body::to_bytes(res.into_body()).await?;
+        let fc: FeatureCollection = serde_json::from_slice(&body)?;
+        let ids: Vec<String> = fc.features.into_iter().filter_map(|f| f.id).collect();
+        assert_eq!(ids, ["medium", "small", "large"]);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn property_selection() -> anyhow::Result<()> {
+    let (addr, _) = setup::spawn_app().await?;
+    let client = hyper::Client::new();
+
+    let collection = Collection {
+        id: "selection".to_string(),
+        ..Default::default()
+    };
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/collections", addr))
+                .header("Content-Type", JSON)
+                .body(Body::from(serde_json::to_string(&collection)?))?,
+        )
+        .await?;
+
+    assert_eq!(201, res.status());
+
+    let features = [("bern", 3), ("zug", 1), ("basel", 2)].map(|(id, population)| {
+        json!({
+            "type": "Feature",
+            "id": id,
+            "properties": { "name": id.to_uppercase(), "population": population },
+            "geometry": { "type": "Point", "coordinates": [7.44, 46.95] }
+        })
+    });
+    let fc = json!({ "type": "FeatureCollection", "features": features });
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/collections/selection/items", addr))
+                .header("Content-Type", GEO_JSON)
+                .body(Body::from(fc.to_string()))?,
+        )
+        .await?;
+
+    assert_eq!(201, res.status());
+
+    let names = |feature: &Feature| -> Vec<String> {
+        feature
+            .properties
+            .as_ref()
+            .map(|p| p.keys().cloned().collect())
+            .unwrap_or_default()
+    };
+
+    // features with the selected properties only, and without geometry
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!(
+                    "http://{}/collections/selection/items?properties=name&skipGeometry=true",
+                    addr
+                ))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(200, res.status());
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    let fc: FeatureCollection = serde_json::from_slice(&body)?;
+    assert_eq!(fc.features.len(), 3);
+    for feature in fc.features.iter() {
+        assert_eq!(names(feature), ["name"]);
+        assert!(feature.geometry.is_none());
+    }
+
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!(
+                    "http://{}/collections/selection/items/zug?properties=population&skipGeometry=true",
+                    addr
+                ))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(200, res.status());
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    let feature: Feature = serde_json::from_slice(&body)?;
+    assert_eq!(names(&feature), ["population"]);
+    assert_eq!(feature.properties.unwrap()["population"], 1);
+    assert!(feature.geometry.is_none());
+
+    // sort keys outside the selection still continue the pages
+    let mut ids = Vec::new();
+    let mut next = Some(format!(
+        "http://{}/collections/selection/items?properties=name&sortby=-population&limit=1",
+        addr
+    ));
+    while let Some(uri) = next {
+        let res = client
+            .request(Request::builder().uri(uri).body(Body::empty())?)
+            .await?;
+
+        assert_eq!(200, res.status());
+        let body = hyper::body::to_bytes(res.into_body()).await?;
+        let fc: FeatureCollection = serde_json::from_slice(&body)?;
+        for feature in fc.features {
+            assert_eq!(names(&feature), ["name"]);
+            assert!(feature.geometry.is_some());
+            ids.extend(feature.id);
+        }
+        next = fc.links.into_iter().find(|l| l.rel == NEXT).map(|l| l.href);
+    }
+    assert_eq!(ids, ["bern", "basel", "zug"]);
+
+    Ok(())
+}
@@ -0,0 +1,660 @@
+mod setup;
+
+use axum::http::{header::CONTENT_TYPE, Request};
+use hyper::Body;
+use serde_json::json;
+
+use ogcapi_types::{
+    common::{
+        link_rel::{ALTERNATE, NEXT},
+        media_type::{FLAT_GEOBUF, GEO_JSON, GEO_JSON_SEQ, JSON, JSON_FG},
+        Collection, Crs,
+    },
+    features::{Feature, FeatureCollection, Time},
+};
+
+#[tokio::test]
+async fn flatgeobuf() -> anyhow::Result<()> {
+    let (addr, _) = setup::spawn_app().await?;
+    let client = hyper::Client::new();
+
+    let collection = Collection {
+        id: "fgb".to_string(),
+        links: vec![],
+        crs: vec![Crs::default()],
+        ..Default::default()
+    };
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/collections", addr))
+                .header("Content-Type", JSON)
+                .body(Body::from(serde_json::to_string(&collection)?))?,
+        )
+        .await?;
+
+    assert_eq!(201, res.status());
+
+    let fc = json!({
+        "type": "FeatureCollection",
+        "features": (0..10).map(|i| json!({
+            "type": "Feature",
+            "id": format!("f{i}"),
+            "properties": { "name": format!("Feature {i}"), "i": i },
+            "geometry": { "type": "Point", "coordinates": [7.0 + i as f64, 46.0] }
+        })).collect::<Vec<_>>()
+    });
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/collections/fgb/items", addr))
+                .header("Content-Type", GEO_JSON)
+                .body(Body::from(fc.to_string()))?,
+        )
+        .await?;
+
+    assert_eq!(201, res.status());
+
+    // complete collection with spatial index
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/collections/fgb/items", addr))
+                .header("Accept", FLAT_GEOBUF)
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(200, res.status());
+    assert_eq!(res.headers()[CONTENT_TYPE], FLAT_GEOBUF);
+
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    assert_eq!(&body[..8], b"fgb\x03fgb\x00");
+
+    // header with the columns of the queryables
+    let (header, size) = Table::root(&body[8..]);
+    assert_eq!(header.string(0), Some("fgb"));
+    assert_eq!(header.doubles(1), Some(vec![7.0, 46.0, 16.0, 46.0]));
+    assert_eq!(header.u8(2), Some(1));
+    assert_eq!(header.u8(3), Some(0));
+    let columns: Vec<(&str, Option<u8>)> = header
+        .tables(7)
+        .iter()
+        .map(|column| (column.string(0).unwrap(), column.u8(1)))
+        .collect();
+    assert_eq!(
+        columns,
+        [("id", Some(11)), ("i", Some(7)), ("name", Some(11))]
+    );
+    assert_eq!(header.u64(8), Some(10));
+    assert_eq!(header.u16(9), Some(16));
+    assert_eq!(header.table(10).and_then(|crs| crs.i32(1)), Some(4326));
+
+    // packed R-tree with the root followed by the leaves in Hilbert order
+    let index = 8 + size;
+    let nodes: Vec<([f64; 4], u64)> = body[index..index + 11 * 40]
+        .chunks(40)
+        .map(|node| {
+            let mut envelope = [0.0; 4];
+            for (i, value) in envelope.iter_mut().enumerate() {
+                *value = f64::from_le_bytes(node[i * 8..i * 8 + 8].try_into().unwrap());
+            }
+            (envelope, u64::from_le_bytes(node[32..].try_into().unwrap()))
+        })
+        .collect();
+    assert_eq!(nodes[0], ([7.0, 46.0, 16.0, 46.0], 1));
+
+    // every leaf refers to the feature at its offset, one after the other
+    let features = index + 11 * 40;
+    let mut offset = 0;
+    let mut ids = Vec::new();
+    for (envelope, leaf) in nodes[1..].iter() {
+        assert_eq!(*leaf, offset as u64);
+        let (feature, size) = Table::root(&body[features + offset..]);
+        offset += size;
+
+        let geometry = feature.table(0).unwrap();
+        assert_eq!(geometry.doubles(1), Some(vec![envelope[0], envelope[1]]));
+        assert_eq!(geometry.u8(6), None);
+        assert_eq!(envelope[0], envelope[2]);
+
+        let i = envelope[0] as i64 - 7;
+        let properties = properties(feature.vector(1, 1).unwrap(), &[11, 7, 11]);
+        assert_eq!(
+            properties,
+            [
+                json!(format!("f{i}")),
+                json!(i),
+                json!(format!("Feature {i}"))
+            ]
+        );
+        ids.push(i);
+    }
+    assert_eq!(features + offset, body.len());
+    ids.sort();
+    assert_eq!(ids, (0..10).collect::<Vec<_>>());
+
+    // first page without index, next page in link header
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!(
+                    "http://{}/collections/fgb/items?f=fgb&limit=5",
+                    addr
+                ))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(200, res.status());
+    let link = res.headers()["Link"].to_str()?;
+    assert!(link.contains("f=fgb") && link.ends_with(r#"rel="next""#));
+
+    let page = hyper::body::to_bytes(res.into_body()).await?;
+    assert!(page.len() < body.len());
+
+    // the features follow the header directly
+    let (header, size) = Table::root(&page[8..]);
+    assert_eq!(header.u64(8), Some(5));
+    assert_eq!(header.u16(9), Some(0));
+    let mut offset = 8 + size;
+    for i in 0..5 {
+        let (feature, size) = Table::root(&page[offset..]);
+        offset += size;
+
+        let geometry = feature.table(0).unwrap();
+        assert_eq!(geometry.doubles(1), Some(vec![7.0 + i as f64, 46.0]));
+        let properties = properties(feature.vector(1, 1).unwrap(), &[11, 7, 11]);
+        assert_eq!(properties[0], json!(format!("f{i}")));
+    }
+    assert_eq!(offset, page.len());
+
+    // not available for other resources
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/collections/fgb?f=fgb", addr))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(400, res.status());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn streamed_geojson() -> anyhow::Result<()> {
+    let (addr, _) = setup::spawn_app().await?;
+    let client = hyper::Client::new();
+
+    let collection = Collection {
+        id: "streamed".to_string(),
+        links: vec![],
+        crs: vec![Crs::default()],
+        ..Default::default()
+    };
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/collections", addr))
+                .header("Content-Type", JSON)
+                .body(Body::from(serde_json::to_string(&collection)?))?,
+        )
+        .await?;
+
+    assert_eq!(201, res.status());
+
+    // more features than fetched from the cursor at once
+    let fc = json!({
+        "type": "FeatureCollection",
+        "features": (0..2500).map(|i| json!({
+            "type": "Feature",
+            "id": format!("f{i:04}"),
+            "properties": { "i": i },
+            "geometry": { "type": "Point", "coordinates": [7.0, 46.0] }
+        })).collect::<Vec<_>>()
+    });
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/collections/streamed/items", addr))
+                .header("Content-Type", GEO_JSON)
+                .body(Body::from(fc.to_string()))?,
+        )
+        .await?;
+
+    assert_eq!(201, res.status());
+
+    // chunked feature collection
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!(
+                    "http://{}/collections/streamed/items?limit=2000",
+                    addr
+                ))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(200, res.status());
+    assert_eq!(res.headers()[CONTENT_TYPE], GEO_JSON);
+
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    let fc: FeatureCollection = serde_json::from_slice(&body)?;
+    assert_eq!(fc.features.len(), 2000);
+    assert_eq!(fc.number_matched, Some(2500));
+    assert_eq!(fc.number_returned, Some(2000));
+    assert_eq!(fc.features[1999].id.as_deref(), Some("f1999"));
+    assert!(fc.links.iter().any(|l| l.rel == NEXT));
+
+    // text sequence
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!(
+                    "http://{}/collections/streamed/items?limit=10000&properties=i",
+                    addr
+                ))
+                .header("Accept", GEO_JSON_SEQ)
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(200, res.status());
+    assert_eq!(res.headers()[CONTENT_TYPE], GEO_JSON_SEQ);
+
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    let features = body
+        .split(|b| *b == 0x1e)
+        .filter(|record| !record.is_empty())
+        .map(serde_json::from_slice)
+        .collect::<Result<Vec<Feature>, _>>()?;
+    assert_eq!(features.len(), 2500);
+    assert!(features
+        .iter()
+        .all(|f| f.properties.as_ref().unwrap().len() == 1));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn csv() -> anyhow::Result<()> {
+    let (addr, _) = setup::spawn_app().await?;
+    let client = hyper::Client::new();
+
+    let collection = Collection {
+        id: "csv".to_string(),
+        links: vec![],
+        crs: vec![Crs::default()],
+        ..Default::default()
+    };
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/collections", addr))
+                .header("Content-Type", JSON)
+                .body(Body::from(serde_json::to_string(&collection)?))?,
+        )
+        .await?;
+
+    assert_eq!(201, res.status());
+
+    let fc = json!({
+        "type": "FeatureCollection",
+        "features": [{
+            "type": "Feature",
+            "id": "a",
+            "properties": { "name": "Bern, CH", "address": { "zip": 3011 } },
+            "geometry": { "type": "Point", "coordinates": [7.5, 46.9] }
+        }, {
+            "type": "Feature",
+            "id": "b",
+            "properties": { "name": "=1+1" },
+            "geometry": { "type": "LineString", "coordinates": [[7.0, 46.0], [8.0, 47.0]] }
+        }]
+    });
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/collections/csv/items", addr))
+                .header("Content-Type", GEO_JSON)
+                .body(Body::from(fc.to_string()))?,
+        )
+        .await?;
+
+    assert_eq!(201, res.status());
+
+    // items with well-known text
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!(
+                    "http://{}/collections/csv/items?f=csv&sortby=id",
+                    addr
+                ))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(200, res.status());
+    assert!(res.headers()[CONTENT_TYPE]
+        .to_str()?
+        .starts_with("text/csv"));
+
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    let csv = std::str::from_utf8(&body)?;
+    let rows: Vec<&str> = csv.split_terminator("\r\n").collect();
+    assert_eq!(rows.len(), 3);
+    assert!(rows[0].starts_with("id,WKT,"));
+    assert!(rows[0].contains("address.zip"));
+    assert!(rows[1].starts_with("a,POINT (7.5 46.9),"));
+    assert!(rows[1].contains(r#""Bern, CH""#));
+    assert!(rows[2].starts_with("b,\"LINESTRING (7 46, 8 47)\","));
+    assert!(rows[2].contains("'=1+1"));
+
+    // single feature with coordinate columns
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!(
+                    "http://{}/collections/csv/items/a?csv-geometry=xy",
+                    addr
+                ))
+                .header("Accept", "text/csv")
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(200, res.status());
+
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    let csv = std::str::from_utf8(&body)?;
+    assert!(csv.starts_with("id,lon,lat,"));
+    assert!(csv.contains("\r\na,7.5,46.9,"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn json_fg() -> anyhow::Result<()> {
+    let (addr, _) = setup::spawn_app().await?;
+    let client = hyper::Client::new();
+
+    let collection = Collection {
+        id: "jsonfg".to_string(),
+        links: vec![],
+        crs: vec![Crs::default(), Crs::from_epsg(2056)],
+        ..Default::default()
+    };
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/collections", addr))
+                .header("Content-Type", JSON)
+                .body(Body::from(serde_json::to_string(&collection)?))?,
+        )
+        .await?;
+
+    assert_eq!(201, res.status());
+
+    let fc = json!({
+        "type": "FeatureCollection",
+        "features": [{
+            "type": "Feature",
+            "id": "bern",
+            "properties": { "datetime": "2022-08-01T12:00:00Z" },
+            "geometry": { "type": "Point", "coordinates": [7.44, 46.95] }
+        }, {
+            "type": "Feature",
+            "id": "zurich",
+            "properties": { "start_datetime": "2022-01-01" },
+            "geometry": { "type": "Point", "coordinates": [8.54, 47.37] }
+        }]
+    });
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/collections/jsonfg/items", addr))
+                .header("Content-Type", GEO_JSON)
+                .body(Body::from(fc.to_string()))?,
+        )
+        .await?;
+
+    assert_eq!(201, res.status());
+
+    // projected coordinates are moved to `place`
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!(
+                    "http://{}/collections/jsonfg/items?f=jsonfg&sortby=id&crs={}",
+                    addr,
+                    Crs::from_epsg(2056)
+                ))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(200, res.status());
+    assert_eq!(res.headers()[CONTENT_TYPE], JSON_FG);
+
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    let fc: FeatureCollection = serde_json::from_slice(&body)?;
+    assert_eq!(fc.feature_type.as_deref(), Some("jsonfg"));
+    assert_eq!(fc.coord_ref_sys, Some(Crs::from_epsg(2056).to_string()));
+    assert!(fc.conforms_to.is_some());
+    assert!(!fc
+        .links
+        .iter()
+        .any(|l| l.rel == ALTERNATE && l.r#type.as_deref() == Some(GEO_JSON)));
+
+    let bern = &fc.features[0];
+    assert!(bern.geometry.is_none() && bern.place.is_some());
+    assert_eq!(bern.time, Some(Time::instant("2022-08-01T12:00:00Z")));
+    let zurich = &fc.features[1];
+    assert_eq!(zurich.time, Some(Time::interval(Some("2022-01-01"), None)));
+
+    // WGS 84 geometries stay in `geometry`
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/collections/jsonfg/items/bern", addr))
+                .header("Accept", JSON_FG)
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(200, res.status());
+    assert_eq!(res.headers()[CONTENT_TYPE], JSON_FG);
+
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    let feature: Feature = serde_json::from_slice(&body)?;
+    assert!(feature.geometry.is_some() && feature.place.is_none());
+    assert_eq!(feature.feature_type.as_deref(), Some("jsonfg"));
+
+    // GeoJSON is restricted to WGS 84 longitude/latitude
+    for path in [
+        "items?f=json&",
+        "items?f=geojsonseq&",
+        "items/bern?f=json&",
+        "items/bern?",
+    ] {
+        let res = client
+            .request(
+                Request::builder()
+                    .uri(format!(
+                        "http://{}/collections/jsonfg/{}crs={}",
+                        addr,
+                        path,
+                        Crs::from_epsg(2056)
+                    ))
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(400, res.status(), "{path}");
+    }
+
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!(
+                    "http://{}/collections/jsonfg/items?f=json&crs={}",
+                    addr,
+                    Crs::default()
+                ))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(200, res.status());
+    assert_eq!(res.headers()["Content-Crs"], Crs::default().to_string());
+
+    Ok(())
+}
+
+/// Minimal reader of the size prefixed FlatBuffers tables of FlatGeobuf
+#[derive(Clone, Copy)]
+struct Table<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Table<'a> {
+    /// Root table of a size prefixed buffer and the size including the prefix
+    fn root(buf: &'a [u8]) -> (Self, usize) {
+        let size = u32_at(buf, 0) as usize;
+        let buf = &buf[4..4 + size];
+        let pos = u32_at(buf, 0) as usize;
+        (Table { buf, pos }, 4 + size)
+    }
+
+    /// Position of a field, if present
+    fn field(&self, slot: usize) -> Option<usize> {
+        let soffset = i32::from_le_bytes(self.buf[self.pos..self.pos + 4].try_into().unwrap());
+        let vtable = (self.pos as i64 - soffset as i64) as usize;
+        let entry = 4 + 2 * slot;
+        if entry >= u16_at(self.buf, vtable) as usize {
+            return None;
+        }
+        match u16_at(self.buf, vtable + entry) {
+            0 => None,
+            offset => Some(self.pos + offset as usize),
+        }
+    }
+
+    fn u8(&self, slot: usize) -> Option<u8> {
+        self.field(slot).map(|pos| self.buf[pos])
+    }
+
+    fn u16(&self, slot: usize) -> Option<u16> {
+        self.field(slot).map(|pos| u16_at(self.buf, pos))
+    }
+
+    fn i32(&self, slot: usize) -> Option<i32> {
+        self.field(slot).map(|pos| u32_at(self.buf, pos) as i32)
+    }
+
+    fn u64(&self, slot: usize) -> Option<u64> {
+        self.field(slot)
+            .map(|pos| u64::from_le_bytes(self.buf[pos..pos + 8].try_into().unwrap()))
+    }
+
+    /// Position of the object an offset field refers to
+    fn target(&self, slot: usize) -> Option<usize> {
+        self.field(slot)
+            .map(|pos| pos + u32_at(self.buf, pos) as usize)
+    }
+
+    fn table(&self, slot: usize) -> Option<Table<'a>> {
+        let pos = self.target(slot)?;
+        Some(Table { buf: self.buf, pos })
+    }
+
+    /// Elements of a vector of scalars with `size` bytes each
+    fn vector(&self, slot: usize, size: usize) -> Option<&'a [u8]> {
+        let pos = self.target(slot)?;
+        let len = u32_at(self.buf, pos) as usize;
+        Some(&self.buf[pos + 4..pos + 4 + len * size])
+    }
+
+    fn string(&self, slot: usize) -> Option<&'a str> {
+        std::str::from_utf8(self.vector(slot, 1)?).ok()
+    }
+
+    fn doubles(&self, slot: usize) -> Option<Vec<f64>> {
+        let bytes = self.vector(slot, 8)?;
+        Some(
+            bytes
+                .chunks(8)
+                .map(|b| f64::from_le_bytes(b.try_into().unwrap()))
+                .collect(),
+        )
+    }
+
+    fn tables(&self, slot: usize) -> Vec<Table<'a>> {
+        let pos = match self.target(slot) {
+            Some(pos) => pos,
+            None => return Vec::new(),
+        };
+        (0..u32_at(self.buf, pos) as usize)
+            .map(|i| {
+                let offset = pos + 4 + 4 * i;
+                Table {
+                    buf: self.buf,
+                    pos: offset + u32_at(self.buf, offset) as usize,
+                }
+            })
+            .collect()
+    }
+}
+
+fn u16_at(buf: &[u8], pos: usize) -> u16 {
+    u16::from_le_bytes(buf[pos..pos + 2].try_into().unwrap())
+}
+
+fn u32_at(buf: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes(buf[pos..pos + 4].try_into().unwrap())
+}
+
+/// Values of the properties by column, for long and string columns
+fn properties(mut bytes: &[u8], types: &[u8]) -> Vec<serde_json::Value> {
+    let mut values = vec![serde_json::Value::Null; types.len()];
+    while !bytes.is_empty() {
+        let column = u16_at(bytes, 0) as usize;
+        bytes = &bytes[2..];
+        values[column] = match types[column] {
+            7 => {
+                let value = i64::from_le_bytes(bytes[..8].try_into().unwrap());
+                bytes = &bytes[8..];
+                json!(value)
+            }
+            11 => {
+                let len = u32_at(bytes, 0) as usize;
+                let value = std::str::from_utf8(&bytes[4..4 + len]).unwrap();
+                bytes = &bytes[4 + len..];
+                json!(value)
+            }
+            other => panic!("unexpected column type {other}"),
+        };
+    }
+    values
+}
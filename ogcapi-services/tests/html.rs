@@ -0,0 +1,118 @@
+mod setup;
+
+use axum::http::{header::CONTENT_TYPE, Request};
+use hyper::Body;
+
+use ogcapi_types::common::{
+    link_rel::ALTERNATE,
+    media_type::{HTML, JSON},
+    Collection, Crs, LandingPage, Link,
+};
+
+#[tokio::test]
+async fn content_negotiation() -> anyhow::Result<()> {
+    let (addr, _) = setup::spawn_app().await?;
+    let client = hyper::Client::new();
+
+    // accept header
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/collections", addr))
+                .header("Accept", "text/html,application/xhtml+xml,*/*;q=0.8")
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(200, res.status());
+    assert!(res.headers()[CONTENT_TYPE].to_str()?.starts_with(HTML));
+
+    // query parameter takes precedence
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/?f=json", addr))
+                .header("Accept", HTML)
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(200, res.status());
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    let landing_page: LandingPage = serde_json::from_slice(&body)?;
+    let alternate = landing_page
+        .links
+        .iter()
+        .find(|l| l.rel == ALTERNATE)
+        .unwrap();
+    assert_eq!(alternate.r#type.as_deref(), Some(HTML));
+    assert!(alternate.href.ends_with("f=html"));
+
+    // unknown format
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/conformance?f=xml", addr))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(400, res.status());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn unsafe_links() -> anyhow::Result<()> {
+    let (addr, _) = setup::spawn_app().await?;
+    let client = hyper::Client::new();
+
+    let collection = Collection {
+        id: "links".to_string(),
+        links: vec![
+            Link::new("javascript:alert(1)", "describedby").title("Script"),
+            Link::new(" JavaScript:alert(1)", "license").title("Padded"),
+            Link::new("data:text/html,<script>alert(1)</script>", "about"),
+            Link::new("https://example.com/docs", "help").title("Docs"),
+            Link::new("../other", "related").title("Relative"),
+        ],
+        crs: vec![Crs::default()],
+        ..Default::default()
+    };
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/collections", addr))
+                .header("Content-Type", JSON)
+                .body(Body::from(serde_json::to_string(&collection)?))?,
+        )
+        .await?;
+
+    assert_eq!(201, res.status());
+
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/collections/links?f=html", addr))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(200, res.status());
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    let html = std::str::from_utf8(&body)?.to_lowercase();
+
+    // rendered as text only
+    assert!(!html.contains(r#"href="data:"#));
+    assert!(!html.contains(r#"href=" javascript:"#));
+    assert!(!html.contains(r#"href="javascript:"#));
+    assert!(html.contains("<td>script</td>"));
+    assert!(html.contains("<td>padded</td>"));
+
+    assert!(html.contains(r#"<a href="https://example.com/docs">docs</a>"#));
+    assert!(html.contains(r#"<a href="../other">relative</a>"#));
+
+    Ok(())
+}
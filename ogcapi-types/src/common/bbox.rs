@@ -12,6 +12,49 @@ pub enum Bbox {
     Bbox3D(Bbox3D),
 }
 
+impl Bbox {
+    /// Coordinates of the lower corner
+    pub fn lower(&self) -> &[f64] {
+        match self {
+            Bbox::Bbox2D(bbox) => &bbox[..2],
+            Bbox::Bbox3D(bbox) => &bbox[..3],
+        }
+    }
+
+    /// Coordinates of the upper corner
+    pub fn upper(&self) -> &[f64] {
+        match self {
+            Bbox::Bbox2D(bbox) => &bbox[2..],
+            Bbox::Bbox3D(bbox) => &bbox[3..],
+        }
+    }
+
+    /// Swap the first two axes, e.g. from latitude/longitude to longitude/latitude order
+    pub fn swap_axes(&self) -> Bbox {
+        match self {
+            Bbox::Bbox2D(b) => Bbox::Bbox2D([b[1], b[0], b[3], b[2]]),
+            Bbox::Bbox3D(b) => Bbox::Bbox3D([b[1], b[0], b[2], b[4], b[3], b[5]]),
+        }
+    }
+
+    /// Split a longitude/latitude bbox crossing the antimeridian, i.e. with a
+    /// west-most edge greater than the east-most edge, into an eastern and a
+    /// western part.
+    pub fn split_antimeridian(&self) -> Vec<Bbox> {
+        match self {
+            Bbox::Bbox2D(b) if b[0] > b[2] => vec![
+                Bbox::Bbox2D([b[0], b[1], 180.0, b[3]]),
+                Bbox::Bbox2D([-180.0, b[1], b[2], b[3]]),
+            ],
+            Bbox::Bbox3D(b) if b[0] > b[3] => vec![
+                Bbox::Bbox3D([b[0], b[1], b[2], 180.0, b[4], b[5]]),
+                Bbox::Bbox3D([-180.0, b[1], b[2], b[3], b[4], b[5]]),
+            ],
+            bbox => vec![bbox.to_owned()],
+        }
+    }
+}
+
 impl fmt::Display for Bbox {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -99,6 +142,23 @@ mod tests {
         let _bbox: Bbox = Bbox::from_str(s).unwrap();
     }
 
+    #[test]
+    fn antimeridian() {
+        let bbox = Bbox::from([160.6, -55.95, -170.0, -25.89]);
+        let parts = bbox.split_antimeridian();
+        assert_eq!(parts[0], Bbox::from([160.6, -55.95, 180.0, -25.89]));
+        assert_eq!(parts[1], Bbox::from([-180.0, -55.95, -170.0, -25.89]));
+
+        let bbox = Bbox::from([7.0, 46.0, 0.0, 8.0, 47.0, 100.0]);
+        assert_eq!(bbox.split_antimeridian(), vec![bbox.to_owned()]);
+        assert_eq!(bbox.lower(), &[7.0, 46.0, 0.0]);
+        assert_eq!(bbox.upper(), &[8.0, 47.0, 100.0]);
+        assert_eq!(
+            bbox.swap_axes(),
+            Bbox::from([46.0, 7.0, 0.0, 47.0, 8.0, 100.0])
+        );
+    }
+
     #[test]
     fn serde_json() {
         let s = "[ 160.6, -55.95, -170, -25.89 ]";
@@ -34,6 +34,9 @@ pub struct Collection {
     #[serde_as(as = "Option<DisplayFromStr>")]
     pub storage_crs: Option<Crs>,
     pub storage_crs_coordinate_epoch: Option<f32>,
+    /// Properties holding the temporal extent of the features, which the
+    /// `datetime` parameter is evaluated against. Defaults to the STAC ones.
+    pub temporal_properties: Option<TemporalProperties>,
     #[serde(default)]
     pub links: Links,
     /// Detailed information relevant to individual query types
@@ -109,6 +112,7 @@ impl Default for Collection {
             crs: vec![Crs::default()],
             storage_crs: Default::default(),
             storage_crs_coordinate_epoch: Default::default(),
+            temporal_properties: Default::default(),
             links: Default::default(),
             #[cfg(feature = "edr")]
             data_queries: Default::default(),
@@ -132,3 +136,45 @@ impl Default for Collection {
         }
     }
 }
+
+/// Properties of the features with an instant or the bounds of an interval
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct TemporalProperties {
+    /// Instant of a feature, e.g. `datetime`
+    pub instant: Option<TemporalProperty>,
+    /// Start of the interval of a feature, open if missing
+    pub start: Option<TemporalProperty>,
+    /// End of the interval of a feature, open if missing
+    pub end: Option<TemporalProperty>,
+}
+
+impl Default for TemporalProperties {
+    fn default() -> Self {
+        Self {
+            instant: Some(TemporalProperty::Property("datetime".to_string())),
+            start: Some(TemporalProperty::Property("start_datetime".to_string())),
+            end: Some(TemporalProperty::Property("end_datetime".to_string())),
+        }
+    }
+}
+
+/// Source of a RFC 3339 timestamp
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum TemporalProperty {
+    /// Read from the feature property
+    Property(String),
+    /// Kept in a dedicated column, generated from the feature property of the
+    /// same name
+    Column(String),
+}
+
+impl TemporalProperty {
+    /// Name of the feature property
+    pub fn name(&self) -> &str {
+        match self {
+            TemporalProperty::Property(name) | TemporalProperty::Column(name) => name,
+        }
+    }
+}
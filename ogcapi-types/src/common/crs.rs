@@ -53,6 +53,7 @@ impl Crs {
                 _ => None,
             },
             Authority::EPSG => Some(self.to_owned()),
+            _ => None,
         }
     }
 
@@ -60,20 +61,24 @@ impl Crs {
         match self.authority {
             Authority::OGC => match self.code.as_str() {
                 "CRS84h" => Some(4979),
-                _ => panic!("Unable to extract epsg code from `{}`", self),
+                _ => None,
             },
             Authority::EPSG => self.code.parse().ok(),
+            _ => None,
         }
     }
 
-    pub fn as_srid(&self) -> i32 {
+    /// SRID of the crs if it is an OGC or EPSG one, other authorities need a
+    /// lookup in a catalogue of spatial reference systems
+    pub fn as_srid(&self) -> Option<i32> {
         match self.authority {
             Authority::OGC => match self.code.as_str() {
-                "CRS84" => 4326,
-                "CRS84h" => 4979,
-                _ => panic!("Unable to extract epsg code from `{}`", self),
+                "CRS84" => Some(4326),
+                "CRS84h" => Some(4979),
+                _ => None,
             },
-            Authority::EPSG => self.code.parse().unwrap(),
+            Authority::EPSG => self.code.parse().ok(),
+            _ => None,
         }
     }
 }
@@ -94,22 +99,50 @@ impl str::FromStr for Crs {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = if s.starts_with("urn") {
-            s.trim_start_matches("urn:ogc:def:crs:")
-                .split(':')
-                .collect()
+        let parts: Vec<&str> = if let Some(urn) = strip_prefix_ignore_case(s, "urn:ogc:def:crs:") {
+            urn.split(':').collect()
+        } else if let Some(uri) = strip_prefix_ignore_case(s, "http://www.opengis.net/def/crs/") {
+            uri.split('/').collect()
         } else {
-            s.trim_start_matches("http://www.opengis.net/def/crs/")
-                .split('/')
-                .collect()
+            s.split(['/', ':']).collect()
         };
-        match parts.len() {
-            3 => Ok(Crs::new(Authority::from_str(parts[0])?, parts[1], parts[2])),
-            _ => Err(format!("Unable to parse CRS from `{s}`!")),
-        }
+        let (authority, version, code) = match parts[..] {
+            // an empty or missing version, e.g. of `EPSG:4326`, stands for the latest one
+            [authority, "", code] | [authority, code] if !code.is_empty() => {
+                let version = match code.to_uppercase().as_str() {
+                    "CRS27" | "CRS83" | "CRS84" if authority.eq_ignore_ascii_case("OGC") => "1.3",
+                    _ => "0",
+                };
+                (authority, version, code)
+            }
+            [authority, version, code] if !code.is_empty() => (authority, version, code),
+            _ => return Err(format!("Unable to parse CRS from `{s}`!")),
+        };
+
+        let authority = Authority::from_str(authority)?;
+        let code = match authority {
+            // e.g. `CRS84` and `CRS84h`
+            Authority::OGC => {
+                let code = code.to_uppercase();
+                match code.strip_suffix('H') {
+                    Some(code) => format!("{code}h"),
+                    None => code,
+                }
+            }
+            _ => code.to_owned(),
+        };
+
+        Ok(Crs::new(authority, version, code))
     }
 }
 
+/// Remainder of the string after the prefix, ignoring the ASCII case
+fn strip_prefix_ignore_case<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    s.get(..prefix.len())
+        .filter(|start| start.eq_ignore_ascii_case(prefix))
+        .map(|_| &s[prefix.len()..])
+}
+
 impl Default for Crs {
     fn default() -> Crs {
         Crs {
@@ -120,11 +153,95 @@ impl Default for Crs {
     }
 }
 
+/// Order of the horizontal axes of a crs
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AxisOrder {
+    /// Easting or longitude first
+    XY,
+    /// Northing or latitude first
+    YX,
+}
+
+impl AxisOrder {
+    /// Axis order declared by the `AXIS` of a WKT 1 or WKT 2 definition, if
+    /// its first axis points in a horizontal direction. Axes of nested
+    /// definitions, like the base crs of a projected one, are skipped.
+    pub fn from_wkt(wkt: &str) -> Option<AxisOrder> {
+        let (_, definition) = wkt_elements(wkt).into_iter().next()?;
+        wkt_elements(definition)
+            .into_iter()
+            .find(|(keyword, _)| keyword == "AXIS")
+            .and_then(|(_, axis)| axis_direction(axis))
+    }
+}
+
+/// Keywords and contents of the elements of a WKT, without descending into
+/// them, e.g. the `AXIS` and `ID` of the contents of a crs
+pub(crate) fn wkt_elements(wkt: &str) -> Vec<(String, &str)> {
+    let mut elements = Vec::new();
+    let mut depth: usize = 0;
+    let mut quoted = false;
+    let mut start = 0;
+    let mut open = 0;
+
+    for (i, c) in wkt.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '[' | '(' if !quoted => {
+                if depth == 0 {
+                    open = i + 1;
+                }
+                depth += 1;
+            }
+            // unbalanced closing brackets of a malformed WKT are skipped
+            ']' | ')' if !quoted && depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    let keyword = wkt.get(start..open.saturating_sub(1)).unwrap_or_default();
+                    elements.push((keyword.trim().to_uppercase(), &wkt[open..i]));
+                }
+            }
+            ',' if !quoted && depth == 0 => start = i + 1,
+            _ => {}
+        }
+    }
+
+    elements
+}
+
+/// First quoted string of a WKT content, e.g. the name of a crs
+pub(crate) fn wkt_name(content: &str) -> Option<&str> {
+    let (name, _) = content.trim_start().strip_prefix('"')?.split_once('"')?;
+    Some(name)
+}
+
+/// Axis order of the direction of an `AXIS`, which follows its quoted name
+fn axis_direction(axis: &str) -> Option<AxisOrder> {
+    let (_, direction) = axis.trim_start().strip_prefix('"')?.split_once('"')?;
+    let direction: String = direction
+        .trim_start_matches(|c: char| c == ',' || c.is_whitespace())
+        .chars()
+        .take_while(char::is_ascii_alphabetic)
+        .collect();
+
+    match direction.to_lowercase().as_str() {
+        "east" | "west" => Some(AxisOrder::XY),
+        "north" | "south" => Some(AxisOrder::YX),
+        _ => None,
+    }
+}
+
 /// CRS Authorities
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Hash)]
 pub enum Authority {
     OGC,
     EPSG,
+    /// International Astronomical Union, for celestial bodies other than the
+    /// earth
+    IAU,
+    ESRI,
+    /// Any other authority, by its name in the URI
+    Other(String),
 }
 
 impl fmt::Display for Authority {
@@ -132,6 +249,9 @@ impl fmt::Display for Authority {
         match self {
             Authority::OGC => write!(f, "OGC"),
             Authority::EPSG => write!(f, "EPSG"),
+            Authority::IAU => write!(f, "IAU"),
+            Authority::ESRI => write!(f, "ESRI"),
+            Authority::Other(name) => write!(f, "{name}"),
         }
     }
 }
@@ -140,9 +260,17 @@ impl str::FromStr for Authority {
     type Err = &'static str;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
+        match s.to_uppercase().as_str() {
             "OGC" => Ok(Authority::OGC),
             "EPSG" => Ok(Authority::EPSG),
+            "IAU" => Ok(Authority::IAU),
+            "ESRI" => Ok(Authority::ESRI),
+            _ if !s.is_empty()
+                && s.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-._".contains(c)) =>
+            {
+                Ok(Authority::Other(s.to_string()))
+            }
             _ => Err("Unknown crs authority!"),
         }
     }
@@ -152,7 +280,7 @@ impl str::FromStr for Authority {
 mod tests {
     use std::str::FromStr;
 
-    use crate::common::{Crs, OGC_CRS84};
+    use crate::common::{Authority, AxisOrder, Crs, OGC_CRS84};
 
     #[test]
     fn parse_crs() {
@@ -182,6 +310,103 @@ mod tests {
         )
     }
 
+    #[test]
+    fn other_authorities() {
+        let crs = Crs::from_str("http://www.opengis.net/def/crs/IAU/2015/49900").unwrap();
+        assert_eq!(crs.authority, Authority::IAU);
+        assert_eq!(crs.as_srid(), None);
+
+        let crs = Crs::from_str("urn:ogc:def:crs:ESRI::54030").unwrap();
+        assert_eq!(crs.authority, Authority::ESRI);
+        assert_eq!(crs.to_urn(), "urn:ogc:def:crs:ESRI:0:54030");
+
+        let crs = Crs::from_str("http://www.opengis.net/def/crs/MyOrg/1/42").unwrap();
+        assert_eq!(crs.authority, Authority::Other("MyOrg".to_string()));
+        assert_eq!(crs.to_string(), "http://www.opengis.net/def/crs/MyOrg/1/42");
+
+        assert!(Crs::from_str("http://www.opengis.net/def/crs/EPSG/0/").is_err());
+        assert!(Crs::from_str("http://www.opengis.net/def/crs/E?SG/0/4326").is_err());
+    }
+
+    #[test]
+    fn case_insensitive() {
+        for s in [
+            "http://www.opengis.net/def/crs/epsg/0/4326",
+            "HTTP://WWW.OPENGIS.NET/DEF/CRS/Epsg/0/4326",
+            "urn:ogc:def:crs:epsg::4326",
+            "URN:OGC:DEF:CRS:EPSG:0:4326",
+            "epsg:4326",
+            "EPSG:4326",
+        ] {
+            assert_eq!(Crs::from_str(s), Ok(Crs::from_epsg(4326)), "{s}");
+        }
+
+        for s in [
+            "http://www.opengis.net/def/crs/ogc/1.3/crs84",
+            "urn:ogc:def:crs:OGC::CRS84",
+            "ogc:crs84",
+        ] {
+            assert_eq!(Crs::from_str(s), Ok(Crs::default()), "{s}");
+        }
+
+        let crs = Crs::from_str("http://www.opengis.net/def/crs/ogc/0/crs84H").unwrap();
+        assert_eq!(
+            crs.to_string(),
+            "http://www.opengis.net/def/crs/OGC/0/CRS84h"
+        );
+        assert_eq!(crs.as_srid(), Some(4979));
+
+        let crs = Crs::from_str("http://www.opengis.net/def/crs/esri/0/54030").unwrap();
+        assert_eq!(crs.authority, Authority::ESRI);
+    }
+
+    #[test]
+    fn unknown_codes() {
+        let crs = Crs::from_str("http://www.opengis.net/def/crs/OGC/1.3/CRS27").unwrap();
+        assert_eq!(crs.as_srid(), None);
+        assert_eq!(crs.as_epsg(), None);
+
+        let crs = Crs::from_str("http://www.opengis.net/def/crs/EPSG/0/abc").unwrap();
+        assert_eq!(crs.as_srid(), None);
+    }
+
+    #[test]
+    fn axis_order() {
+        let wkt = r#"GEOGCS["WGS 84",DATUM["WGS_1984",SPHEROID["WGS 84",6378137,298.257223563]],PRIMEM["Greenwich",0],UNIT["degree",0.0174532925199433],AXIS["Latitude",NORTH],AXIS["Longitude",EAST],AUTHORITY["EPSG","4326"]]"#;
+        assert_eq!(AxisOrder::from_wkt(wkt), Some(AxisOrder::YX));
+
+        // the axes of the base crs do not count
+        let wkt = r#"PROJCS["CH1903+ / LV95",GEOGCS["CH1903+",DATUM["CH1903+",SPHEROID["Bessel 1841",6377397.155,299.1528128]],UNIT["degree",0.0174532925199433],AXIS["Latitude",NORTH],AXIS["Longitude",EAST]],PROJECTION["Hotine_Oblique_Mercator_Azimuth_Center"],UNIT["metre",1],AXIS["Easting",EAST],AXIS["Northing",NORTH],AUTHORITY["EPSG","2056"]]"#;
+        assert_eq!(AxisOrder::from_wkt(wkt), Some(AxisOrder::XY));
+
+        let wkt = r#"GEOGCRS["WGS 84",DATUM["World Geodetic System 1984",ELLIPSOID["WGS 84",6378137,298.257223563]],CS[ellipsoidal,2],AXIS["geodetic latitude (Lat)",north,ORDER[1]],AXIS["geodetic longitude (Lon)",east,ORDER[2]],ID["EPSG",4326]]"#;
+        assert_eq!(AxisOrder::from_wkt(wkt), Some(AxisOrder::YX));
+
+        let wkt = r#"PROJCRS["ETRS89-extended / LAEA Europe",BASEGEOGCRS["ETRS89",ID["EPSG",4258]],CONVERSION["Europe Equal Area 2001",METHOD["Lambert Azimuthal Equal Area"]],CS[Cartesian,2],AXIS["northing (Y)",north,LENGTHUNIT["metre",1]],AXIS["easting (X)",east,LENGTHUNIT["metre",1]],ID["EPSG",3035]]"#;
+        assert_eq!(AxisOrder::from_wkt(wkt), Some(AxisOrder::YX));
+
+        let wkt = r#"PROJCS["WGS 84 / Pseudo-Mercator",GEOGCS["WGS 84",AUTHORITY["EPSG","4326"]],PROJECTION["Mercator_1SP"],AUTHORITY["EPSG","3857"]]"#;
+        assert_eq!(AxisOrder::from_wkt(wkt), None);
+    }
+
+    #[test]
+    fn malformed_wkt() {
+        for wkt in [
+            "]",
+            ")[",
+            "GEOGCS]]",
+            r#"GEOGCS["WGS 84",AXIS["Lat",NORTH]]],AXIS["Lon",EAST]"#,
+            r#"],GEOGCS["WGS 84""#,
+            "",
+        ] {
+            AxisOrder::from_wkt(wkt);
+        }
+
+        // stray closing brackets are skipped
+        let wkt = r#"]GEOGCS["WGS 84",AXIS["Latitude",NORTH],AXIS["Longitude",EAST]]"#;
+        assert_eq!(AxisOrder::from_wkt(wkt), Some(AxisOrder::YX));
+    }
+
     #[test]
     fn to_epsg() {
         let crs = Crs::from_str("http://www.opengis.net/def/crs/EPSG/0/4979").unwrap();
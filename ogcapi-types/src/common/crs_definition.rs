@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_with::DisplayFromStr;
+
+use super::{
+    crs::{wkt_elements, wkt_name},
+    projjson, Bbox, Crs, Links,
+};
+
+/// Definition of a coordinate reference system supported by the server
+#[serde_with::serde_as]
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CrsDefinition {
+    /// URI of the crs
+    #[serde_as(as = "DisplayFromStr")]
+    pub crs: Crs,
+    pub name: Option<String>,
+    pub area_of_use: Option<AreaOfUse>,
+    /// Well-known text of the definition, WKT 1 or WKT 2 as provided by the
+    /// backend
+    pub wkt: Option<String>,
+    /// PROJJSON of the definition, converted from the WKT of geographic and
+    /// projected crs
+    pub projjson: Option<Value>,
+}
+
+/// Extent in which a crs is valid
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AreaOfUse {
+    pub description: Option<String>,
+    /// Bounding box in CRS84
+    pub bbox: Option<Bbox>,
+}
+
+impl CrsDefinition {
+    pub fn new(crs: Crs) -> Self {
+        CrsDefinition {
+            crs,
+            ..Default::default()
+        }
+    }
+
+    /// Definition with the name, area of use and PROJJSON of a WKT 1 or WKT 2.
+    /// Only the latter declares an area of use, as `AREA` and `BBOX` of its
+    /// `USAGE`.
+    pub fn from_wkt(crs: Crs, wkt: &str) -> Self {
+        let definition = wkt_elements(wkt)
+            .into_iter()
+            .next()
+            .map(|(_, definition)| definition)
+            .unwrap_or_default();
+        let elements = wkt_elements(definition);
+
+        let usage = elements
+            .iter()
+            .find(|(keyword, _)| keyword == "USAGE")
+            .map(|(_, usage)| wkt_elements(usage))
+            .unwrap_or_else(|| elements.to_owned());
+        let element = |name: &str| {
+            usage
+                .iter()
+                .find(|(keyword, _)| keyword == name)
+                .map(|(_, content)| *content)
+        };
+
+        // the `BBOX` is south, west, north, east in degrees
+        let bbox = element("BBOX").and_then(|bbox| {
+            let numbers: Vec<f64> = bbox
+                .split(',')
+                .map(|n| n.trim().parse())
+                .collect::<Result<_, _>>()
+                .ok()?;
+            match numbers[..] {
+                [south, west, north, east] => Some(Bbox::from([west, south, east, north])),
+                _ => None,
+            }
+        });
+        let description = element("AREA").and_then(wkt_name).map(str::to_owned);
+        let area_of_use =
+            (description.is_some() || bbox.is_some()).then_some(AreaOfUse { description, bbox });
+
+        CrsDefinition {
+            name: wkt_name(definition).map(str::to_owned),
+            area_of_use,
+            wkt: (!wkt.is_empty()).then(|| wkt.to_owned()),
+            projjson: projjson::from_wkt(wkt, &crs),
+            crs,
+        }
+    }
+}
+
+/// List of the crs supported by the server or a collection
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
+pub struct CrsDefinitions {
+    #[serde(default)]
+    pub links: Links,
+    pub crs: Vec<CrsDefinition>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::{Bbox, Crs};
+
+    use super::{AreaOfUse, CrsDefinition};
+
+    #[test]
+    fn from_wkt() {
+        let wkt = r#"PROJCRS["ETRS89-extended / LAEA Europe",BASEGEOGCRS["ETRS89",ID["EPSG",4258]],CONVERSION["Europe Equal Area 2001",METHOD["Lambert Azimuthal Equal Area"]],CS[Cartesian,2],AXIS["northing (Y)",north,LENGTHUNIT["metre",1]],AXIS["easting (X)",east,LENGTHUNIT["metre",1]],USAGE[SCOPE["Statistical analysis."],AREA["Europe - European Union (EU) countries and candidates."],BBOX[24.6,-35.58,84.73,44.83]],ID["EPSG",3035]]"#;
+        let definition = CrsDefinition::from_wkt(Crs::from_epsg(3035), wkt);
+        assert_eq!(
+            definition.name.as_deref(),
+            Some("ETRS89-extended / LAEA Europe")
+        );
+        assert_eq!(
+            definition.area_of_use,
+            Some(AreaOfUse {
+                description: Some(
+                    "Europe - European Union (EU) countries and candidates.".to_string()
+                ),
+                bbox: Some(Bbox::from([-35.58, 24.6, 44.83, 84.73]))
+            })
+        );
+        assert_eq!(definition.wkt.as_deref(), Some(wkt));
+
+        // no area of use in WKT 1
+        let wkt = r#"GEOGCS["WGS 84",DATUM["WGS_1984",SPHEROID["WGS 84",6378137,298.257223563]],AXIS["Latitude",NORTH],AXIS["Longitude",EAST],AUTHORITY["EPSG","4326"]]"#;
+        let definition = CrsDefinition::from_wkt(Crs::from_epsg(4326), wkt);
+        assert_eq!(definition.name.as_deref(), Some("WGS 84"));
+        assert_eq!(definition.area_of_use, None);
+        assert_eq!(
+            definition.projjson.as_ref().map(|p| &p["type"]),
+            Some(&"GeographicCRS".into())
+        );
+
+        let definition = CrsDefinition::from_wkt(Crs::default(), "");
+        assert_eq!(definition, CrsDefinition::new(Crs::default()));
+    }
+}
@@ -0,0 +1,52 @@
+use std::{fmt, str};
+
+use serde_json::Value;
+
+/// Opaque continuation token for keyset pagination
+///
+/// Holds the sort key values of the last feature of a page, encoded as
+/// URL safe base64 JSON array.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cursor(pub Vec<Value>);
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let json = serde_json::to_vec(&self.0).map_err(|_| fmt::Error)?;
+        write!(
+            f,
+            "{}",
+            base64::encode_config(json, base64::URL_SAFE_NO_PAD)
+        )
+    }
+}
+
+impl str::FromStr for Cursor {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let json =
+            base64::decode_config(s, base64::URL_SAFE_NO_PAD).map_err(|_| "Invalid token")?;
+        let values = serde_json::from_slice(&json).map_err(|_| "Invalid token")?;
+        Ok(Cursor(values))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::Cursor;
+
+    #[test]
+    fn roundtrip() {
+        let cursor = Cursor(vec![json!("communes"), json!("a/b?c")]);
+        let token = cursor.to_string();
+        assert!(token
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(token.parse::<Cursor>().unwrap(), cursor);
+
+        assert!("not a token".parse::<Cursor>().is_err());
+        assert!("eyJhIjoxfQ".parse::<Cursor>().is_err());
+    }
+}
@@ -2,7 +2,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_with::DisplayFromStr;
 
-use crate::common::{Bbox, Crs};
+use crate::common::{Bbox, Crs, Datetime, IntervalDatetime};
 
 #[serde_with::skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -21,13 +21,17 @@ impl Default for Extent {
 }
 
 #[serde_with::serde_as]
+#[serde_with::skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct SpatialExtent {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub bbox: Vec<Bbox>,
     #[serde(default)]
     #[serde_as(as = "DisplayFromStr")]
     pub crs: Crs,
+    /// Bounding boxes in the storage crs of the collection
+    pub storage_crs_bbox: Option<Vec<Bbox>>,
 }
 
 impl Default for SpatialExtent {
@@ -35,6 +39,7 @@ impl Default for SpatialExtent {
         Self {
             bbox: vec![Bbox::Bbox2D([-180.0, -90.0, 180.0, 90.0])],
             crs: Default::default(),
+            storage_crs_bbox: None,
         }
     }
 }
@@ -58,6 +63,71 @@ impl Default for TemporalExtent {
     }
 }
 
+impl SpatialExtent {
+    /// Whether any of the bboxes intersects a longitude/latitude `bbox`. Both
+    /// may cross the antimeridian.
+    pub fn intersects(&self, bbox: &Bbox) -> bool {
+        let parts = bbox.split_antimeridian();
+        self.bbox
+            .iter()
+            .flat_map(Bbox::split_antimeridian)
+            .any(|extent| {
+                parts.iter().any(|part| {
+                    (0..2).all(|i| {
+                        extent.lower()[i] <= part.upper()[i] && extent.upper()[i] >= part.lower()[i]
+                    })
+                })
+            })
+    }
+}
+
+impl TemporalExtent {
+    /// Whether any of the intervals intersects the `datetime`, missing bounds
+    /// are open
+    pub fn intersects(&self, datetime: &Datetime) -> bool {
+        let bound = |datetime: &IntervalDatetime| match datetime {
+            IntervalDatetime::Datetime(datetime) => Some(*datetime),
+            IntervalDatetime::Open => None,
+        };
+        let (from, to) = match datetime {
+            Datetime::Datetime(datetime) => (Some(*datetime), Some(*datetime)),
+            Datetime::Interval { from, to } => (bound(from), bound(to)),
+        };
+
+        self.interval.iter().any(|interval| {
+            let start = interval.first().copied().flatten();
+            let end = interval.get(1).copied().flatten();
+            start.zip(to).map_or(true, |(start, to)| start <= to)
+                && end.zip(from).map_or(true, |(end, from)| end >= from)
+        })
+    }
+}
+
 fn default_trs() -> String {
     "http://www.opengis.net/def/uom/ISO-8601/0/Gregorian".to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{SpatialExtent, TemporalExtent};
+    use crate::common::Bbox;
+
+    #[test]
+    fn intersects() {
+        let extent = SpatialExtent {
+            bbox: vec![Bbox::from([170.0, -20.0, -170.0, -10.0])],
+            ..Default::default()
+        };
+        assert!(extent.intersects(&Bbox::from([-175.0, -15.0, -160.0, 0.0])));
+        assert!(extent.intersects(&Bbox::from([175.0, -15.0, 0.0, -175.0, 0.0, 100.0])));
+        assert!(!extent.intersects(&Bbox::from([0.0, -15.0, 10.0, 0.0])));
+
+        let extent = TemporalExtent {
+            interval: vec![vec![Some("2020-01-01T00:00:00Z".parse().unwrap()), None]],
+            ..Default::default()
+        };
+        assert!(extent.intersects(&"2030-01-01T00:00:00Z".parse().unwrap()));
+        assert!(extent.intersects(&"../2020-06-01T00:00:00Z".parse().unwrap()));
+        assert!(!extent.intersects(&"2019-01-01T00:00:00Z/2019-12-31T00:00:00Z".parse().unwrap()));
+    }
+}
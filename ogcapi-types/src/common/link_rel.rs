@@ -6,7 +6,10 @@
 pub const ABOUT: &str = "about";
 
 /// Refers to a substitute for the link’s context.
-pub const ATERNATE: &str = "alternate";
+pub const ALTERNATE: &str = "alternate";
+
+#[deprecated(note = "use `ALTERNATE`")]
+pub const ATERNATE: &str = ALTERNATE;
 
 pub const CHILD: &str = "child";
 
@@ -66,6 +69,11 @@ pub const PREV: &str = "prev";
 /// See: <http://www.opengis.net/def/rel/ogc/1.0/processes>
 pub const PROCESSES: &str = "processes";
 
+/// The target URI points to the queryables of the context resource.
+///
+/// See: <http://www.opengis.net/def/rel/ogc/1.0/queryables>
+pub const QUERYABLES: &str = "queryables";
+
 pub const RELATED: &str = "related";
 
 /// The target URI points to the results of a job.
@@ -3,15 +3,27 @@
 /// Media Type for `application/prs.coverage+json`
 pub const COVERAGE_JSON: &str = "application/prs.coverage+json";
 
+/// Media Type for `text/csv`
+pub const CSV: &str = "text/csv";
+
+/// Media Type for `application/flatgeobuf`
+pub const FLAT_GEOBUF: &str = "application/flatgeobuf";
+
 /// Media Type for `application/geo+json`
 pub const GEO_JSON: &str = "application/geo+json";
 
+/// Media Type for `application/geo+json-seq`
+pub const GEO_JSON_SEQ: &str = "application/geo+json-seq";
+
 /// Media Type for `text/html`
 pub const HTML: &str = "text/html";
 
 /// Media Type for `application/json`
 pub const JSON: &str = "application/json";
 
+/// Media Type for `application/vnd.ogc.fg+json`
+pub const JSON_FG: &str = "application/vnd.ogc.fg+json";
+
 /// Media Type for `application/vnd.oai.openapi;version=3.0`
 pub const OPEN_API: &str = "application/vnd.oai.openapi;version=3.0";
 
@@ -30,5 +42,8 @@ pub const PNG: &str = "image/png";
 /// Media Type for `application/problem+json`
 pub const PROBLEM_JSON: &str = "application/problem+json";
 
+/// Media Type for `application/schema+json`
+pub const SCHEMA_JSON: &str = "application/schema+json";
+
 /// Media Type for `application/vnd.ogc.sld+xml;version=1.0`
 pub const SLD: &str = "application/vnd.ogc.sld+xml;version=1.0";
@@ -3,6 +3,8 @@ mod collection;
 mod collections;
 mod conformance;
 mod crs;
+mod crs_definition;
+mod cursor;
 mod datetime;
 mod exception;
 mod extent;
@@ -11,6 +13,7 @@ mod link;
 pub mod link_rel;
 mod links;
 pub mod media_type;
+mod projjson;
 mod query;
 
 pub use bbox::Bbox;
@@ -18,6 +21,8 @@ pub use collection::*;
 pub use collections::Collections;
 pub use conformance::Conformance;
 pub use crs::*;
+pub use crs_definition::{AreaOfUse, CrsDefinition, CrsDefinitions};
+pub use cursor::Cursor;
 pub use datetime::{Datetime, IntervalDatetime};
 pub use exception::Exception;
 pub use extent::*;
@@ -0,0 +1,968 @@
+//! Conversion of WKT 1 and WKT 2 definitions of geographic and projected crs to
+//! [PROJJSON](https://proj.org/specifications/projjson.html)
+//!
+//! The methods and parameters of WKT 1 projections are renamed to the ones of
+//! the EPSG, as far as they are known. A `TOWGS84` is not carried over.
+
+use std::f64::consts::PI;
+
+use serde_json::{json, Map, Value};
+
+use super::{Authority, Crs};
+
+const SCHEMA: &str = "https://proj.org/schemas/v0.5/projjson.schema.json";
+
+/// WKT 1 name of a parameter with its EPSG name and code
+type Parameter = (&'static str, &'static str, u32);
+
+/// WKT 1 name of a projection with the EPSG name and code of its method and its
+/// parameters
+type Method = (&'static str, &'static str, u32, &'static [Parameter]);
+
+/// Parameters of methods with a natural origin
+const NATURAL_ORIGIN: &[Parameter] = &[
+    ("latitude_of_origin", "Latitude of natural origin", 8801),
+    ("central_meridian", "Longitude of natural origin", 8802),
+    ("scale_factor", "Scale factor at natural origin", 8805),
+    ("false_easting", "False easting", 8806),
+    ("false_northing", "False northing", 8807),
+];
+
+/// Parameters of conic methods with a false origin
+const FALSE_ORIGIN: &[Parameter] = &[
+    ("latitude_of_origin", "Latitude of false origin", 8821),
+    ("latitude_of_center", "Latitude of false origin", 8821),
+    ("central_meridian", "Longitude of false origin", 8822),
+    ("longitude_of_center", "Longitude of false origin", 8822),
+    (
+        "standard_parallel_1",
+        "Latitude of 1st standard parallel",
+        8823,
+    ),
+    (
+        "standard_parallel_2",
+        "Latitude of 2nd standard parallel",
+        8824,
+    ),
+    ("false_easting", "Easting at false origin", 8826),
+    ("false_northing", "Northing at false origin", 8827),
+];
+
+/// Known methods of WKT 1 projections
+const METHODS: &[Method] = &[
+    (
+        "Transverse_Mercator",
+        "Transverse Mercator",
+        9807,
+        NATURAL_ORIGIN,
+    ),
+    ("Mercator_1SP", "Mercator (variant A)", 9804, NATURAL_ORIGIN),
+    (
+        "Mercator_2SP",
+        "Mercator (variant B)",
+        9805,
+        &[
+            (
+                "standard_parallel_1",
+                "Latitude of 1st standard parallel",
+                8823,
+            ),
+            ("central_meridian", "Longitude of natural origin", 8802),
+            ("false_easting", "False easting", 8806),
+            ("false_northing", "False northing", 8807),
+        ],
+    ),
+    (
+        "Lambert_Conformal_Conic_1SP",
+        "Lambert Conic Conformal (1SP)",
+        9801,
+        NATURAL_ORIGIN,
+    ),
+    (
+        "Lambert_Conformal_Conic_2SP",
+        "Lambert Conic Conformal (2SP)",
+        9802,
+        FALSE_ORIGIN,
+    ),
+    (
+        "Albers_Conic_Equal_Area",
+        "Albers Equal Area",
+        9822,
+        FALSE_ORIGIN,
+    ),
+    (
+        "Lambert_Azimuthal_Equal_Area",
+        "Lambert Azimuthal Equal Area",
+        9820,
+        &[
+            ("latitude_of_center", "Latitude of natural origin", 8801),
+            ("longitude_of_center", "Longitude of natural origin", 8802),
+            ("false_easting", "False easting", 8806),
+            ("false_northing", "False northing", 8807),
+        ],
+    ),
+    (
+        "Polar_Stereographic",
+        "Polar Stereographic (variant A)",
+        9810,
+        NATURAL_ORIGIN,
+    ),
+    (
+        "Oblique_Stereographic",
+        "Oblique Stereographic",
+        9809,
+        NATURAL_ORIGIN,
+    ),
+    ("Cassini_Soldner", "Cassini-Soldner", 9806, NATURAL_ORIGIN),
+    (
+        "Hotine_Oblique_Mercator_Azimuth_Center",
+        "Hotine Oblique Mercator (variant B)",
+        9815,
+        &[
+            ("latitude_of_center", "Latitude of projection centre", 8811),
+            (
+                "longitude_of_center",
+                "Longitude of projection centre",
+                8812,
+            ),
+            ("azimuth", "Azimuth of initial line", 8813),
+            (
+                "rectified_grid_angle",
+                "Angle from Rectified to Skew Grid",
+                8814,
+            ),
+            ("scale_factor", "Scale factor on initial line", 8815),
+            ("false_easting", "Easting at projection centre", 8816),
+            ("false_northing", "Northing at projection centre", 8817),
+        ],
+    ),
+];
+
+/// Method of the Web Mercator, which WKT 1 declares as `Mercator_1SP` on a
+/// sphere with a `PROJ4` extension
+const PSEUDO_MERCATOR: Method = (
+    "Mercator_1SP",
+    "Popular Visualisation Pseudo Mercator",
+    1024,
+    &[
+        ("latitude_of_origin", "Latitude of natural origin", 8801),
+        ("central_meridian", "Longitude of natural origin", 8802),
+        ("false_easting", "False easting", 8806),
+        ("false_northing", "False northing", 8807),
+    ],
+);
+
+/// PROJJSON of a geographic or projected crs, if the WKT defines one
+pub(crate) fn from_wkt(wkt: &str, crs: &Crs) -> Option<Value> {
+    let node = Node::parse(wkt)?;
+
+    let mut projjson = Map::new();
+    projjson.insert("$schema".to_owned(), SCHEMA.into());
+    match node.keyword.as_str() {
+        "GEOGCS" | "GEOGCRS" | "GEOGRAPHICCRS" | "GEODCRS" | "GEODETICCRS" => {
+            // the EPSG has the latitude first, if the definition does not tell
+            let latitude_first = crs.authority == Authority::EPSG;
+            projjson.extend(geographic(&node, Some(latitude_first))?);
+        }
+        "PROJCS" | "PROJCRS" | "PROJECTEDCRS" => projjson.extend(projected(&node)?),
+        _ => return None,
+    }
+
+    Some(Value::Object(projjson))
+}
+
+/// Geographic crs, with default axes for a definition without `AXIS` unless
+/// `latitude_first` is `None`
+fn geographic(node: &Node, latitude_first: Option<bool>) -> Option<Map<String, Value>> {
+    let angular = node
+        .child(&["UNIT", "ANGLEUNIT"])
+        .and_then(|u| unit(u, UnitKind::Angular))
+        .unwrap_or_else(|| "degree".into());
+
+    let mut crs = Map::new();
+    crs.insert("type".to_owned(), "GeographicCRS".into());
+    crs.insert("name".to_owned(), node.name()?.into());
+
+    if let Some(ensemble) = node.child(&["ENSEMBLE"]) {
+        crs.insert("datum_ensemble".to_owned(), datum_ensemble(ensemble)?);
+    } else {
+        let datum = node.child(&["DATUM", "GEODETICDATUM", "TRF"])?;
+        let primem = node.child(&["PRIMEM", "PRIMEMERIDIAN"]);
+        crs.insert("datum".to_owned(), self::datum(datum, primem, &angular)?);
+    }
+
+    let longitude = ("Geodetic longitude", "Lon", "east");
+    let latitude = ("Geodetic latitude", "Lat", "north");
+    let defaults = match latitude_first {
+        Some(true) => vec![latitude, longitude],
+        Some(false) => vec![longitude, latitude],
+        None => vec![],
+    };
+    if let Some(cs) = coordinate_system(node, "ellipsoidal", &angular, &defaults) {
+        // a geodetic crs may also be geocentric
+        if cs["subtype"] == "Cartesian" {
+            crs.insert("type".to_owned(), "GeodeticCRS".into());
+        }
+        crs.insert("coordinate_system".to_owned(), cs);
+    }
+
+    usages(node, &mut crs);
+    if let Some(id) = id(node) {
+        crs.insert("id".to_owned(), id);
+    }
+
+    Some(crs)
+}
+
+fn projected(node: &Node) -> Option<Map<String, Value>> {
+    let base = node.child(&["GEOGCS", "BASEGEOGCRS", "BASEGEODCRS"])?;
+    let mut base_crs = geographic(base, None)?;
+    base_crs.remove("type");
+
+    let linear = node
+        .child(&["UNIT", "LENGTHUNIT"])
+        .and_then(|u| unit(u, UnitKind::Linear))
+        .unwrap_or_else(|| "metre".into());
+
+    let conversion = match node.child(&["CONVERSION"]) {
+        Some(conversion) => self::conversion(conversion)?,
+        None => {
+            let angular = base
+                .child(&["UNIT", "ANGLEUNIT"])
+                .and_then(|u| unit(u, UnitKind::Angular))
+                .unwrap_or_else(|| "degree".into());
+            projection(node, &angular, &linear)?
+        }
+    };
+
+    let defaults = [("Easting", "E", "east"), ("Northing", "N", "north")];
+
+    let mut crs = Map::new();
+    crs.insert("type".to_owned(), "ProjectedCRS".into());
+    crs.insert("name".to_owned(), node.name()?.into());
+    crs.insert("base_crs".to_owned(), Value::Object(base_crs));
+    crs.insert("conversion".to_owned(), conversion);
+    crs.insert(
+        "coordinate_system".to_owned(),
+        coordinate_system(node, "Cartesian", &linear, &defaults)?,
+    );
+    usages(node, &mut crs);
+    if let Some(id) = id(node) {
+        crs.insert("id".to_owned(), id);
+    }
+
+    Some(crs)
+}
+
+/// Geodetic reference frame, the longitude of the prime meridian is in the unit
+/// of the crs in WKT 1
+fn datum(datum: &Node, primem: Option<&Node>, angular: &Value) -> Option<Value> {
+    let mut frame = json!({
+        "type": "GeodeticReferenceFrame",
+        "name": datum.name()?,
+        "ellipsoid": ellipsoid(datum.child(&["SPHEROID", "ELLIPSOID"])?)?,
+    });
+
+    // Greenwich is implied
+    if let Some(primem) = primem.filter(|p| p.number(1).map_or(false, |l| l != 0.0)) {
+        let mut longitude = number(primem.number(1)?);
+        let unit = match primem.child(&["ANGLEUNIT", "UNIT"]) {
+            Some(unit) => self::unit(unit, UnitKind::Angular)?,
+            None => angular.to_owned(),
+        };
+        if unit != "degree" {
+            longitude = json!({ "value": longitude, "unit": unit });
+        }
+        frame["prime_meridian"] = json!({ "name": primem.name()?, "longitude": longitude });
+    }
+    if let Some(id) = id(datum) {
+        frame["id"] = id;
+    }
+
+    Some(frame)
+}
+
+fn datum_ensemble(ensemble: &Node) -> Option<Value> {
+    let members: Vec<Value> = ensemble
+        .children("MEMBER")
+        .filter_map(|member| {
+            let mut value = json!({ "name": member.name()? });
+            if let Some(id) = id(member) {
+                value["id"] = id;
+            }
+            Some(value)
+        })
+        .collect();
+
+    let mut value = json!({
+        "name": ensemble.name()?,
+        "members": members,
+        "ellipsoid": ellipsoid(ensemble.child(&["ELLIPSOID", "SPHEROID"])?)?,
+    });
+    if let Some(accuracy) = ensemble.child(&["ENSEMBLEACCURACY"]) {
+        value["accuracy"] = accuracy.number(0)?.to_string().into();
+    }
+    if let Some(id) = id(ensemble) {
+        value["id"] = id;
+    }
+
+    Some(value)
+}
+
+fn ellipsoid(ellipsoid: &Node) -> Option<Value> {
+    let mut semi_major_axis = number(ellipsoid.number(1)?);
+    if let Some(unit) = ellipsoid.child(&["LENGTHUNIT", "UNIT"]) {
+        let unit = self::unit(unit, UnitKind::Linear)?;
+        if unit != "metre" {
+            semi_major_axis = json!({ "value": semi_major_axis, "unit": unit });
+        }
+    }
+
+    let mut value = json!({ "name": ellipsoid.name()? });
+    match ellipsoid.number(2)? {
+        // a sphere
+        f if f == 0.0 => value["radius"] = semi_major_axis,
+        f => {
+            value["semi_major_axis"] = semi_major_axis;
+            value["inverse_flattening"] = number(f);
+        }
+    }
+    if let Some(id) = id(ellipsoid) {
+        value["id"] = id;
+    }
+
+    Some(value)
+}
+
+/// Conversion of a WKT 1 `PROJECTION` and its `PARAMETER`, the angles are in
+/// the unit of the base crs and the lengths in the one of the projected crs.
+/// Parameters a known method does not have are dropped.
+fn projection(node: &Node, angular: &Value, linear: &Value) -> Option<Value> {
+    let projection = node.child(&["PROJECTION"])?.name()?;
+
+    let pseudo_mercator = node.children("EXTENSION").any(|extension| {
+        let proj4 = match extension.text(1) {
+            Some(proj4) if extension.name() == Some("PROJ4") => proj4,
+            _ => return false,
+        };
+        ["+proj=merc", "+a=6378137", "+b=6378137"]
+            .iter()
+            .all(|option| proj4.split_whitespace().any(|o| o == *option))
+    });
+    let known = if pseudo_mercator && projection.eq_ignore_ascii_case(PSEUDO_MERCATOR.0) {
+        Some(&PSEUDO_MERCATOR)
+    } else {
+        METHODS
+            .iter()
+            .find(|(name, ..)| name.eq_ignore_ascii_case(projection))
+    };
+
+    let method = match known {
+        Some((_, name, code, _)) => json!({ "name": name, "id": epsg(*code) }),
+        None => json!({ "name": projection }),
+    };
+
+    let mut parameters = Vec::new();
+    for parameter in node.children("PARAMETER") {
+        let name = parameter.name()?;
+        let value = parameter.number(1)?;
+
+        let (name, code) = match known {
+            Some((.., parameters)) => {
+                match parameters
+                    .iter()
+                    .find(|(wkt, ..)| wkt.eq_ignore_ascii_case(name))
+                {
+                    Some((_, name, code)) => (*name, Some(*code)),
+                    None => continue,
+                }
+            }
+            None => (name, None),
+        };
+
+        let unit = match parameter_kind(name, code) {
+            UnitKind::Angular => angular.to_owned(),
+            UnitKind::Linear => linear.to_owned(),
+            UnitKind::Scale => Value::from("unity"),
+        };
+
+        let mut parameter = json!({ "name": name, "value": number(value), "unit": unit });
+        if let Some(code) = code {
+            parameter["id"] = epsg(code);
+        }
+        parameters.push(parameter);
+    }
+
+    Some(json!({
+        "name": "unnamed",
+        "method": method,
+        "parameters": parameters,
+    }))
+}
+
+/// Kind of the unit of a WKT 1 parameter, by its EPSG code or else its name
+fn parameter_kind(name: &str, code: Option<u32>) -> UnitKind {
+    match code {
+        Some(8805 | 8815) => UnitKind::Scale,
+        Some(8806 | 8807 | 8816 | 8817 | 8826 | 8827) => UnitKind::Linear,
+        Some(_) => UnitKind::Angular,
+        None => {
+            let name = name.to_lowercase();
+            if name.contains("scale") {
+                UnitKind::Scale
+            } else if name.starts_with("false_")
+                || name.contains("easting")
+                || name.contains("northing")
+            {
+                UnitKind::Linear
+            } else {
+                UnitKind::Angular
+            }
+        }
+    }
+}
+
+/// WKT 2 `CONVERSION` with its `METHOD` and `PARAMETER`
+fn conversion(conversion: &Node) -> Option<Value> {
+    let method = conversion.child(&["METHOD", "PROJECTION"])?;
+    let mut method_value = json!({ "name": method.name()? });
+    if let Some(id) = id(method) {
+        method_value["id"] = id;
+    }
+
+    let mut parameters = Vec::new();
+    for parameter in conversion.children("PARAMETER") {
+        let mut value = json!({
+            "name": parameter.name()?,
+            "value": number(parameter.number(1)?),
+        });
+        if let Some(unit) = parameter
+            .child(&["ANGLEUNIT", "LENGTHUNIT", "SCALEUNIT", "UNIT"])
+            .and_then(|u| unit(u, UnitKind::Scale))
+        {
+            value["unit"] = unit;
+        }
+        if let Some(id) = id(parameter) {
+            value["id"] = id;
+        }
+        parameters.push(value);
+    }
+
+    Some(json!({
+        "name": conversion.name()?,
+        "method": method_value,
+        "parameters": parameters,
+    }))
+}
+
+/// Coordinate system of the `CS` and `AXIS` of a crs, the axes without a unit
+/// of their own are in the one of the crs
+fn coordinate_system(
+    node: &Node,
+    subtype: &str,
+    unit: &Value,
+    defaults: &[(&str, &str, &str)],
+) -> Option<Value> {
+    let subtype = node.child(&["CS"]).and_then(Node::word).unwrap_or(subtype);
+    let kind = if subtype == "ellipsoidal" {
+        UnitKind::Angular
+    } else {
+        UnitKind::Linear
+    };
+
+    let mut axes = Vec::new();
+    for axis in node.children("AXIS") {
+        let (name, abbreviation) = axis_name(axis.name()?);
+        let direction = axis.word()?;
+        // WKT 1 has the direction in upper case, WKT 2 in camel case
+        let direction = if direction.chars().all(|c| c.is_ascii_uppercase()) {
+            direction.to_lowercase()
+        } else {
+            direction.to_owned()
+        };
+        let unit = axis
+            .child(&["ANGLEUNIT", "LENGTHUNIT", "SCALEUNIT", "UNIT"])
+            .and_then(|u| self::unit(u, kind))
+            .unwrap_or_else(|| unit.to_owned());
+
+        axes.push(json!({
+            "name": name,
+            "abbreviation": abbreviation,
+            "direction": direction,
+            "unit": unit,
+        }));
+    }
+
+    if axes.is_empty() {
+        axes = defaults
+            .iter()
+            .map(|(name, abbreviation, direction)| {
+                json!({
+                    "name": name,
+                    "abbreviation": abbreviation,
+                    "direction": direction,
+                    "unit": unit,
+                })
+            })
+            .collect();
+    }
+    if axes.is_empty() {
+        return None;
+    }
+
+    Some(json!({ "subtype": subtype, "axis": axes }))
+}
+
+/// Name and abbreviation of an axis, WKT 2 appends the abbreviation in
+/// parentheses, e.g. `geodetic latitude (Lat)`
+fn axis_name(name: &str) -> (String, String) {
+    let (name, abbreviation) = match name.strip_suffix(')').and_then(|n| n.rsplit_once('(')) {
+        Some((name, abbreviation)) => (name.trim(), Some(abbreviation)),
+        None => (name, None),
+    };
+
+    match abbreviation {
+        // only the abbreviation, e.g. `(E)`
+        Some(abbreviation) if name.is_empty() => {
+            (axis_name(abbreviation).0, abbreviation.to_owned())
+        }
+        Some(abbreviation) => (capitalize(name), abbreviation.to_owned()),
+        None => {
+            let (name, abbreviation) = match name.to_lowercase().as_str() {
+                "latitude" | "lat" | "geodetic latitude" => ("Geodetic latitude", "Lat"),
+                "longitude" | "lon" | "long" | "geodetic longitude" => {
+                    ("Geodetic longitude", "Lon")
+                }
+                "ellipsoidal height" | "height" | "h" => ("Ellipsoidal height", "h"),
+                "easting" | "x" | "e" => ("Easting", "E"),
+                "northing" | "y" | "n" => ("Northing", "N"),
+                _ => (name, name),
+            };
+            (name.to_owned(), abbreviation.to_owned())
+        }
+    }
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UnitKind {
+    Angular,
+    Linear,
+    Scale,
+}
+
+/// Unit of a `UNIT`, whose kind follows from the context in WKT 1, with the
+/// shorthands of PROJJSON for degree, metre and unity
+fn unit(unit: &Node, kind: UnitKind) -> Option<Value> {
+    let name = unit.name()?;
+    let factor = unit.number(1)?;
+
+    let kind = match unit.keyword.as_str() {
+        "ANGLEUNIT" => UnitKind::Angular,
+        "LENGTHUNIT" => UnitKind::Linear,
+        "SCALEUNIT" => UnitKind::Scale,
+        _ => kind,
+    };
+    let (shorthand, base, r#type) = match kind {
+        UnitKind::Angular => ("degree", PI / 180.0, "AngularUnit"),
+        UnitKind::Linear => ("metre", 1.0, "LinearUnit"),
+        UnitKind::Scale => ("unity", 1.0, "ScaleUnit"),
+    };
+
+    let name_matches = name.eq_ignore_ascii_case(shorthand)
+        || (kind == UnitKind::Linear && name.eq_ignore_ascii_case("meter"));
+    if name_matches && ((factor - base) / base).abs() < 1e-12 {
+        return Some(shorthand.into());
+    }
+
+    Some(json!({
+        "type": r#type,
+        "name": name,
+        "conversion_factor": number(factor),
+    }))
+}
+
+/// Scope, area and bounding box of the `USAGE` of WKT 2, or of the WKT 2015
+/// which has them directly in the crs
+fn usages(node: &Node, crs: &mut Map<String, Value>) {
+    let mut usages: Vec<Map<String, Value>> = node
+        .children("USAGE")
+        .map(usage)
+        .filter(|usage| !usage.is_empty())
+        .collect();
+    if usages.is_empty() {
+        usages.push(usage(node));
+    }
+
+    match usages.len() {
+        1 => crs.extend(usages.remove(0)),
+        _ => {
+            let usages = usages.into_iter().map(Value::Object).collect();
+            crs.insert("usages".to_owned(), Value::Array(usages));
+        }
+    }
+}
+
+fn usage(node: &Node) -> Map<String, Value> {
+    let mut usage = Map::new();
+    if let Some(scope) = node.child(&["SCOPE"]).and_then(Node::name) {
+        usage.insert("scope".to_owned(), scope.into());
+    }
+    if let Some(area) = node.child(&["AREA"]).and_then(Node::name) {
+        usage.insert("area".to_owned(), area.into());
+    }
+    // the `BBOX` is south, west, north, east in degrees
+    if let Some(bbox) = node.child(&["BBOX"]) {
+        if let (Some(south), Some(west), Some(north), Some(east)) = (
+            bbox.number(0),
+            bbox.number(1),
+            bbox.number(2),
+            bbox.number(3),
+        ) {
+            usage.insert(
+                "bbox".to_owned(),
+                json!({
+                    "south_latitude": number(south),
+                    "west_longitude": number(west),
+                    "north_latitude": number(north),
+                    "east_longitude": number(east),
+                }),
+            );
+        }
+    }
+    usage
+}
+
+/// Identifier of an `AUTHORITY` or `ID`
+fn id(node: &Node) -> Option<Value> {
+    let id = node.child(&["ID", "AUTHORITY"])?;
+    let code = match id.values.get(1)? {
+        Item::Number(code) => number(*code),
+        Item::Text(code) => code
+            .parse::<i64>()
+            .map(Value::from)
+            .unwrap_or_else(|_| Value::from(*code)),
+        _ => return None,
+    };
+
+    Some(json!({ "authority": id.name()?, "code": code }))
+}
+
+fn epsg(code: u32) -> Value {
+    json!({ "authority": "EPSG", "code": code })
+}
+
+/// Integral numbers without a fraction, like PROJ writes them
+fn number(n: f64) -> Value {
+    if n.fract() == 0.0 && n.abs() < 1e15 {
+        Value::from(n as i64)
+    } else {
+        Value::from(n)
+    }
+}
+
+/// Element of a WKT with its keyword and values
+#[derive(Debug)]
+struct Node<'a> {
+    keyword: String,
+    values: Vec<Item<'a>>,
+}
+
+#[derive(Debug)]
+enum Item<'a> {
+    Text(&'a str),
+    Number(f64),
+    Word(&'a str),
+    Node(Node<'a>),
+}
+
+impl<'a> Node<'a> {
+    fn parse(wkt: &'a str) -> Option<Node<'a>> {
+        let wkt = wkt.trim();
+        let open = wkt.find(|c: char| c == '[' || c == '(')?;
+        let content = wkt[open + 1..]
+            .strip_suffix(|c: char| c == ']' || c == ')')?
+            .trim();
+
+        let keyword = wkt[..open].trim().to_uppercase();
+        if keyword.is_empty()
+            || !keyword
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return None;
+        }
+
+        let values = split(content)
+            .into_iter()
+            .filter(|value| !value.is_empty())
+            .map(Item::parse)
+            .collect::<Option<_>>()?;
+
+        Some(Node { keyword, values })
+    }
+
+    fn nodes(&self) -> impl Iterator<Item = &Node<'a>> {
+        self.values.iter().filter_map(|item| match item {
+            Item::Node(node) => Some(node),
+            _ => None,
+        })
+    }
+
+    fn child(&self, keywords: &[&str]) -> Option<&Node<'a>> {
+        self.nodes()
+            .find(|node| keywords.contains(&node.keyword.as_str()))
+    }
+
+    fn children<'b>(&'b self, keyword: &'b str) -> impl Iterator<Item = &'b Node<'a>> {
+        self.nodes().filter(move |node| node.keyword == keyword)
+    }
+
+    /// Quoted value at the start, e.g. the name of a crs
+    fn name(&self) -> Option<&'a str> {
+        self.text(0)
+    }
+
+    fn text(&self, i: usize) -> Option<&'a str> {
+        match self.values.get(i)? {
+            Item::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    fn number(&self, i: usize) -> Option<f64> {
+        match self.values.get(i)? {
+            Item::Number(n) => Some(*n),
+            Item::Text(text) => text.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// First unquoted value, e.g. the direction of an axis
+    fn word(&self) -> Option<&'a str> {
+        self.values.iter().find_map(|item| match item {
+            Item::Word(word) => Some(*word),
+            _ => None,
+        })
+    }
+}
+
+impl<'a> Item<'a> {
+    fn parse(value: &'a str) -> Option<Item<'a>> {
+        if let Some(text) = value.strip_prefix('"') {
+            text.strip_suffix('"').map(Item::Text)
+        } else if value.ends_with(|c: char| c == ']' || c == ')') {
+            Node::parse(value).map(Item::Node)
+        } else if let Ok(n) = value.parse() {
+            Some(Item::Number(n))
+        } else {
+            Some(Item::Word(value))
+        }
+    }
+}
+
+/// Values of a WKT content, separated by the commas outside of quotes and
+/// nested elements
+fn split(content: &str) -> Vec<&str> {
+    let mut values = Vec::new();
+    let mut depth = 0usize;
+    let mut quoted = false;
+    let mut start = 0;
+
+    for (i, c) in content.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '[' | '(' if !quoted => depth += 1,
+            ']' | ')' if !quoted => depth = depth.saturating_sub(1),
+            ',' if !quoted && depth == 0 => {
+                values.push(content[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    values.push(content[start..].trim());
+
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::common::Crs;
+
+    use super::{from_wkt, number};
+
+    #[test]
+    fn projected_wkt1() {
+        let wkt = r#"PROJCS["CH1903+ / LV95",GEOGCS["CH1903+",DATUM["CH1903+",SPHEROID["Bessel 1841",6377397.155,299.1528128,AUTHORITY["EPSG","7004"]],TOWGS84[674.374,15.056,405.346,0,0,0,0],AUTHORITY["EPSG","6150"]],PRIMEM["Greenwich",0,AUTHORITY["EPSG","8901"]],UNIT["degree",0.0174532925199433,AUTHORITY["EPSG","9122"]],AUTHORITY["EPSG","4150"]],PROJECTION["Hotine_Oblique_Mercator_Azimuth_Center"],PARAMETER["latitude_of_center",46.95240555555556],PARAMETER["longitude_of_center",7.439583333333333],PARAMETER["azimuth",90],PARAMETER["rectified_grid_angle",90],PARAMETER["scale_factor",1],PARAMETER["false_easting",2600000],PARAMETER["false_northing",1200000],UNIT["metre",1,AUTHORITY["EPSG","9001"]],AXIS["Easting",EAST],AXIS["Northing",NORTH],AUTHORITY["EPSG","2056"]]"#;
+
+        let parameter = |name: &str, value: f64, unit: &str, code: u32| {
+            json!({
+                "name": name,
+                "value": number(value),
+                "unit": unit,
+                "id": { "authority": "EPSG", "code": code }
+            })
+        };
+
+        assert_eq!(
+            from_wkt(wkt, &Crs::from_epsg(2056)),
+            Some(json!({
+                "$schema": "https://proj.org/schemas/v0.5/projjson.schema.json",
+                "type": "ProjectedCRS",
+                "name": "CH1903+ / LV95",
+                "base_crs": {
+                    "name": "CH1903+",
+                    "datum": {
+                        "type": "GeodeticReferenceFrame",
+                        "name": "CH1903+",
+                        "ellipsoid": {
+                            "name": "Bessel 1841",
+                            "semi_major_axis": 6377397.155,
+                            "inverse_flattening": 299.1528128,
+                            "id": { "authority": "EPSG", "code": 7004 }
+                        },
+                        "id": { "authority": "EPSG", "code": 6150 }
+                    },
+                    "id": { "authority": "EPSG", "code": 4150 }
+                },
+                "conversion": {
+                    "name": "unnamed",
+                    "method": {
+                        "name": "Hotine Oblique Mercator (variant B)",
+                        "id": { "authority": "EPSG", "code": 9815 }
+                    },
+                    "parameters": [
+                        parameter("Latitude of projection centre", 46.95240555555556, "degree", 8811),
+                        parameter("Longitude of projection centre", 7.439583333333333, "degree", 8812),
+                        parameter("Azimuth of initial line", 90.0, "degree", 8813),
+                        parameter("Angle from Rectified to Skew Grid", 90.0, "degree", 8814),
+                        parameter("Scale factor on initial line", 1.0, "unity", 8815),
+                        parameter("Easting at projection centre", 2600000.0, "metre", 8816),
+                        parameter("Northing at projection centre", 1200000.0, "metre", 8817),
+                    ]
+                },
+                "coordinate_system": {
+                    "subtype": "Cartesian",
+                    "axis": [
+                        { "name": "Easting", "abbreviation": "E", "direction": "east", "unit": "metre" },
+                        { "name": "Northing", "abbreviation": "N", "direction": "north", "unit": "metre" }
+                    ]
+                },
+                "id": { "authority": "EPSG", "code": 2056 }
+            }))
+        );
+    }
+
+    #[test]
+    fn pseudo_mercator() {
+        let wkt = r#"PROJCS["WGS 84 / Pseudo-Mercator",GEOGCS["WGS 84",DATUM["WGS_1984",SPHEROID["WGS 84",6378137,298.257223563,AUTHORITY["EPSG","7030"]],AUTHORITY["EPSG","6326"]],PRIMEM["Greenwich",0,AUTHORITY["EPSG","8901"]],UNIT["degree",0.0174532925199433,AUTHORITY["EPSG","9122"]],AUTHORITY["EPSG","4326"]],PROJECTION["Mercator_1SP"],PARAMETER["central_meridian",0],PARAMETER["scale_factor",1],PARAMETER["false_easting",0],PARAMETER["false_northing",0],UNIT["metre",1,AUTHORITY["EPSG","9001"]],AXIS["X",EAST],AXIS["Y",NORTH],EXTENSION["PROJ4","+proj=merc +a=6378137 +b=6378137 +lat_ts=0.0 +lon_0=0.0 +x_0=0.0 +y_0=0 +k=1.0 +units=m +nadgrids=@null +wktext +no_defs"],AUTHORITY["EPSG","3857"]]"#;
+        let projjson = from_wkt(wkt, &Crs::from_epsg(3857)).unwrap();
+
+        let conversion = &projjson["conversion"];
+        assert_eq!(
+            conversion["method"]["name"],
+            "Popular Visualisation Pseudo Mercator"
+        );
+        let parameters: Vec<&str> = conversion["parameters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            parameters,
+            [
+                "Longitude of natural origin",
+                "False easting",
+                "False northing"
+            ]
+        );
+        assert_eq!(projjson["coordinate_system"]["axis"][0]["name"], "Easting");
+
+        // the same without the extension is a Mercator on the ellipsoid
+        let wkt = wkt.replace(
+            r#"EXTENSION["PROJ4","+proj=merc"#,
+            r#"EXTENSION["PROJ4","+proj=x"#,
+        );
+        let projjson = from_wkt(&wkt, &Crs::from_epsg(3857)).unwrap();
+        assert_eq!(
+            projjson["conversion"]["method"]["name"],
+            "Mercator (variant A)"
+        );
+    }
+
+    #[test]
+    fn geographic() {
+        // WKT 1 without axes, which are latitude first for the EPSG
+        let wkt = r#"GEOGCS["WGS 84",DATUM["WGS_1984",SPHEROID["WGS 84",6378137,298.257223563,AUTHORITY["EPSG","7030"]],AUTHORITY["EPSG","6326"]],PRIMEM["Greenwich",0,AUTHORITY["EPSG","8901"]],UNIT["degree",0.0174532925199433,AUTHORITY["EPSG","9122"]],AUTHORITY["EPSG","4326"]]"#;
+        let projjson = from_wkt(wkt, &Crs::from_epsg(4326)).unwrap();
+        assert_eq!(projjson["type"], "GeographicCRS");
+        assert_eq!(projjson["datum"]["ellipsoid"]["semi_major_axis"], 6378137);
+        assert_eq!(projjson["datum"].get("prime_meridian"), None);
+        assert_eq!(
+            projjson["coordinate_system"],
+            json!({
+                "subtype": "ellipsoidal",
+                "axis": [
+                    { "name": "Geodetic latitude", "abbreviation": "Lat", "direction": "north", "unit": "degree" },
+                    { "name": "Geodetic longitude", "abbreviation": "Lon", "direction": "east", "unit": "degree" }
+                ]
+            })
+        );
+
+        // WKT 2 with axes, usage and a prime meridian other than Greenwich
+        let wkt = r#"GEOGCRS["NTF (Paris)",DATUM["Nouvelle Triangulation Francaise (Paris)",ELLIPSOID["Clarke 1880 (IGN)",6378249.2,293.466021293627,LENGTHUNIT["metre",1]]],PRIMEM["Paris",2.5969213,ANGLEUNIT["grad",0.0157079632679489]],CS[ellipsoidal,2],AXIS["geodetic latitude (Lat)",north,ORDER[1]],AXIS["geodetic longitude (Lon)",east,ORDER[2]],ANGLEUNIT["grad",0.0157079632679489],USAGE[SCOPE["Geodesy."],AREA["France."],BBOX[41.31,-4.87,51.14,9.63]],ID["EPSG",4807]]"#;
+        let projjson = from_wkt(wkt, &Crs::from_epsg(4807)).unwrap();
+        let grad = json!({
+            "type": "AngularUnit",
+            "name": "grad",
+            "conversion_factor": 0.0157079632679489
+        });
+        assert_eq!(
+            projjson["datum"]["prime_meridian"]["longitude"],
+            json!({ "value": 2.5969213, "unit": grad })
+        );
+        assert_eq!(projjson["coordinate_system"]["axis"][0]["unit"], grad);
+        assert_eq!(
+            projjson["coordinate_system"]["axis"][1]["name"],
+            "Geodetic longitude"
+        );
+        assert_eq!(projjson["area"], "France.");
+        assert_eq!(projjson["bbox"]["west_longitude"], -4.87);
+        assert_eq!(projjson["id"], json!({ "authority": "EPSG", "code": 4807 }));
+
+        // height with its own unit
+        let wkt = r#"GEOGCRS["WGS 84 (CRS84h)",DATUM["World Geodetic System 1984",ELLIPSOID["WGS 84",6378137,298.257223563,LENGTHUNIT["metre",1]]],PRIMEM["Greenwich",0,ANGLEUNIT["degree",0.0174532925199433]],CS[ellipsoidal,3],AXIS["geodetic longitude (Lon)",east,ORDER[1],ANGLEUNIT["degree",0.0174532925199433]],AXIS["geodetic latitude (Lat)",north,ORDER[2],ANGLEUNIT["degree",0.0174532925199433]],AXIS["ellipsoidal height (h)",up,ORDER[3],LENGTHUNIT["metre",1]],ID["OGC","CRS84h"]]"#;
+        let projjson = from_wkt(wkt, &Crs::default()).unwrap();
+        let axes = projjson["coordinate_system"]["axis"].as_array().unwrap();
+        assert_eq!(axes[0]["name"], "Geodetic longitude");
+        assert_eq!(axes[2]["unit"], "metre");
+        assert_eq!(
+            projjson["id"],
+            json!({ "authority": "OGC", "code": "CRS84h" })
+        );
+    }
+
+    #[test]
+    fn unsupported() {
+        let wkt = r#"VERT_CS["EGM96 height",VERT_DATUM["EGM96 geoid",2005],UNIT["metre",1],AXIS["Up",UP],AUTHORITY["EPSG","5773"]]"#;
+        assert_eq!(from_wkt(wkt, &Crs::from_epsg(5773)), None);
+
+        // no datum
+        let wkt = r#"PROJCRS["ETRS89-extended / LAEA Europe",BASEGEOGCRS["ETRS89",ID["EPSG",4258]],CONVERSION["Europe Equal Area 2001",METHOD["Lambert Azimuthal Equal Area"]],CS[Cartesian,2],ID["EPSG",3035]]"#;
+        assert_eq!(from_wkt(wkt, &Crs::from_epsg(3035)), None);
+
+        assert_eq!(from_wkt("", &Crs::default()), None);
+        assert_eq!(from_wkt("GEOGCS[", &Crs::default()), None);
+    }
+}
@@ -1,10 +1,11 @@
-use serde::Deserialize;
-use serde_with::DisplayFromStr;
+use serde::{Deserialize, Serialize};
+use serde_with::{formats::CommaSeparator, DisplayFromStr, StringWithSeparator};
 
 use crate::common::{Bbox, Crs, Datetime};
 
 #[serde_with::serde_as]
-#[derive(Deserialize, Debug, Clone)]
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[serde(deny_unknown_fields, rename_all = "kebab-case")]
 pub struct Query {
     #[serde(default)]
@@ -16,7 +17,11 @@ pub struct Query {
     #[serde(default)]
     #[serde_as(as = "Option<DisplayFromStr>")]
     pub datetime: Option<Datetime>,
-    pub limit: Option<isize>,
-    pub offset: Option<isize>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+    /// Terms to match in the title, description or keywords, e.g. `lakes,rivers`
+    #[serde(default)]
+    #[serde_as(as = "Option<StringWithSeparator::<CommaSeparator, String>>")]
+    pub q: Option<Vec<String>>,
     pub f: Option<String>,
 }
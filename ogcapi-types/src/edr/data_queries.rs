@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::common::Link;
+use crate::common::{CrsDefinition, Link};
 
 use super::QueryType;
 
@@ -44,3 +44,12 @@ pub struct CrsObject {
     /// Well Known text description of the coordinate reference system
     pub wkt: String,
 }
+
+impl From<&CrsDefinition> for CrsObject {
+    fn from(definition: &CrsDefinition) -> Self {
+        CrsObject {
+            crs: definition.crs.to_string(),
+            wkt: definition.wkt.to_owned().unwrap_or_default(),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+/// How features of a bulk upload are written
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BulkMode {
+    /// Fail for features with an id that already exists
+    Insert,
+    /// Replace features with an id that already exists
+    Upsert,
+}
+
+impl Default for BulkMode {
+    fn default() -> Self {
+        BulkMode::Insert
+    }
+}
+
+/// Outcome of a bulk upload
+///
+/// The upload is all or nothing, the `ids` of the written features are only
+/// reported if there are no `errors`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct BulkReport {
+    #[serde(default)]
+    pub ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<BulkError>,
+}
+
+/// Error of a single feature in a bulk upload
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BulkError {
+    /// Position of the feature in the upload
+    pub index: usize,
+    pub id: Option<String>,
+    pub message: String,
+}
+
+impl BulkError {
+    pub fn new(index: usize, id: Option<&str>, message: impl ToString) -> Self {
+        BulkError {
+            index,
+            id: id.map(ToOwned::to_owned),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl BulkReport {
+    /// Returns `true` if the upload was written
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
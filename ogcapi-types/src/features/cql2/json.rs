@@ -0,0 +1,297 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate};
+use geojson::Geometry;
+use serde_json::{Map, Value};
+
+use super::{validate_geometry, ComparisonOp, Expr, SpatialOp, TemporalOp};
+
+impl TryFrom<&Value> for Expr {
+    type Error = String;
+
+    /// Convert a CQL2-JSON encoded filter expression
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Bool(b) => Ok(Expr::Bool(*b)),
+            Value::Number(n) => n
+                .as_f64()
+                .map(Expr::Number)
+                .ok_or_else(|| format!("Invalid number `{n}`")),
+            Value::String(s) => Ok(Expr::String(s.to_owned())),
+            Value::Object(object) => object_expr(object),
+            Value::Null => Err("Unexpected `null`".to_string()),
+            Value::Array(_) => Err(format!("Unexpected array `{value}`")),
+        }
+    }
+}
+
+fn object_expr(object: &Map<String, Value>) -> Result<Expr, String> {
+    if let Some(op) = object.get("op") {
+        let op = op
+            .as_str()
+            .ok_or_else(|| format!("Expected string for `op` but found `{op}`"))?;
+        let args = match object.get("args") {
+            Some(Value::Array(args)) => args.as_slice(),
+            Some(args) => return Err(format!("Expected array for `args` but found `{args}`")),
+            None => &[],
+        };
+        return operation(op, args);
+    }
+
+    if let Some(property) = object.get("property") {
+        return property
+            .as_str()
+            .map(|p| Expr::Property(p.to_owned()))
+            .ok_or_else(|| format!("Expected string for `property` but found `{property}`"));
+    }
+
+    if let Some(timestamp) = object.get("timestamp") {
+        let s = timestamp.as_str().unwrap_or_default();
+        let timestamp = DateTime::parse_from_rfc3339(s)
+            .map_err(|e| format!("Invalid timestamp `{timestamp}`: {e}"))?;
+        return Ok(Expr::Timestamp(timestamp.into()));
+    }
+
+    if let Some(date) = object.get("date") {
+        let s = date.as_str().unwrap_or_default();
+        let date = NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .map_err(|e| format!("Invalid date `{date}`: {e}"))?;
+        return Ok(Expr::Date(date));
+    }
+
+    if let Some(interval) = object.get("interval") {
+        return match interval.as_array().map(|a| a.as_slice()) {
+            Some([start, end]) => Ok(Expr::Interval(
+                Box::new(Expr::try_from(start)?),
+                Box::new(Expr::try_from(end)?),
+            )),
+            _ => Err(format!(
+                "Expected two elements in `interval` but found `{interval}`"
+            )),
+        };
+    }
+
+    if let Some(bbox) = object.get("bbox") {
+        let numbers = bbox
+            .as_array()
+            .and_then(|a| a.iter().map(|n| n.as_f64()).collect::<Option<Vec<f64>>>())
+            .ok_or_else(|| format!("Expected array of numbers for `bbox` but found `{bbox}`"))?;
+        if numbers.len() != 4 && numbers.len() != 6 {
+            return Err("Expected 4 or 6 numbers in `bbox`".to_string());
+        }
+        return Ok(Expr::Bbox(numbers));
+    }
+
+    if object.contains_key("type") {
+        let geometry = Geometry::from_json_object(object.to_owned())
+            .map_err(|e| format!("Invalid geometry: {e}"))?;
+        validate_geometry(&geometry.value).map_err(|e| format!("Invalid geometry: {e}"))?;
+        return Ok(Expr::Geometry(geometry));
+    }
+
+    Err(format!(
+        "Unexpected object `{}`",
+        Value::Object(object.to_owned())
+    ))
+}
+
+fn operation(op: &str, args: &[Value]) -> Result<Expr, String> {
+    let lowercase = op.to_lowercase();
+    match lowercase.as_str() {
+        "and" | "or" => {
+            if args.len() < 2 {
+                return Err(format!("Expected at least two arguments for `{op}`"));
+            }
+            let exprs = args
+                .iter()
+                .map(Expr::try_from)
+                .collect::<Result<Vec<Expr>, String>>()?;
+            if lowercase == "and" {
+                Ok(Expr::And(exprs))
+            } else {
+                Ok(Expr::Or(exprs))
+            }
+        }
+        "not" => {
+            let [a] = arguments::<1>(op, args)?;
+            Ok(Expr::Not(Box::new(a)))
+        }
+        "=" | "<>" | "<" | "<=" | ">" | ">=" => {
+            let [a, b] = arguments::<2>(op, args)?;
+            Ok(Expr::Comparison(
+                ComparisonOp::from_str(op)?,
+                Box::new(a),
+                Box::new(b),
+            ))
+        }
+        "like" => {
+            let [a, b] = arguments::<2>(op, args)?;
+            Ok(Expr::Like(Box::new(a), Box::new(b)))
+        }
+        "between" => {
+            let [a, low, high] = arguments::<3>(op, args)?;
+            Ok(Expr::Between(Box::new(a), Box::new(low), Box::new(high)))
+        }
+        "in" => match args {
+            [a, Value::Array(list)] => Ok(Expr::In(
+                Box::new(Expr::try_from(a)?),
+                list.iter()
+                    .map(Expr::try_from)
+                    .collect::<Result<Vec<Expr>, String>>()?,
+            )),
+            _ => Err("Expected an expression and a list as arguments for `in`".to_string()),
+        },
+        "isnull" => {
+            let [a] = arguments::<1>(op, args)?;
+            Ok(Expr::IsNull(Box::new(a)))
+        }
+        s if s.starts_with("s_") => {
+            let [a, b] = arguments::<2>(op, args)?;
+            Ok(Expr::Spatial(
+                SpatialOp::from_str(op)?,
+                Box::new(a),
+                Box::new(b),
+            ))
+        }
+        s if s.starts_with("t_") => {
+            let [a, b] = arguments::<2>(op, args)?;
+            Ok(Expr::Temporal(
+                TemporalOp::from_str(op)?,
+                Box::new(a),
+                Box::new(b),
+            ))
+        }
+        _ => Err(format!("Unsupported operator `{op}`")),
+    }
+}
+
+fn arguments<const N: usize>(op: &str, args: &[Value]) -> Result<[Expr; N], String> {
+    if args.len() != N {
+        return Err(format!(
+            "Expected {N} argument(s) for `{op}` but found {}",
+            args.len()
+        ));
+    }
+    let exprs = args
+        .iter()
+        .map(Expr::try_from)
+        .collect::<Result<Vec<Expr>, String>>()?;
+    Ok(exprs.try_into().expect("length checked above"))
+}
+
+#[cfg(test)]
+mod tests {
+    use geojson::Value as GeoJsonValue;
+    use serde_json::json;
+
+    use crate::features::cql2::{ComparisonOp, Expr, SpatialOp, TemporalOp};
+
+    #[test]
+    fn comparison() {
+        let expr = Expr::try_from(&json!({
+            "op": "<=",
+            "args": [{ "property": "eo:cloud_cover" }, 10.5]
+        }))
+        .unwrap();
+        assert_eq!(
+            expr,
+            Expr::Comparison(
+                ComparisonOp::Lte,
+                Box::new(Expr::Property("eo:cloud_cover".to_string())),
+                Box::new(Expr::Number(10.5))
+            )
+        );
+    }
+
+    #[test]
+    fn same_as_text() {
+        let json = Expr::try_from(&json!({
+            "op": "and",
+            "args": [
+                { "op": "like", "args": [{ "property": "name" }, "Ber%"] },
+                { "op": "in", "args": [{ "property": "ISO_A2" }, ["CH", "AT"]] },
+                { "op": "not", "args": [{ "op": "isNull", "args": [{ "property": "pop" }] }] },
+                { "op": "between", "args": [{ "property": "pop" }, 100, 1000] }
+            ]
+        }))
+        .unwrap();
+        let text: Expr = "name LIKE 'Ber%' AND ISO_A2 IN ('CH', 'AT') \
+            AND pop IS NOT NULL AND pop BETWEEN 100 AND 1000"
+            .parse()
+            .unwrap();
+        assert_eq!(json, text);
+    }
+
+    #[test]
+    fn spatial() {
+        let expr = Expr::try_from(&json!({
+            "op": "s_intersects",
+            "args": [
+                { "property": "geometry" },
+                { "type": "Point", "coordinates": [7.0, 46.0] }
+            ]
+        }))
+        .unwrap();
+        match expr {
+            Expr::Spatial(SpatialOp::Intersects, _, b) => match *b {
+                Expr::Geometry(g) => assert!(matches!(g.value, GeoJsonValue::Point(_))),
+                _ => panic!("expected geometry"),
+            },
+            _ => panic!("expected spatial predicate"),
+        }
+
+        let expr = Expr::try_from(&json!({
+            "op": "s_within",
+            "args": [{ "property": "geometry" }, { "bbox": [6, 45, 9, 49] }]
+        }))
+        .unwrap();
+        assert!(
+            matches!(expr, Expr::Spatial(SpatialOp::Within, _, b) if *b == Expr::Bbox(vec![6.0, 45.0, 9.0, 49.0]))
+        );
+    }
+
+    #[test]
+    fn temporal() {
+        let expr = Expr::try_from(&json!({
+            "op": "t_intersects",
+            "args": [
+                { "property": "datetime" },
+                { "interval": ["2020-01-01T00:00:00Z", ".."] }
+            ]
+        }))
+        .unwrap();
+        match expr {
+            Expr::Temporal(TemporalOp::Intersects, _, b) => match *b {
+                Expr::Interval(_, end) => assert!(end.is_open()),
+                _ => panic!("expected interval"),
+            },
+            _ => panic!("expected temporal predicate"),
+        }
+
+        let expr = Expr::try_from(&json!({
+            "op": ">",
+            "args": [{ "property": "updated" }, { "timestamp": "2020-01-01T00:00:00Z" }]
+        }))
+        .unwrap();
+        assert!(
+            matches!(expr, Expr::Comparison(ComparisonOp::Gt, _, b) if matches!(*b, Expr::Timestamp(_)))
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Expr::try_from(&json!({ "op": "=", "args": [1] })).is_err());
+        assert!(Expr::try_from(&json!({ "op": "foo", "args": [1, 2] })).is_err());
+        assert!(Expr::try_from(&json!({ "op": "and", "args": [true] })).is_err());
+        assert!(Expr::try_from(&json!({ "op": "in", "args": [1, 2] })).is_err());
+        assert!(Expr::try_from(&json!({ "date": "01.01.2020" })).is_err());
+        assert!(Expr::try_from(&json!({ "type": "Point" })).is_err());
+        assert!(Expr::try_from(&json!({ "type": "Point", "coordinates": [7.0] })).is_err());
+        assert!(Expr::try_from(&json!({
+            "type": "Polygon",
+            "coordinates": [[[6, 45], [6, 49], [9, 49], [9, 45]]]
+        }))
+        .is_err());
+        assert!(Expr::try_from(&json!(null)).is_err());
+    }
+}
@@ -0,0 +1,306 @@
+//! Common Query Language (CQL2)
+//!
+//! Expression tree for filters as specified in
+//! [OGC CQL2](https://docs.ogc.org/DRAFTS/21-065.html).
+mod json;
+mod text;
+
+use std::{fmt, str};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use geojson::{Geometry, Position, Value};
+
+use super::FilterLang;
+
+/// CQL2 expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// Logical conjunction of the expressions
+    And(Vec<Expr>),
+    /// Logical disjunction of the expressions
+    Or(Vec<Expr>),
+    /// Logical negation of the expression
+    Not(Box<Expr>),
+    /// Binary comparison (`=`, `<>`, `<`, `<=`, `>`, `>=`)
+    Comparison(ComparisonOp, Box<Expr>, Box<Expr>),
+    /// Pattern matching with `%` and `_` wildcards
+    Like(Box<Expr>, Box<Expr>),
+    /// Inclusive range check
+    Between(Box<Expr>, Box<Expr>, Box<Expr>),
+    /// Membership in a list of values
+    In(Box<Expr>, Vec<Expr>),
+    /// Null or missing check
+    IsNull(Box<Expr>),
+    /// Spatial relationship between two geometries
+    Spatial(SpatialOp, Box<Expr>, Box<Expr>),
+    /// Temporal relationship between two instants or intervals
+    Temporal(TemporalOp, Box<Expr>, Box<Expr>),
+    /// Reference to a queryable property
+    Property(String),
+    /// Boolean literal
+    Bool(bool),
+    /// Numeric literal
+    Number(f64),
+    /// Character string literal
+    String(String),
+    /// Timestamp instant
+    Timestamp(DateTime<Utc>),
+    /// Date instant
+    Date(NaiveDate),
+    /// Interval with start and end, an open bound is represented by the string `..`
+    Interval(Box<Expr>, Box<Expr>),
+    /// Geometry literal
+    Geometry(Geometry),
+    /// Bounding box literal
+    Bbox(Vec<f64>),
+}
+
+impl Expr {
+    /// Parse a filter expression in the given encoding
+    pub fn parse(filter: &str, filter_lang: &FilterLang) -> Result<Expr, String> {
+        match filter_lang {
+            FilterLang::CqlText => filter.parse(),
+            FilterLang::CqlJson => {
+                let value: serde_json::Value =
+                    serde_json::from_str(filter).map_err(|e| e.to_string())?;
+                Expr::try_from(&value)
+            }
+        }
+    }
+
+    /// Names of all properties referenced in the expression
+    pub fn properties(&self) -> Vec<&str> {
+        let mut properties = Vec::new();
+        self.collect_properties(&mut properties);
+        properties
+    }
+
+    fn collect_properties<'a>(&'a self, properties: &mut Vec<&'a str>) {
+        match self {
+            Expr::Property(p) => properties.push(p),
+            Expr::And(exprs) | Expr::Or(exprs) => {
+                exprs.iter().for_each(|e| e.collect_properties(properties))
+            }
+            Expr::In(a, list) => {
+                a.collect_properties(properties);
+                list.iter().for_each(|e| e.collect_properties(properties));
+            }
+            Expr::Not(a) | Expr::IsNull(a) => a.collect_properties(properties),
+            Expr::Comparison(_, a, b)
+            | Expr::Like(a, b)
+            | Expr::Spatial(_, a, b)
+            | Expr::Temporal(_, a, b)
+            | Expr::Interval(a, b) => {
+                a.collect_properties(properties);
+                b.collect_properties(properties);
+            }
+            Expr::Between(a, low, high) => {
+                a.collect_properties(properties);
+                low.collect_properties(properties);
+                high.collect_properties(properties);
+            }
+            Expr::Bool(_)
+            | Expr::Number(_)
+            | Expr::String(_)
+            | Expr::Timestamp(_)
+            | Expr::Date(_)
+            | Expr::Geometry(_)
+            | Expr::Bbox(_) => (),
+        }
+    }
+
+    /// Returns `true` if the expression is the open interval bound `..`
+    pub fn is_open(&self) -> bool {
+        matches!(self, Expr::String(s) if s == "..")
+    }
+}
+
+/// Check the number of positions of a geometry literal against the
+/// constraints of GeoJSON, which spatial operators depend on
+fn validate_geometry(value: &Value) -> Result<(), String> {
+    let position = |position: &Position| {
+        if position.len() < 2 {
+            return Err("Expected positions with at least 2 coordinates".to_string());
+        }
+        Ok(())
+    };
+    let line = |positions: &Vec<Position>| {
+        if positions.len() < 2 {
+            return Err("Expected line strings with at least 2 positions".to_string());
+        }
+        positions.iter().try_for_each(position)
+    };
+    let polygon = |rings: &Vec<Vec<Position>>| {
+        rings.iter().try_for_each(|ring| {
+            if ring.len() < 4 || ring.first() != ring.last() {
+                return Err("Expected closed rings with at least 4 positions".to_string());
+            }
+            ring.iter().try_for_each(position)
+        })
+    };
+
+    match value {
+        Value::Point(p) => position(p),
+        Value::MultiPoint(points) => points.iter().try_for_each(position),
+        Value::LineString(positions) => line(positions),
+        Value::MultiLineString(lines) => lines.iter().try_for_each(line),
+        Value::Polygon(rings) => polygon(rings),
+        Value::MultiPolygon(polygons) => polygons.iter().try_for_each(polygon),
+        Value::GeometryCollection(geometries) => geometries
+            .iter()
+            .try_for_each(|geometry| validate_geometry(&geometry.value)),
+    }
+}
+
+/// Binary comparison operators
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComparisonOp {
+    Eq,
+    Neq,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+}
+
+impl fmt::Display for ComparisonOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComparisonOp::Eq => write!(f, "="),
+            ComparisonOp::Neq => write!(f, "<>"),
+            ComparisonOp::Lt => write!(f, "<"),
+            ComparisonOp::Lte => write!(f, "<="),
+            ComparisonOp::Gt => write!(f, ">"),
+            ComparisonOp::Gte => write!(f, ">="),
+        }
+    }
+}
+
+impl str::FromStr for ComparisonOp {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "=" => Ok(ComparisonOp::Eq),
+            "<>" => Ok(ComparisonOp::Neq),
+            "<" => Ok(ComparisonOp::Lt),
+            "<=" => Ok(ComparisonOp::Lte),
+            ">" => Ok(ComparisonOp::Gt),
+            ">=" => Ok(ComparisonOp::Gte),
+            _ => Err(format!("Unknown comparison operator `{s}`")),
+        }
+    }
+}
+
+/// Spatial operators based on the DE-9IM model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpatialOp {
+    Intersects,
+    Equals,
+    Disjoint,
+    Touches,
+    Within,
+    Overlaps,
+    Crosses,
+    Contains,
+}
+
+impl fmt::Display for SpatialOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpatialOp::Intersects => write!(f, "s_intersects"),
+            SpatialOp::Equals => write!(f, "s_equals"),
+            SpatialOp::Disjoint => write!(f, "s_disjoint"),
+            SpatialOp::Touches => write!(f, "s_touches"),
+            SpatialOp::Within => write!(f, "s_within"),
+            SpatialOp::Overlaps => write!(f, "s_overlaps"),
+            SpatialOp::Crosses => write!(f, "s_crosses"),
+            SpatialOp::Contains => write!(f, "s_contains"),
+        }
+    }
+}
+
+impl str::FromStr for SpatialOp {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "s_intersects" => Ok(SpatialOp::Intersects),
+            "s_equals" => Ok(SpatialOp::Equals),
+            "s_disjoint" => Ok(SpatialOp::Disjoint),
+            "s_touches" => Ok(SpatialOp::Touches),
+            "s_within" => Ok(SpatialOp::Within),
+            "s_overlaps" => Ok(SpatialOp::Overlaps),
+            "s_crosses" => Ok(SpatialOp::Crosses),
+            "s_contains" => Ok(SpatialOp::Contains),
+            _ => Err(format!("Unknown spatial operator `{s}`")),
+        }
+    }
+}
+
+/// Temporal operators based on Allen's interval algebra
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemporalOp {
+    After,
+    Before,
+    Contains,
+    Disjoint,
+    During,
+    Equals,
+    FinishedBy,
+    Finishes,
+    Intersects,
+    Meets,
+    MetBy,
+    OverlappedBy,
+    Overlaps,
+    StartedBy,
+    Starts,
+}
+
+impl fmt::Display for TemporalOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemporalOp::After => write!(f, "t_after"),
+            TemporalOp::Before => write!(f, "t_before"),
+            TemporalOp::Contains => write!(f, "t_contains"),
+            TemporalOp::Disjoint => write!(f, "t_disjoint"),
+            TemporalOp::During => write!(f, "t_during"),
+            TemporalOp::Equals => write!(f, "t_equals"),
+            TemporalOp::FinishedBy => write!(f, "t_finishedby"),
+            TemporalOp::Finishes => write!(f, "t_finishes"),
+            TemporalOp::Intersects => write!(f, "t_intersects"),
+            TemporalOp::Meets => write!(f, "t_meets"),
+            TemporalOp::MetBy => write!(f, "t_metby"),
+            TemporalOp::OverlappedBy => write!(f, "t_overlappedby"),
+            TemporalOp::Overlaps => write!(f, "t_overlaps"),
+            TemporalOp::StartedBy => write!(f, "t_startedby"),
+            TemporalOp::Starts => write!(f, "t_starts"),
+        }
+    }
+}
+
+impl str::FromStr for TemporalOp {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "t_after" => Ok(TemporalOp::After),
+            "t_before" => Ok(TemporalOp::Before),
+            "t_contains" => Ok(TemporalOp::Contains),
+            "t_disjoint" => Ok(TemporalOp::Disjoint),
+            "t_during" => Ok(TemporalOp::During),
+            "t_equals" => Ok(TemporalOp::Equals),
+            "t_finishedby" => Ok(TemporalOp::FinishedBy),
+            "t_finishes" => Ok(TemporalOp::Finishes),
+            "t_intersects" => Ok(TemporalOp::Intersects),
+            "t_meets" => Ok(TemporalOp::Meets),
+            "t_metby" => Ok(TemporalOp::MetBy),
+            "t_overlappedby" => Ok(TemporalOp::OverlappedBy),
+            "t_overlaps" => Ok(TemporalOp::Overlaps),
+            "t_startedby" => Ok(TemporalOp::StartedBy),
+            "t_starts" => Ok(TemporalOp::Starts),
+            _ => Err(format!("Unknown temporal operator `{s}`")),
+        }
+    }
+}
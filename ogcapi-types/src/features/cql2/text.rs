@@ -0,0 +1,671 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate};
+use geojson::{Geometry, Position, Value};
+
+use super::{validate_geometry, ComparisonOp, Expr, SpatialOp, TemporalOp};
+
+/// Maximum nesting of parentheses, `NOT` and geometry collections, which
+/// bounds the recursion of the parser
+const MAX_DEPTH: usize = 128;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Quoted(String),
+    String(String),
+    Number(f64),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Ident(s) => write!(f, "{s}"),
+            Token::Quoted(s) => write!(f, "\"{s}\""),
+            Token::String(s) => write!(f, "'{s}'"),
+            Token::Number(n) => write!(f, "{n}"),
+            Token::Op(o) => write!(f, "{o}"),
+            Token::LParen => write!(f, "("),
+            Token::RParen => write!(f, ")"),
+            Token::Comma => write!(f, ","),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            '=' => {
+                tokens.push(Token::Op("="));
+                i += 1;
+            }
+            '<' => match chars.get(i + 1) {
+                Some('>') => {
+                    tokens.push(Token::Op("<>"));
+                    i += 2;
+                }
+                Some('=') => {
+                    tokens.push(Token::Op("<="));
+                    i += 2;
+                }
+                _ => {
+                    tokens.push(Token::Op("<"));
+                    i += 1;
+                }
+            },
+            '>' => match chars.get(i + 1) {
+                Some('=') => {
+                    tokens.push(Token::Op(">="));
+                    i += 2;
+                }
+                _ => {
+                    tokens.push(Token::Op(">"));
+                    i += 1;
+                }
+            },
+            '\'' | '"' => {
+                let quote = c;
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        Some(&c) if c == quote => {
+                            // a doubled quote is an escaped quote
+                            if chars.get(i + 1) == Some(&quote) {
+                                value.push(quote);
+                                i += 2;
+                            } else {
+                                i += 1;
+                                break;
+                            }
+                        }
+                        Some(&c) => {
+                            value.push(c);
+                            i += 1;
+                        }
+                        None => return Err(format!("Unterminated literal `{quote}{value}`")),
+                    }
+                }
+                if quote == '\'' {
+                    tokens.push(Token::String(value));
+                } else {
+                    tokens.push(Token::Quoted(value));
+                }
+            }
+            c if c.is_ascii_digit() || matches!(c, '-' | '+' | '.') => {
+                let start = i;
+                i += 1;
+                while let Some(&c) = chars.get(i) {
+                    if c.is_ascii_digit() || c == '.' {
+                        i += 1;
+                    } else if matches!(c, 'e' | 'E') {
+                        i += 1;
+                        if matches!(chars.get(i), Some('-' | '+')) {
+                            i += 1;
+                        }
+                    } else {
+                        break;
+                    }
+                }
+                let number: String = chars[start..i].iter().collect();
+                let number = number
+                    .parse()
+                    .map_err(|_| format!("Invalid number `{number}`"))?;
+                tokens.push(Token::Number(number));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while let Some(&c) = chars.get(i) {
+                    if c.is_alphanumeric() || matches!(c, '_' | ':' | '.') {
+                        i += 1;
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Ident(chars[start..i].iter().collect()));
+            }
+            c => return Err(format!("Unexpected character `{c}`")),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    /// Parse one level deeper
+    fn nested<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<T, String>,
+    ) -> Result<T, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(format!("Filter is nested deeper than {MAX_DEPTH} levels"));
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + offset)
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| "Unexpected end of filter".to_string())?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        let token = self.next()?;
+        if token == expected {
+            Ok(())
+        } else {
+            Err(format!("Expected `{expected}` but found `{token}`"))
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(s)) if s.eq_ignore_ascii_case(keyword))
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        if self.is_keyword(keyword) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut exprs = vec![self.and()?];
+        while self.keyword("OR") {
+            exprs.push(self.and()?);
+        }
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            Expr::Or(exprs)
+        })
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut exprs = vec![self.not()?];
+        while self.keyword("AND") {
+            exprs.push(self.not()?);
+        }
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            Expr::And(exprs)
+        })
+    }
+
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.keyword("NOT") {
+            Ok(Expr::Not(Box::new(self.nested(Self::not)?)))
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        if self.peek() == Some(&Token::LParen) {
+            self.pos += 1;
+            let expr = self.nested(Self::or)?;
+            self.expect(Token::RParen)?;
+            return Ok(expr);
+        }
+
+        if let (Some(Token::Ident(name)), Some(Token::LParen)) = (self.peek(), self.peek_at(1)) {
+            if let Ok(op) = SpatialOp::from_str(name) {
+                self.pos += 2;
+                let (a, b) = self.binary_args()?;
+                return Ok(Expr::Spatial(op, Box::new(a), Box::new(b)));
+            }
+            if let Ok(op) = TemporalOp::from_str(name) {
+                self.pos += 2;
+                let (a, b) = self.binary_args()?;
+                return Ok(Expr::Temporal(op, Box::new(a), Box::new(b)));
+            }
+        }
+
+        let lhs = self.scalar()?;
+
+        if let Some(Token::Op(op)) = self.peek() {
+            let op = ComparisonOp::from_str(op)?;
+            self.pos += 1;
+            let rhs = self.scalar()?;
+            return Ok(Expr::Comparison(op, Box::new(lhs), Box::new(rhs)));
+        }
+
+        if self.keyword("IS") {
+            let negated = self.keyword("NOT");
+            if !self.keyword("NULL") {
+                return Err("Expected `NULL` after `IS`".to_string());
+            }
+            let expr = Expr::IsNull(Box::new(lhs));
+            return Ok(if negated {
+                Expr::Not(Box::new(expr))
+            } else {
+                expr
+            });
+        }
+
+        let negated = self.keyword("NOT");
+
+        let expr = if self.keyword("LIKE") {
+            Expr::Like(Box::new(lhs), Box::new(self.scalar()?))
+        } else if self.keyword("BETWEEN") {
+            let low = self.scalar()?;
+            if !self.keyword("AND") {
+                return Err("Expected `AND` in `BETWEEN` predicate".to_string());
+            }
+            let high = self.scalar()?;
+            Expr::Between(Box::new(lhs), Box::new(low), Box::new(high))
+        } else if self.keyword("IN") {
+            self.expect(Token::LParen)?;
+            let mut list = vec![self.scalar()?];
+            while self.peek() == Some(&Token::Comma) {
+                self.pos += 1;
+                list.push(self.scalar()?);
+            }
+            self.expect(Token::RParen)?;
+            Expr::In(Box::new(lhs), list)
+        } else if negated {
+            return Err("Expected `LIKE`, `BETWEEN` or `IN` after `NOT`".to_string());
+        } else if let Expr::Bool(_) = lhs {
+            lhs
+        } else {
+            return match self.peek() {
+                Some(token) => Err(format!("Unexpected token `{token}`")),
+                None => Err("Expected a predicate".to_string()),
+            };
+        };
+
+        Ok(if negated {
+            Expr::Not(Box::new(expr))
+        } else {
+            expr
+        })
+    }
+
+    fn binary_args(&mut self) -> Result<(Expr, Expr), String> {
+        let a = self.scalar()?;
+        self.expect(Token::Comma)?;
+        let b = self.scalar()?;
+        self.expect(Token::RParen)?;
+        Ok((a, b))
+    }
+
+    fn scalar(&mut self) -> Result<Expr, String> {
+        match self.next()? {
+            Token::String(s) => Ok(Expr::String(s)),
+            Token::Number(n) => Ok(Expr::Number(n)),
+            Token::Quoted(p) => Ok(Expr::Property(p)),
+            Token::Ident(ident) => {
+                let is_call = self.peek() == Some(&Token::LParen);
+                match ident.to_uppercase().as_str() {
+                    "TRUE" => Ok(Expr::Bool(true)),
+                    "FALSE" => Ok(Expr::Bool(false)),
+                    "TIMESTAMP" if is_call => {
+                        let s = self.string_arg()?;
+                        let timestamp = DateTime::parse_from_rfc3339(&s)
+                            .map_err(|e| format!("Invalid timestamp `{s}`: {e}"))?;
+                        Ok(Expr::Timestamp(timestamp.into()))
+                    }
+                    "DATE" if is_call => {
+                        let s = self.string_arg()?;
+                        let date = NaiveDate::parse_from_str(&s, "%Y-%m-%d")
+                            .map_err(|e| format!("Invalid date `{s}`: {e}"))?;
+                        Ok(Expr::Date(date))
+                    }
+                    "INTERVAL" if is_call => {
+                        self.pos += 1;
+                        let (start, end) = self.binary_args()?;
+                        Ok(Expr::Interval(Box::new(start), Box::new(end)))
+                    }
+                    "BBOX" if is_call => {
+                        self.pos += 1;
+                        let mut numbers = vec![self.number()?];
+                        while self.peek() == Some(&Token::Comma) {
+                            self.pos += 1;
+                            numbers.push(self.number()?);
+                        }
+                        self.expect(Token::RParen)?;
+                        if numbers.len() != 4 && numbers.len() != 6 {
+                            return Err("Expected 4 or 6 numbers in `BBOX`".to_string());
+                        }
+                        Ok(Expr::Bbox(numbers))
+                    }
+                    "POINT" | "LINESTRING" | "POLYGON" | "MULTIPOINT" | "MULTILINESTRING"
+                    | "MULTIPOLYGON" | "GEOMETRYCOLLECTION" => {
+                        let value = self.wkt(&ident.to_uppercase())?;
+                        validate_geometry(&value).map_err(|e| format!("Invalid geometry: {e}"))?;
+                        Ok(Expr::Geometry(Geometry::new(value)))
+                    }
+                    _ if is_call => Err(format!("Unsupported function `{ident}`")),
+                    _ => Ok(Expr::Property(ident)),
+                }
+            }
+            token => Err(format!("Unexpected token `{token}`")),
+        }
+    }
+
+    fn string_arg(&mut self) -> Result<String, String> {
+        self.expect(Token::LParen)?;
+        let s = match self.next()? {
+            Token::String(s) => s,
+            token => return Err(format!("Expected string literal but found `{token}`")),
+        };
+        self.expect(Token::RParen)?;
+        Ok(s)
+    }
+
+    fn number(&mut self) -> Result<f64, String> {
+        match self.next()? {
+            Token::Number(n) => Ok(n),
+            token => Err(format!("Expected number but found `{token}`")),
+        }
+    }
+
+    fn wkt(&mut self, kind: &str) -> Result<Value, String> {
+        // optional dimension marker
+        let _ = self.keyword("Z") || self.keyword("M") || self.keyword("ZM");
+
+        if self.is_keyword("EMPTY") {
+            return Err("Empty geometries are not supported".to_string());
+        }
+
+        match kind {
+            "POINT" => {
+                self.expect(Token::LParen)?;
+                let position = self.position()?;
+                self.expect(Token::RParen)?;
+                Ok(Value::Point(position))
+            }
+            "LINESTRING" => Ok(Value::LineString(self.positions()?)),
+            "POLYGON" => Ok(Value::Polygon(self.rings()?)),
+            "MULTIPOINT" => {
+                self.expect(Token::LParen)?;
+                let mut points = Vec::new();
+                loop {
+                    // both `MULTIPOINT((1 2), (3 4))` and `MULTIPOINT(1 2, 3 4)` are valid
+                    if self.peek() == Some(&Token::LParen) {
+                        self.pos += 1;
+                        points.push(self.position()?);
+                        self.expect(Token::RParen)?;
+                    } else {
+                        points.push(self.position()?);
+                    }
+                    if self.peek() == Some(&Token::Comma) {
+                        self.pos += 1;
+                    } else {
+                        break;
+                    }
+                }
+                self.expect(Token::RParen)?;
+                Ok(Value::MultiPoint(points))
+            }
+            "MULTILINESTRING" => Ok(Value::MultiLineString(self.rings()?)),
+            "MULTIPOLYGON" => {
+                self.expect(Token::LParen)?;
+                let mut polygons = vec![self.rings()?];
+                while self.peek() == Some(&Token::Comma) {
+                    self.pos += 1;
+                    polygons.push(self.rings()?);
+                }
+                self.expect(Token::RParen)?;
+                Ok(Value::MultiPolygon(polygons))
+            }
+            "GEOMETRYCOLLECTION" => {
+                self.expect(Token::LParen)?;
+                let mut geometries = Vec::new();
+                loop {
+                    match self.next()? {
+                        Token::Ident(kind) => {
+                            let value = self.nested(|p| p.wkt(&kind.to_uppercase()))?;
+                            geometries.push(Geometry::new(value))
+                        }
+                        token => return Err(format!("Expected geometry but found `{token}`")),
+                    }
+                    if self.peek() == Some(&Token::Comma) {
+                        self.pos += 1;
+                    } else {
+                        break;
+                    }
+                }
+                self.expect(Token::RParen)?;
+                Ok(Value::GeometryCollection(geometries))
+            }
+            _ => Err(format!("Unsupported geometry type `{kind}`")),
+        }
+    }
+
+    fn position(&mut self) -> Result<Position, String> {
+        let mut position = Vec::new();
+        while let Some(Token::Number(n)) = self.peek() {
+            position.push(*n);
+            self.pos += 1;
+        }
+        if (2..=4).contains(&position.len()) {
+            Ok(position)
+        } else {
+            Err("Expected position with 2 to 4 coordinates".to_string())
+        }
+    }
+
+    fn positions(&mut self) -> Result<Vec<Position>, String> {
+        self.expect(Token::LParen)?;
+        let mut positions = vec![self.position()?];
+        while self.peek() == Some(&Token::Comma) {
+            self.pos += 1;
+            positions.push(self.position()?);
+        }
+        self.expect(Token::RParen)?;
+        Ok(positions)
+    }
+
+    fn rings(&mut self) -> Result<Vec<Vec<Position>>, String> {
+        self.expect(Token::LParen)?;
+        let mut rings = vec![self.positions()?];
+        while self.peek() == Some(&Token::Comma) {
+            self.pos += 1;
+            rings.push(self.positions()?);
+        }
+        self.expect(Token::RParen)?;
+        Ok(rings)
+    }
+}
+
+impl FromStr for Expr {
+    type Err = String;
+
+    /// Parse a CQL2-Text encoded filter expression
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            pos: 0,
+            depth: 0,
+        };
+
+        let expr = parser.or()?;
+
+        match parser.peek() {
+            Some(token) => Err(format!("Unexpected token `{token}`")),
+            None => Ok(expr),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use geojson::Value;
+
+    use crate::features::cql2::{ComparisonOp, Expr, SpatialOp, TemporalOp};
+
+    fn property(name: &str) -> Box<Expr> {
+        Box::new(Expr::Property(name.to_string()))
+    }
+
+    #[test]
+    fn comparison() {
+        let expr = Expr::from_str("\"eo:cloud_cover\" <= 10.5").unwrap();
+        assert_eq!(
+            expr,
+            Expr::Comparison(
+                ComparisonOp::Lte,
+                property("eo:cloud_cover"),
+                Box::new(Expr::Number(10.5))
+            )
+        );
+
+        let expr = Expr::from_str("name = 'O''Brien'").unwrap();
+        assert_eq!(
+            expr,
+            Expr::Comparison(
+                ComparisonOp::Eq,
+                property("name"),
+                Box::new(Expr::String("O'Brien".to_string()))
+            )
+        );
+    }
+
+    #[test]
+    fn logical() {
+        let expr = Expr::from_str("a = 1 AND (b <> -2 OR NOT c > 3) and d IS NOT NULL").unwrap();
+        match expr {
+            Expr::And(exprs) => {
+                assert_eq!(exprs.len(), 3);
+                assert!(matches!(exprs[1], Expr::Or(_)));
+                assert!(matches!(exprs[2], Expr::Not(_)));
+            }
+            _ => panic!("expected conjunction"),
+        }
+    }
+
+    #[test]
+    fn advanced_comparison() {
+        let expr = Expr::from_str("name NOT LIKE 'Ber%'").unwrap();
+        assert!(matches!(expr, Expr::Not(e) if matches!(*e, Expr::Like(..))));
+
+        let expr = Expr::from_str("pop BETWEEN 100 AND 1000 AND x = 1").unwrap();
+        match expr {
+            Expr::And(exprs) => assert!(matches!(exprs[0], Expr::Between(..))),
+            _ => panic!("expected conjunction"),
+        }
+
+        let expr = Expr::from_str("ISO_A2 IN ('CH', 'AT', 'DE')").unwrap();
+        assert!(matches!(expr, Expr::In(_, list) if list.len() == 3));
+    }
+
+    #[test]
+    fn spatial() {
+        let expr =
+            Expr::from_str("S_INTERSECTS(geometry, POLYGON((6 45, 6 49, 9 49, 9 45, 6 45)))")
+                .unwrap();
+        match expr {
+            Expr::Spatial(SpatialOp::Intersects, a, b) => {
+                assert_eq!(*a, Expr::Property("geometry".to_string()));
+                match *b {
+                    Expr::Geometry(g) => assert!(matches!(g.value, Value::Polygon(_))),
+                    _ => panic!("expected geometry"),
+                }
+            }
+            _ => panic!("expected spatial predicate"),
+        }
+
+        let expr = Expr::from_str("s_within(geometry, BBOX(6, 45, 9, 49))").unwrap();
+        assert!(
+            matches!(expr, Expr::Spatial(SpatialOp::Within, _, b) if *b == Expr::Bbox(vec![6.0, 45.0, 9.0, 49.0]))
+        );
+
+        let expr = Expr::from_str("S_CONTAINS(geometry, MULTIPOINT(7 46, (8 47)))").unwrap();
+        assert!(matches!(expr, Expr::Spatial(SpatialOp::Contains, ..)));
+    }
+
+    #[test]
+    fn temporal() {
+        let expr = Expr::from_str(
+            "T_INTERSECTS(INTERVAL(start, end), INTERVAL('2020-01-01T00:00:00Z', '..'))",
+        )
+        .unwrap();
+        match expr {
+            Expr::Temporal(TemporalOp::Intersects, a, b) => {
+                assert!(matches!(*a, Expr::Interval(..)));
+                match *b {
+                    Expr::Interval(_, end) => assert!(end.is_open()),
+                    _ => panic!("expected interval"),
+                }
+            }
+            _ => panic!("expected temporal predicate"),
+        }
+
+        let expr = Expr::from_str("t_before(datetime, DATE('2020-01-01'))").unwrap();
+        assert!(matches!(expr, Expr::Temporal(TemporalOp::Before, ..)));
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Expr::from_str("a =").is_err());
+        assert!(Expr::from_str("a = 1 b = 2").is_err());
+        assert!(Expr::from_str("name = 'unterminated").is_err());
+        assert!(Expr::from_str("foo(a) = 1").is_err());
+        assert!(Expr::from_str("S_INTERSECTS(geometry, POINT(1))").is_err());
+        assert!(Expr::from_str("S_INTERSECTS(geometry, LINESTRING(1 2))").is_err());
+        assert!(
+            Expr::from_str("S_INTERSECTS(geometry, POLYGON((6 45, 6 49, 9 49, 9 45)))").is_err()
+        );
+
+        // nesting is limited
+        let nested = |depth: usize| format!("{}a = 1{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(Expr::from_str(&nested(100)).is_ok());
+        assert!(Expr::from_str(&nested(100_000)).is_err());
+        assert!(Expr::from_str(&format!("{}a = 1", "NOT ".repeat(100_000))).is_err());
+        let collections = format!(
+            "S_INTERSECTS(geometry, {}POINT(1 2){})",
+            "GEOMETRYCOLLECTION(".repeat(100_000),
+            ")".repeat(100_000)
+        );
+        assert!(Expr::from_str(&collections).is_err());
+    }
+}
@@ -30,7 +30,21 @@ pub struct Feature {
     pub r#type: Type,
     #[serialize_always]
     pub properties: Option<Map<String, Value>>,
-    pub geometry: Geometry,
+    #[serialize_always]
+    pub geometry: Option<Geometry>,
+    /// JSON-FG: Geometry in a coordinate reference system other than WGS 84
+    pub place: Option<Geometry>,
+    /// JSON-FG: Temporal extent of the feature
+    pub time: Option<Time>,
+    /// JSON-FG: Type of the feature, usually the collection
+    #[serde(rename = "featureType")]
+    pub feature_type: Option<String>,
+    /// JSON-FG: Coordinate reference system of `place`
+    #[serde(rename = "coordRefSys")]
+    pub coord_ref_sys: Option<String>,
+    /// JSON-FG: Conformance classes of the document
+    #[serde(rename = "conformsTo")]
+    pub conforms_to: Option<Vec<String>>,
     #[serde(default)]
     pub links: Links,
     /// The STAC version the Item implements.
@@ -50,6 +64,35 @@ pub struct Feature {
     pub bbox: Option<Bbox>,
 }
 
+/// JSON-FG temporal extent, either an instant or an interval with `..` for an
+/// open end
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Time {
+    Date(String),
+    Timestamp(String),
+    Interval([String; 2]),
+}
+
+impl Time {
+    /// Instant from a RFC 3339 date or timestamp
+    pub fn instant(datetime: &str) -> Self {
+        if datetime.len() == 10 {
+            Time::Date(datetime.to_owned())
+        } else {
+            Time::Timestamp(datetime.to_owned())
+        }
+    }
+
+    /// Interval from optional start and end instants
+    pub fn interval(start: Option<&str>, end: Option<&str>) -> Self {
+        Time::Interval([
+            start.unwrap_or("..").to_owned(),
+            end.unwrap_or("..").to_owned(),
+        ])
+    }
+}
+
 impl Feature {
     pub fn append_properties(&mut self, mut other: Map<String, Value>) {
         if let Some(properties) = self.properties.as_mut() {
@@ -59,3 +102,33 @@ impl Feature {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Feature, Time};
+
+    #[test]
+    fn json_fg() {
+        let feature: Feature = serde_json::from_str(
+            r#"{
+                "type": "Feature",
+                "featureType": "building",
+                "coordRefSys": "http://www.opengis.net/def/crs/EPSG/0/2056",
+                "time": { "interval": ["2022-01-01", ".."] },
+                "place": { "type": "Point", "coordinates": [2600000.0, 1200000.0] },
+                "geometry": null,
+                "properties": {}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(feature.feature_type.as_deref(), Some("building"));
+        assert_eq!(feature.time, Some(Time::interval(Some("2022-01-01"), None)));
+        assert!(feature.place.is_some() && feature.geometry.is_none());
+
+        let value = serde_json::to_value(Time::instant("2022-01-01T12:00:00Z")).unwrap();
+        assert_eq!(value["timestamp"], "2022-01-01T12:00:00Z");
+        let value = serde_json::to_value(Time::instant("2022-01-01")).unwrap();
+        assert_eq!(value["date"], "2022-01-01");
+    }
+}
@@ -29,6 +29,12 @@ pub struct FeatureCollection {
     pub time_stamp: Option<String>,
     pub number_matched: Option<u64>,
     pub number_returned: Option<u64>,
+    /// JSON-FG: Type of the features, usually the collection
+    pub feature_type: Option<String>,
+    /// JSON-FG: Coordinate reference system of the `place` of the features
+    pub coord_ref_sys: Option<String>,
+    /// JSON-FG: Conformance classes of the document
+    pub conforms_to: Option<Vec<String>>,
 }
 
 impl FeatureCollection {
@@ -1,9 +1,16 @@
+mod bulk;
+pub mod cql2;
 mod feature;
 mod feature_collection;
 mod query;
+mod queryables;
+mod sortby;
 
-pub use feature::Feature;
+pub use bulk::{BulkError, BulkMode, BulkReport};
+pub use feature::{Feature, Time};
 pub use feature_collection::FeatureCollection;
-pub use query::Query;
+pub use query::{CsvGeometry, FilterLang, Query};
+pub use queryables::{Queryable, Queryables};
+pub use sortby::{Direction, SortBy};
 
 pub use geojson::Geometry;
@@ -1,16 +1,22 @@
 use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
-use serde_with::DisplayFromStr;
+use serde_with::{formats::CommaSeparator, DisplayFromStr, StringWithSeparator};
 
-use crate::common::{Bbox, Crs, Datetime};
+use crate::common::{Bbox, Crs, Cursor, Datetime};
+
+use super::{cql2::Expr, SortBy};
 
 #[serde_with::serde_as]
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[serde(rename_all = "kebab-case")]
 pub struct Query {
     pub limit: Option<usize>,
     pub offset: Option<usize>,
+    /// Continuation token of the next page
+    #[serde(default)]
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub token: Option<Cursor>,
     #[serde(default)]
     #[serde_as(as = "Option<DisplayFromStr>")]
     pub bbox: Option<Bbox>,
@@ -23,21 +29,54 @@ pub struct Query {
     #[serde(default)]
     #[serde_as(as = "DisplayFromStr")]
     pub crs: Crs,
+    /// Sort keys, e.g. `+name,-datetime`
+    #[serde(default)]
+    #[serde_as(as = "Option<StringWithSeparator::<CommaSeparator, SortBy>>")]
+    pub sortby: Option<Vec<SortBy>>,
+    /// Properties to include in the response, e.g. `name,population`
+    #[serde(default)]
+    #[serde_as(as = "Option<StringWithSeparator::<CommaSeparator, String>>")]
+    pub properties: Option<Vec<String>>,
+    /// Omit the geometry in the response
+    #[serde(default, rename = "skipGeometry")]
+    pub skip_geometry: Option<bool>,
+    /// Simplify the geometries with this tolerance, in units of the `crs`
+    pub max_allowable_offset: Option<f64>,
+    /// Simplify the geometries to the pixel size of a web map zoom level
+    pub zoom_level: Option<u8>,
+    /// Number of decimal places of the coordinates
+    pub coordinate_precision: Option<u8>,
     pub filter: Option<String>,
     #[serde(default)]
     pub filter_lang: Option<FilterLang>,
     #[serde(default)]
     #[serde_as(as = "Option<DisplayFromStr>")]
     pub filter_crs: Option<Crs>,
+    /// Encoding of the response, e.g. `html`
+    pub f: Option<String>,
+    /// Geometry columns of the CSV encoding
+    #[serde(default)]
+    pub csv_geometry: Option<CsvGeometry>,
     /// Parameters for filtering on feature properties
     #[serde(default, flatten)]
     pub additional_parameters: HashMap<String, String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(rename_all = "kebab-case")]
+impl Query {
+    /// Parse the `filter` parameter according to the `filter-lang`
+    pub fn filter_expr(&self) -> Result<Option<Expr>, String> {
+        self.filter
+            .as_ref()
+            .map(|filter| Expr::parse(filter, &self.filter_lang.clone().unwrap_or_default()))
+            .transpose()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum FilterLang {
+    #[serde(rename = "cql2-text", alias = "cql-text")]
     CqlText,
+    #[serde(rename = "cql2-json", alias = "cql-json")]
     CqlJson,
 }
 
@@ -46,3 +85,19 @@ impl std::default::Default for FilterLang {
         FilterLang::CqlText
     }
 }
+
+/// Geometry columns of the CSV encoding
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CsvGeometry {
+    /// Well-known text in a single column
+    Wkt,
+    /// Coordinates of points in one column per axis
+    Xy,
+}
+
+impl std::default::Default for CsvGeometry {
+    fn default() -> Self {
+        CsvGeometry::Wkt
+    }
+}
@@ -1,16 +1,173 @@
+use std::collections::BTreeMap;
+
+use chrono::DateTime;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::cql2::Expr;
+
+/// JSON Schema of the properties that may be used in filter expressions
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Queryables {
+    #[serde(rename = "$schema")]
+    pub schema: String,
+    #[serde(rename = "$id")]
+    pub id: Option<String>,
+    pub r#type: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub properties: BTreeMap<String, Queryable>,
+    #[serde(default)]
+    pub additional_properties: bool,
+}
+
+/// JSON Schema of a single queryable property
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Queryable {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub r#type: Option<String>,
+    pub format: Option<String>,
+    #[serde(rename = "$ref")]
+    pub r#ref: Option<String>,
+    #[serde(flatten, default)]
+    pub additional_properties: Map<String, Value>,
+}
+
+impl Queryable {
+    /// Value of a query parameter according to the type of the queryable, or
+    /// `None` if it does not conform. Date-times are normalized to RFC 3339.
+    pub fn value(&self, text: &str) -> Option<Value> {
+        match (self.r#type.as_deref(), self.format.as_deref()) {
+            (Some("integer"), _) => text.parse::<i64>().ok().map(Value::from),
+            (Some("number"), _) => text
+                .parse::<f64>()
+                .ok()
+                .filter(|number| number.is_finite())
+                .map(Value::from),
+            (Some("boolean"), _) => text.parse::<bool>().ok().map(Value::from),
+            (Some("string"), Some("date-time")) => DateTime::parse_from_rfc3339(text)
+                .ok()
+                .map(|datetime| Value::from(datetime.to_rfc3339())),
+            _ => Some(Value::from(text)),
+        }
+    }
+}
+
+impl Default for Queryables {
+    fn default() -> Self {
+        Self {
+            schema: "https://json-schema.org/draft/2019-09/schema".to_string(),
+            id: None,
+            r#type: "object".to_string(),
+            title: None,
+            description: None,
+            properties: BTreeMap::new(),
+            additional_properties: false,
+        }
+    }
+}
+
+impl Queryables {
+    /// Apply overrides, e.g. from the collection metadata, on a per property basis
+    pub fn merge(&mut self, overrides: BTreeMap<String, Queryable>) {
+        for (name, queryable) in overrides {
+            let merged = match self.properties.remove(&name) {
+                Some(inferred) => Queryable {
+                    title: queryable.title.or(inferred.title),
+                    description: queryable.description.or(inferred.description),
+                    r#type: queryable.r#type.or(inferred.r#type),
+                    format: queryable.format.or(inferred.format),
+                    r#ref: queryable.r#ref.or(inferred.r#ref),
+                    additional_properties: inferred
+                        .additional_properties
+                        .into_iter()
+                        .chain(queryable.additional_properties)
+                        .collect(),
+                },
+                None => queryable,
+            };
+            self.properties.insert(name, merged);
+        }
+    }
+
+    /// Returns `true` if the property is queryable
+    pub fn contains(&self, property: &str) -> bool {
+        self.additional_properties || self.properties.contains_key(property)
+    }
+
+    /// Returns the first property referenced by the expression that is not queryable
+    pub fn unknown_property<'a>(&self, expr: &'a Expr) -> Option<&'a str> {
+        expr.properties().into_iter().find(|p| !self.contains(p))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde_json::json;
+
+    use super::{Queryable, Queryables};
+
+    #[test]
+    fn merge_and_check() {
+        let mut queryables = Queryables::default();
+        queryables.properties.insert(
+            "name".to_string(),
+            Queryable {
+                r#type: Some("string".to_string()),
+                ..Default::default()
+            },
+        );
+
+        queryables.merge(BTreeMap::from([(
+            "name".to_string(),
+            Queryable {
+                title: Some("Name".to_string()),
+                ..Default::default()
+            },
+        )]));
+
+        let name = &queryables.properties["name"];
+        assert_eq!(name.title.as_deref(), Some("Name"));
+        assert_eq!(name.r#type.as_deref(), Some("string"));
+
+        let expr = "name = 'Bern' AND nmae = 'Bern'".parse().unwrap();
+        assert_eq!(queryables.unknown_property(&expr), Some("nmae"));
+    }
+
+    #[test]
+    fn typed_values() {
+        let queryable = |r#type: &str, format: Option<&str>| Queryable {
+            r#type: Some(r#type.to_string()),
+            format: format.map(str::to_string),
+            ..Default::default()
+        };
+
+        let integer = queryable("integer", None);
+        assert_eq!(integer.value("42"), Some(json!(42)));
+        assert_eq!(integer.value("4.2"), None);
+
+        let number = queryable("number", None);
+        assert_eq!(number.value("4.2"), Some(json!(4.2)));
+        assert_eq!(number.value("NaN"), None);
+
+        let boolean = queryable("boolean", None);
+        assert_eq!(boolean.value("true"), Some(json!(true)));
+        assert_eq!(boolean.value("yes"), None);
+
+        let datetime = queryable("string", Some("date-time"));
+        assert_eq!(
+            datetime.value("2022-10-17T12:00:00Z"),
+            Some(json!("2022-10-17T12:00:00+00:00"))
+        );
+        assert_eq!(datetime.value("yesterday"), None);
 
-#[derive(Serialize, Deserialize, Debug)]
-struct Queryables {
-    queryables: Vec<Queryable>
+        assert_eq!(Queryable::default().value("42"), Some(json!("42")));
+    }
 }
-#[derive(Serialize, Deserialize, Debug)]
-struct Queryable {
-    queryable: String,
-    title: Option<String>,
-    description: Option<String>,
-    language: Option<String>, // default en
-    r#type: String,
-    #[serde(rename = "type-ref")]
-    type_ref: String,
-}
\ No newline at end of file
@@ -0,0 +1,116 @@
+use std::{fmt, str};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::Feature;
+
+/// Sort keys derived from the geometry rather than from a property
+pub const GEOMETRY_SORT_KEYS: [&str; 2] = ["geometry.area", "geometry.length"];
+
+/// Sort order
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Asc,
+    Desc,
+}
+
+impl Default for Direction {
+    fn default() -> Self {
+        Direction::Asc
+    }
+}
+
+/// Sort key, e.g. `+name` or `-datetime` in query parameters
+///
+/// Besides properties, the field may be `id`, `collection` or one of the
+/// [GEOMETRY_SORT_KEYS].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SortBy {
+    pub field: String,
+    #[serde(default)]
+    pub direction: Direction,
+}
+
+impl SortBy {
+    pub fn new(field: impl ToString, direction: Direction) -> Self {
+        SortBy {
+            field: field.to_string(),
+            direction,
+        }
+    }
+
+    /// Returns `true` if the sort key is derived from the geometry
+    pub fn is_geometry_derived(&self) -> bool {
+        GEOMETRY_SORT_KEYS.contains(&self.field.as_str())
+    }
+
+    /// Value of the sort key for a feature, `null` if it is missing or
+    /// derived from the geometry
+    pub fn value(&self, feature: &Feature) -> Value {
+        match self.field.as_str() {
+            "id" => Value::from(feature.id.to_owned()),
+            "collection" => Value::from(feature.collection.to_owned()),
+            field => feature
+                .properties
+                .as_ref()
+                .and_then(|p| p.get(field))
+                .cloned()
+                .unwrap_or(Value::Null),
+        }
+    }
+}
+
+impl fmt::Display for SortBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.direction {
+            Direction::Asc => write!(f, "+{}", self.field),
+            Direction::Desc => write!(f, "-{}", self.field),
+        }
+    }
+}
+
+impl str::FromStr for SortBy {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // an unencoded `+` in a query string is decoded as space
+        let s = s.trim();
+        let (field, direction) = if let Some(field) = s.strip_prefix('-') {
+            (field, Direction::Desc)
+        } else if let Some(field) = s.strip_prefix('+') {
+            (field, Direction::Asc)
+        } else {
+            (s, Direction::Asc)
+        };
+
+        if field.is_empty() {
+            return Err("Empty sort key");
+        }
+
+        Ok(SortBy::new(field, direction))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Direction, SortBy};
+
+    #[test]
+    fn from_str() {
+        assert_eq!(
+            "-datetime".parse::<SortBy>().unwrap(),
+            SortBy::new("datetime", Direction::Desc)
+        );
+        assert_eq!(
+            " name".parse::<SortBy>().unwrap(),
+            SortBy::new("name", Direction::Asc)
+        );
+        assert_eq!(
+            "+eo:cloud_cover".parse::<SortBy>().unwrap().to_string(),
+            "+eo:cloud_cover"
+        );
+        assert!("-".parse::<SortBy>().is_err());
+    }
+}
@@ -2,7 +2,10 @@ use geojson::Geometry;
 use serde::{Deserialize, Serialize};
 use serde_with::{formats::CommaSeparator, DisplayFromStr, StringWithSeparator};
 
-use crate::common::{Bbox, Datetime};
+use crate::{
+    common::{Bbox, Crs, Cursor, Datetime},
+    features::{cql2::Expr, FilterLang, SortBy},
+};
 
 /// Search parameters for searching a SpatioTemporal Asset Catalog.
 #[serde_with::serde_as]
@@ -10,6 +13,10 @@ use crate::common::{Bbox, Datetime};
 pub struct SearchParams {
     pub limit: Option<u64>,
     pub offset: Option<u64>,
+    /// Continuation token of the next page
+    #[serde(default)]
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub token: Option<Cursor>,
     #[serde(default)]
     #[serde_as(as = "Option<DisplayFromStr>")]
     pub bbox: Option<Bbox>,
@@ -25,6 +32,16 @@ pub struct SearchParams {
     #[serde(default)]
     #[serde_as(as = "Option<StringWithSeparator::<CommaSeparator, String>>")]
     pub collections: Option<Vec<String>>,
+    /// Sort keys, e.g. `-properties.datetime,+id`
+    #[serde(default)]
+    #[serde_as(as = "Option<StringWithSeparator::<CommaSeparator, SortBy>>")]
+    pub sortby: Option<Vec<SortBy>>,
+    pub filter: Option<String>,
+    #[serde(default, rename = "filter-lang")]
+    pub filter_lang: Option<FilterLang>,
+    #[serde(default, rename = "filter-crs")]
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub filter_crs: Option<Crs>,
 }
 
 impl SearchParams {
@@ -82,6 +99,27 @@ impl SearchParams {
         self.collections = Some(collections.into_iter().map(|c| c.to_string()).collect());
         self
     }
+
+    /// Set the `sortby` property
+    pub fn with_sortby(mut self, sortby: Vec<SortBy>) -> Self {
+        self.sortby = Some(sortby);
+        self
+    }
+
+    /// Set the `filter` property
+    pub fn with_filter(mut self, filter: impl ToString, filter_lang: FilterLang) -> Self {
+        self.filter = Some(filter.to_string());
+        self.filter_lang = Some(filter_lang);
+        self
+    }
+
+    /// Parse the `filter` parameter according to the `filter-lang`
+    pub fn filter_expr(&self) -> Result<Option<Expr>, String> {
+        self.filter
+            .as_ref()
+            .map(|filter| Expr::parse(filter, &self.filter_lang.clone().unwrap_or_default()))
+            .transpose()
+    }
 }
 
 /// Search body for searching a SpatioTemporal Asset Catalog.
@@ -90,6 +128,9 @@ impl SearchParams {
 pub struct SearchBody {
     pub limit: Option<u64>,
     pub offset: Option<u64>,
+    #[serde(default)]
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub token: Option<Cursor>,
     pub bbox: Option<Bbox>,
     #[serde(default)]
     #[serde_as(as = "Option<DisplayFromStr>")]
@@ -100,18 +141,87 @@ pub struct SearchBody {
     pub ids: Option<Vec<String>>,
     #[serde(default)]
     pub collections: Option<Vec<String>>,
+    #[serde(default)]
+    pub sortby: Option<Vec<SortBy>>,
+    /// Filter expression, either a CQL2-JSON object or a CQL2-Text string
+    #[serde(default)]
+    pub filter: Option<serde_json::Value>,
+    #[serde(default, rename = "filter-lang")]
+    pub filter_lang: Option<FilterLang>,
+    #[serde(default, rename = "filter-crs")]
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub filter_crs: Option<Crs>,
 }
 
 impl From<SearchBody> for SearchParams {
     fn from(body: SearchBody) -> Self {
+        // the body defaults to `cql2-json` unless the filter is a string
+        let (filter, filter_lang) = match body.filter {
+            Some(serde_json::Value::String(filter)) => (Some(filter), body.filter_lang),
+            Some(filter) => (
+                Some(filter.to_string()),
+                body.filter_lang.or(Some(FilterLang::CqlJson)),
+            ),
+            None => (None, body.filter_lang),
+        };
+
         SearchParams {
             limit: body.limit,
             offset: body.offset,
+            token: body.token,
             bbox: body.bbox,
             datetime: body.datetime,
             intersects: body.intersects,
             ids: body.ids,
             collections: body.collections,
+            sortby: body.sortby,
+            filter,
+            filter_lang,
+            filter_crs: body.filter_crs,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::features::FilterLang;
+
+    use super::{SearchBody, SearchParams};
+
+    #[test]
+    fn body_filter() {
+        let body: SearchBody = serde_json::from_value(json!({
+            "filter": { "op": "=", "args": [{ "property": "id" }, "a"] }
+        }))
+        .unwrap();
+        let params = SearchParams::from(body);
+        assert_eq!(params.filter_lang, Some(FilterLang::CqlJson));
+        assert!(params.filter_expr().unwrap().is_some());
+
+        let body: SearchBody = serde_json::from_value(json!({
+            "filter": "id = 'a'",
+            "filter-lang": "cql2-text"
+        }))
+        .unwrap();
+        let params = SearchParams::from(body);
+        assert_eq!(params.filter.as_deref(), Some("id = 'a'"));
+        assert!(params.filter_expr().unwrap().is_some());
+    }
+
+    #[test]
+    fn body_sortby() {
+        let body: SearchBody = serde_json::from_value(json!({
+            "sortby": [
+                { "field": "properties.datetime", "direction": "desc" },
+                { "field": "id" }
+            ]
+        }))
+        .unwrap();
+        let params = SearchParams::from(body);
+        let sortby = params.sortby.as_ref().unwrap();
+        assert_eq!(sortby[0].to_string(), "-properties.datetime");
+        assert_eq!(sortby[1].to_string(), "+id");
+    }
+}
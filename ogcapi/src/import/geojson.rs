@@ -28,6 +28,7 @@ pub async fn load(args: Args) -> anyhow::Result<()> {
                         .try_into()
                         .unwrap_or_else(|_| [-180.0, -90.0, 180.0, 90.0].into())],
                     crs: Crs::default(),
+                    ..Default::default()
                 }),
                 ..Default::default()
             })
@@ -48,6 +49,10 @@ pub async fn load(args: Args) -> anyhow::Result<()> {
 
     bulk_load_features(&collection.id, &geojson.features, &db.pool).await?;
 
+    if geojson.bbox.is_none() {
+        db.update_extent(&collection.id, false).await?;
+    }
+
     // stats
     let elapsed = now.elapsed().as_millis() as f64 / 1000.0;
     tracing::info!(
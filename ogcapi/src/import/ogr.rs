@@ -66,7 +66,8 @@ pub async fn load(mut args: Args) -> Result<(), anyhow::Error> {
         let transform = CoordTransform::new(&spatial_ref_src, &spatial_ref_dst)?;
 
         // Create collection
-        let storage_crs = Crs::from_srid(spatial_ref_dst.auth_code()?);
+        let storage_srid = spatial_ref_dst.auth_code()?;
+        let storage_crs = Crs::from_srid(storage_srid);
 
         let collection = Collection {
             id: args.collection.to_owned(),
@@ -92,6 +93,7 @@ pub async fn load(mut args: Args) -> Result<(), anyhow::Error> {
                                 .auth_code()
                                 .map(Crs::from_srid)
                                 .unwrap_or_default(),
+                            ..Default::default()
                         }),
                         temporal: None,
                     }
@@ -133,7 +135,7 @@ pub async fn load(mut args: Args) -> Result<(), anyhow::Error> {
                 .await?;
             sqlx::query("SELECT AddGeometryColumn ('items', $1, 'geom', $2, $3, $4)")
                 .bind(&collection.id)
-                .bind(storage_crs.as_srid())
+                .bind(storage_srid)
                 .bind(geometry_type)
                 .bind(dimensions)
                 .execute(&db.pool)
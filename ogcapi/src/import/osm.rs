@@ -83,6 +83,8 @@ pub async fn load(args: Args) -> Result<(), anyhow::Error> {
 
     tx.commit().await?;
 
+    db.update_extent(&collection.id, false).await?;
+
     Ok(())
 }
 